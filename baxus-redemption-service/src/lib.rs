@@ -1,12 +1,194 @@
-use anchor_lang::{prelude::*, solana_program::entrypoint_deprecated::ProgramResult};
-use anchor_spl::token::{TokenAccount, Token, Mint};
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::memo::Memo;
+use anchor_spl::token_interface::{TokenAccount, TokenInterface, Mint};
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        cpi_guard::CpiGuard, default_account_state::DefaultAccountState, interest_bearing_mint::InterestBearingConfig,
+        metadata_pointer::MetadataPointer, non_transferable::NonTransferable, transfer_hook, BaseStateWithExtensions, StateWithExtensions,
+    },
+    onchain::invoke_transfer_checked,
+    state::{Account as TokenAccountState, AccountState, Mint as MintState},
+};
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::{get_stack_height, AccountMeta, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use pyth_sdk_solana::Price;
+use spl_token_metadata_interface::state::TokenMetadata;
+use crate::program::BaxusRedemptionService;
+use std::convert::TryFrom;
+use std::str::FromStr;
 
 // You must be sure to update declare_id to match the actual runtime ID
 declare_id!("AuRbLaNg1BnPbu9d9sNM6hVTLAnyNBZVkdHCWXX14csw");
 
+// PDA seeds, exported in the IDL so clients don't have to hard-code them
+
+// RedemptionInfo PDA, after the asset's mint. The escrow account is seeded by the mint alone, so it has no seed constant
+#[constant]
+pub const REDEMPTION_SEED: &[u8] = b"redemption";
+
+// The program's single Config PDA
+#[constant]
+pub const CONFIG_SEED: &[u8] = b"config";
+
+// The program's single AuditLog PDA
+#[constant]
+pub const AUDIT_LOG_SEED: &[u8] = b"audit_log";
+
+// StatusIndex PDAs, followed by the status's discriminant
+#[constant]
+pub const STATUS_INDEX_SEED: &[u8] = b"status_index";
+
+// StatusIndexPage PDAs, followed by the status's discriminant and the page number
+#[constant]
+pub const STATUS_INDEX_PAGE_SEED: &[u8] = b"status_index_page";
+
+// Invoice PDA, after the RedemptionInfo's address
+#[constant]
+pub const INVOICE_SEED: &[u8] = b"invoice";
+
+// Appraisal PDA, after the asset's mint
+#[constant]
+pub const APPRAISAL_SEED: &[u8] = b"appraisal";
+
+// The program's single DutyRates PDA
+#[constant]
+pub const DUTY_RATES_SEED: &[u8] = b"duty_rates";
+
+// FeeCurrency PDAs, followed by the payment mint
+#[constant]
+pub const FEE_CURRENCY_SEED: &[u8] = b"fee_currency";
+
+// CollectionConfig PDAs, followed by the collection
+#[constant]
+pub const COLLECTION_CONFIG_SEED: &[u8] = b"collection_config";
+
+// Partner PDAs, followed by the partner ID in little-endian bytes
+#[constant]
+pub const PARTNER_SEED: &[u8] = b"partner";
+
+// RedemptionReceipt PDAs, followed by the asset's mint and the opening timestamp in little-endian bytes
+#[constant]
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+
+// ContactReveal PDA, after the RedemptionInfo's address
+#[constant]
+pub const CONTACT_SEED: &[u8] = b"contact";
+
+// PDA that is the freeze authority of asset mints, and thaws the escrow and customer token accounts of mints that create them frozen
+#[constant]
+pub const FREEZE_AUTHORITY_SEED: &[u8] = b"freeze_authority";
+
+// PDA the treasury approves as a delegate on its fee account to fund on-time rebates
+#[constant]
+pub const REBATE_AUTHORITY_SEED: &[u8] = b"rebate_authority";
+
+// CustodyLog PDA, after the RedemptionInfo's address
+#[constant]
+pub const CUSTODY_LOG_SEED: &[u8] = b"custody_log";
+
+// ConditionLog PDA, after the RedemptionInfo's address
+#[constant]
+pub const CONDITION_LOG_SEED: &[u8] = b"condition_log";
+
+// Escrow token account PDA for an attached certificate, after the certificate's mint
+#[constant]
+pub const ATTACHMENT_ESCROW_SEED: &[u8] = b"attachment_escrow";
+
+// The program's single Accounting PDA
+#[constant]
+pub const ACCOUNTING_SEED: &[u8] = b"accounting";
+
+// CustomerRegistry PDAs, preceded by the customer's key
+#[constant]
+pub const CUSTOMER_REGISTRY_SEED: &[u8] = b"customer_registry";
+
+// EscrowLink PDAs, preceded by the escrow token account's key
+#[constant]
+pub const ESCROW_LINK_SEED: &[u8] = b"escrow_link";
+
+// BurnApproval PDAs, preceded by the redemption_info key
+#[constant]
+pub const BURN_APPROVAL_SEED: &[u8] = b"burn_approval";
+
+// Collateral token account PDAs, preceded by the redemption_info key
+#[constant]
+pub const COLLATERAL_SEED: &[u8] = b"collateral";
+
+// The program's single RedemptionSnapshot PDA
+#[constant]
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+
+// The program's single ProofOfReserve PDA
+#[constant]
+pub const PROOF_OF_RESERVE_SEED: &[u8] = b"proof_of_reserve";
+
+// The program's single RedemptionCounter PDA
+#[constant]
+pub const REDEMPTION_COUNTER_SEED: &[u8] = b"redemption_counter";
+
+// GuardianSet PDAs, preceded by the redemption_info key
+#[constant]
+pub const GUARDIAN_SET_SEED: &[u8] = b"guardians";
+
+// RecoveryRequest PDAs, preceded by the redemption_info key
+#[constant]
+pub const RECOVERY_SEED: &[u8] = b"recovery";
+
+// The PriorityIndex PDA
+#[constant]
+pub const PRIORITY_INDEX_SEED: &[u8] = b"priority_index";
+
+// MockPriceFeed PDAs, followed by the priced mint
+#[constant]
+pub const MOCK_PRICE_SEED: &[u8] = b"mock_price";
+
+// The program's single InventoryAttestation PDA
+#[constant]
+pub const INVENTORY_ATTESTATION_SEED: &[u8] = b"inventory_attestation";
+
+// RiskScore PDAs, preceded by the customer's wallet
+#[constant]
+pub const RISK_SCORE_SEED: &[u8] = b"risk_score";
+
+// BuybackPayout PDAs and their escrowed fee mint token accounts, followed by the asset's mint and the bought-back redemption's opening timestamp
+// in little-endian bytes, like receipts
+#[constant]
+pub const BUYBACK_PAYOUT_SEED: &[u8] = b"buyback_payout";
+#[constant]
+pub const BUYBACK_ESCROW_SEED: &[u8] = b"buyback_escrow";
+
+// The program's single FulfillmentReputation PDA
+#[constant]
+pub const REPUTATION_SEED: &[u8] = b"reputation";
+
+// CustomerProfile PDAs, preceded by the customer's key
+#[constant]
+pub const CUSTOMER_PROFILE_SEED: &[u8] = b"customer_profile";
+
+// The Metaplex Token Metadata program, and the seed its metadata PDAs start with. We only ever read its metadata accounts, so we don't depend on
+// its crate
+pub mod token_metadata_program {
+    anchor_lang::declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+}
+pub const TOKEN_METADATA_SEED: &[u8] = b"metadata";
+pub const TOKEN_METADATA_EDITION_SEED: &[u8] = b"edition";
+
+// The Wen New Standard program, which is the transfer hook of every WNS mint. We only call its approve_transfer instruction, so we don't
+// depend on its crate either
+pub mod wns_program {
+    anchor_lang::declare_id!("wns1gDLt8fgLcGhWi5MqAqgXpwEP1JftKE9eZnXS1HM");
+}
+// Number of accounts WNS's approve_transfer takes: payer, authority, mint, approve account, payment mint, distribution token account,
+// authority token account, distribution account, and the system, distribution, token and associated token programs
+pub const WNS_APPROVE_TRANSFER_ACCOUNTS: usize = 12;
+
 // On the Solana side of things, the BAXUS redemption service will consist of transferring an existing token account's NFT to a BAXUS controlled escrow account,
 // where it will be held while the physical asset is shipped to the physical owner
-// The BAXUS escrow account will be created for this transaction and will live at a PDA - the customer will fund the creation of this account
+// The BAXUS escrow account will be created for this transaction and will live at a PDA - the customer (or a payer for them) will fund the creation of this account
 //
 // When the physical asset has been delivered and signed for by the physical owner, the NFT will be burned (this is an existing function in the SPL Token 
 // library, and therefore that functionality probably doesn't need to be created here) and the BAXUS escrow account used to hold it will be closed (again,
@@ -19,227 +201,8452 @@ declare_id!("AuRbLaNg1BnPbu9d9sNM6hVTLAnyNBZVkdHCWXX14csw");
 // 3) Burn Asset Token      - if the customer verifies identity and the asset is delivered to them, the asset token is burned and the escrow and redemption info accounts are closed
 //
 // The existing token account will be called customer_token_account
-// The customer account used to fund the escrow account will be called customer_payment_account
+// The customer account that owns customer_token_account will be called customer_payment_account
 // The BAXUS escrow account will be called baxus_escrow_account
 
 #[program]
 pub mod baxus_redemption_service {
 
     use super::*;
-    pub fn initialize_redemption(ctx: Context<InitializeRedemption>) -> ProgramResult {
-        let redemption_info = &mut ctx.accounts.redemption_info;
-        redemption_info.customer_token_account = ctx.accounts.customer_token_account.key();
-        redemption_info.customer_payment_account = ctx.accounts.customer_payment_account.key();
-        redemption_info.escrow_bump = *ctx.bumps.get("baxus_escrow_account").unwrap();
-        redemption_info.redemption_bump = *ctx.bumps.get("redemption_info").unwrap();
+    // Opens a Standard redemption, insured only if the customer's profile opts in. Arguments added since live on initialize_redemption_v2, and
+    // this keeps working with their defaults until the admin disables v1 instructions in the config
+    pub fn initialize_redemption<'info>(ctx: Context<'_, '_, '_, 'info, InitializeRedemption<'info>>, contact_commitment: [u8; 32]) -> Result<()> {
+        require!(!ctx.accounts.config.v1_disabled, ErrorCode::InstructionVersionDisabled);
 
-        anchor_spl::token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(), 
-                anchor_spl::token::Transfer {
-                    from: ctx.accounts.customer_token_account.to_account_info(),
-                    to: ctx.accounts.baxus_escrow_account.to_account_info(),
-                    authority: ctx.accounts.customer_payment_account.to_account_info(),
-                }), 
-            1,
+        initialize_redemption_v2(ctx, contact_commitment, None, ServiceTier::Standard, [0; 32])
+    }
+
+    // Take the customer's token into escrow and open the redemption. Failed metadata and collection checks only turn the asset away in strict
+    // mode, and are reported in a ValidationWarning event otherwise. A customer account frozen by our freeze authority PDA is thawed first,
+    // and WNS mints take the WNS program and its approve_transfer accounts as remaining accounts, ahead of the hook's extra accounts
+    // idempotency_key is any value the client picks for this redemption, or all zeroes to make retries fail like v1, and insure is None to
+    // go with the customer's profile
+    pub fn initialize_redemption_v2<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeRedemption<'info>>,
+        contact_commitment: [u8; 32],
+        insure: Option<bool>,
+        service_tier: ServiceTier,
+        idempotency_key: [u8; 32],
+    ) -> Result<()> {
+        check_cpi_caller(&ctx.accounts.config, &ctx.accounts.instructions)?;
+        open_redemption(ctx, contact_commitment, insure, service_tier, idempotency_key, Pubkey::default())
+    }
+
+    // initialize_redemption_v2 for whitelisted marketplace programs to call through a CPI in the same transaction as a sale, so the buyer can
+    // buy a BAXUS NFT and lock it for redemption atomically. The buyer still signs as the customer, since their signature carries through
+    // the CPI, and the config's CPI policy doesn't apply - only the marketplace whitelist does
+    pub fn initialize_redemption_for<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeRedemption<'info>>,
+        contact_commitment: [u8; 32],
+        insure: Option<bool>,
+        service_tier: ServiceTier,
+        idempotency_key: [u8; 32],
+    ) -> Result<()> {
+        let marketplace = check_marketplace_caller(&ctx.accounts.config, &ctx.accounts.instructions)?;
+
+        open_redemption(ctx, contact_commitment, insure, service_tier, idempotency_key, marketplace)
+    }
+
+    // Run every check initialize_redemption would, without moving anything, so front-ends can simulate this before asking the customer to sign
+    // the real deposit
+    pub fn validate_redemption_request(ctx: Context<ValidateRedemptionRequest>) -> Result<()> {
+        check_redemption_request(
+            &ctx.accounts.config,
+            &ctx.accounts.token_mint_account,
+            &ctx.accounts.customer_token_account,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.baxus_escrow_account.key(),
+            &ctx.accounts.freeze_authority.key(),
         )?;
+        check_risk_score(&ctx.accounts.config, ctx.accounts.risk_score.as_deref())?;
 
         Ok(())
     }
     
-    pub fn return_asset_token(ctx: Context<ReturnAssetToken>) -> ProgramResult {
+    // Hand the asset token back to the customer. Only the operator signs, so BAXUS (or the partner) pays for the transaction and the receipt,
+    // while the customer gets back the rent of the escrow and redemption accounts, and the return is recorded in the audit log. A rejected
+    // redemption goes back with finalize_rejection instead, so the customer keeps their chance to appeal
+    pub fn return_asset_token<'info>(ctx: Context<'_, '_, '_, 'info, ReturnAssetToken<'info>>) -> Result<()> {
+        require!(ctx.accounts.redemption_info.rejection.is_none(), ErrorCode::RedemptionRejected);
 
-        anchor_spl::token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(), 
-                anchor_spl::token::Transfer {
-                    from: ctx.accounts.baxus_escrow_account.to_account_info(),
-                    to: ctx.accounts.customer_token_account.to_account_info(),
-                    authority: ctx.accounts.baxus_escrow_account.to_account_info()
-                }, 
-                &[&[
-                    ctx.accounts.token_mint_account.key().as_ref(), 
-                    &[ctx.accounts.redemption_info.escrow_bump],
-                ]]
-            ), 
-            1)?;
+        let audit_log = ctx.accounts.audit_log.as_ref().ok_or(ErrorCode::AuditLogRequired)?;
+        audit_log.load_mut()?.append(ctx.accounts.authority.key(), AuditAction::ForceReturn, Clock::get()?.slot);
 
-        anchor_spl::token::close_account(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(), 
-                anchor_spl::token::CloseAccount {
-                    account: ctx.accounts.baxus_escrow_account.to_account_info(),
-                    destination: ctx.accounts.customer_payment_account.to_account_info(),
-                    authority: ctx.accounts.baxus_escrow_account.to_account_info(),
-                }, 
-                &[&[
-                    ctx.accounts.token_mint_account.key().as_ref(), 
-                    &[ctx.accounts.redemption_info.escrow_bump],
-                ]]
-            ),
+        return_to_customer(ctx, RedemptionOutcome::Returned)
+    }
+
+    // Turn a redemption down before it ships. The token stays in escrow and the redemption can't move on while the customer has the config's
+    // appeal_window_secs to appeal - after that, or once an appeal is upheld, finalize_rejection hands the token back
+    pub fn reject_redemption(ctx: Context<RejectRedemption>, reason: RejectionReason) -> Result<()> {
+        check_operator(&ctx.accounts.config, &ctx.accounts.redemption_info, ctx.accounts.partner.as_deref(), &ctx.accounts.authority.key())?;
+
+        let now = ctx.accounts.config.clock()?.unix_timestamp;
+        let redemption_key = ctx.accounts.redemption_info.key();
+        remove_priority(&mut ctx.accounts.redemption_info, ctx.accounts.priority_index.as_mut(), &redemption_key)?;
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        redemption_info.rejection = Some(reason);
+        redemption_info.rejected_at = now;
+        redemption_info.appeal_resolved = None;
+
+        Ok(())
+    }
+
+    // The customer appeals a rejection within the appeal window, committing to a hash of their evidence (kept off-chain) and posting the
+    // config's appeal bond into redemption_info. The redemption is frozen again until compliance resolves the appeal, and a redemption can
+    // only be appealed once
+    pub fn appeal_rejection(ctx: Context<AppealRejection>, evidence_hash: [u8; 32]) -> Result<()> {
+        require!(evidence_hash != [0; 32], ErrorCode::MissingAppealEvidence);
+        require!(
+            ctx.accounts.config.clock()?.unix_timestamp < ctx.accounts.redemption_info.rejected_at.saturating_add(ctx.accounts.config.appeal_window_secs),
+            ErrorCode::AppealWindowClosed
+        );
+
+        let bond = ctx.accounts.config.appeal_bond_lamports;
+        if bond > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.customer_payment_account.to_account_info(),
+                        to: ctx.accounts.redemption_info.to_account_info(),
+                    }),
+                bond,
+            )?;
+        }
+
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        redemption_info.appeal_evidence_hash = evidence_hash;
+        redemption_info.frozen_by_compliance = true;
+        redemption_info.appeal_bond_lamports = bond;
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.customer_payment_account.key(), AuditAction::RejectionAppeal, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Compliance and the admin settle an appeal, unfreezing the redemption and recording their decision. If the rejection is upheld it can be
+    // finalized straight away, without waiting out the rest of the window; if not, the rejection is cleared and the redemption carries on where
+    // it was. unfreeze refuses while the appeal is pending, so this is the only way to lift its freeze
+    pub fn resolve_appeal(ctx: Context<ResolveAppeal>, upheld: bool) -> Result<()> {
+        // The bond sits on top of redemption_info's rent, so the account stays rent exempt once it's moved out directly
+        let bond = ctx.accounts.redemption_info.appeal_bond_lamports;
+        if bond > 0 {
+            let redemption_info = ctx.accounts.redemption_info.to_account_info();
+            **redemption_info.try_borrow_mut_lamports()? -= bond;
+            if upheld {
+                **ctx.accounts.treasury.try_borrow_mut_lamports()? += bond;
+            } else {
+                **ctx.accounts.customer_payment_account.try_borrow_mut_lamports()? += bond;
+            }
+        }
+
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        redemption_info.appeal_bond_lamports = 0;
+        redemption_info.frozen_by_compliance = false;
+        redemption_info.appeal_resolved = Some(upheld);
+        if !upheld {
+            redemption_info.rejection = None;
+            redemption_info.rejected_at = 0;
+            redemption_info.appeal_evidence_hash = [0; 32];
+        }
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::AppealResolution, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // return_asset_token for a rejected redemption, once the appeal window has closed without an appeal or resolve_appeal upheld the rejection,
+    // recording why it was rejected on its receipt
+    pub fn finalize_rejection<'info>(ctx: Context<'_, '_, '_, 'info, ReturnAssetToken<'info>>) -> Result<()> {
+        let redemption_info = &ctx.accounts.redemption_info;
+        let reason = redemption_info.rejection.ok_or(ErrorCode::RedemptionNotRejected)?;
+        if redemption_info.appeal_evidence_hash != [0; 32] {
+            // An overturned appeal clears the rejection, so an appealed rejection that still stands is either upheld or waiting on a decision
+            require!(redemption_info.appeal_resolved == Some(true), ErrorCode::AppealUnresolved);
+        } else {
+            require!(
+                ctx.accounts.config.clock()?.unix_timestamp >= redemption_info.rejected_at.saturating_add(ctx.accounts.config.appeal_window_secs),
+                ErrorCode::AppealWindowOpen
+            );
+        }
+
+        return_to_customer(ctx, RedemptionOutcome::Rejected { reason })
+    }
+
+    // Burn the delivered asset's token, leaving a receipt - for print editions it records the edition number, and their Metaplex accounts are
+    // left alone so the edition number can never be printed again. On-time rebates and creator fee shares are paid out of the treasury's fee
+    // account through the rebate_authority delegate, so the treasury doesn't have to sign
+    pub fn burn_asset_token<'info>(ctx: Context<'_, '_, '_, 'info, BurnAssetToken<'info>>) -> Result<()>{
+        check_operator(&ctx.accounts.config, &ctx.accounts.redemption_info, ctx.accounts.partner.as_deref(), &ctx.accounts.authority.key())?;
+        require!(
+            ctx.accounts.redemption_info.burn_consented() || ctx.accounts.redemption_info.cooldown_elapsed(&ctx.accounts.config, ctx.accounts.config.clock()?.slot),
+            ErrorCode::TransitionCooldown
+        );
+        require!(
+            ctx.accounts.redemption_info.storage_fees_cleared(&ctx.accounts.config, ctx.accounts.config.clock()?.unix_timestamp),
+            ErrorCode::StorageFeesOutstanding
+        );
+
+        let redemption_key = ctx.accounts.redemption_info.key();
+        ctx.accounts.status_index.remove(&mut ctx.accounts.status_index_page, &redemption_key);
+        deregister_customer_redemption(&ctx.accounts.redemption_info, ctx.accounts.customer_registry.as_mut(), &redemption_key)?;
+
+        if ctx.accounts.redemption_info.brand_authority != Pubkey::default() {
+            require!(ctx.accounts.burn_approval.is_some(), ErrorCode::BrandApprovalRequired);
+        }
+        if ctx.accounts.redemption_info.enhanced_approval {
+            require!(ctx.accounts.enhanced_approver.is_some(), ErrorCode::EnhancedApprovalRequired);
+        }
+
+        // The edition account is the mint's edition PDA, which only Metaplex can have created - if it doesn't own it, the asset isn't a
+        // Metaplex asset and so can't be a print
+        let edition = &ctx.accounts.edition;
+        let print_edition = if *edition.owner == token_metadata_program::ID { print_edition_of(edition)? } else { None };
+        let (master_edition, edition_number) = print_edition.unwrap_or_default();
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.record(
+            ctx.accounts.token_mint_account.key(),
+            &ctx.accounts.redemption_info,
+            RedemptionOutcome::Burned,
+            ctx.accounts.config.clock()?.unix_timestamp,
+            ctx.bumps.receipt,
+        );
+        receipt.edition_number = edition_number;
+        receipt.master_edition = master_edition;
+        receipt.interest_rate = interest_rate_of(&ctx.accounts.token_mint_account.to_account_info())?;
+
+        // Donations stop if the admin takes the charity out of the config
+        let donation_bps = if ctx.accounts.config.charity != Pubkey::default() { ctx.accounts.redemption_info.donation_bps } else { 0 };
+        let mut donated_usd_cents = 0;
+
+        let redemption_info = &ctx.accounts.redemption_info;
+        let rebate_bps = if redemption_info.on_time() { ctx.accounts.config.on_time_rebate_bps } else { 0 };
+        if rebate_bps > 0 || redemption_info.creator_share_bps > 0 {
+            let (
+                Some(invoice),
+                Some(fee_mint),
+                Some(treasury_fee_account),
+                Some(customer_fee_account),
+                Some(rebate_authority),
+                Some(fee_token_program),
+                Some(accounting),
+            ) = (
+                &ctx.accounts.invoice,
+                &ctx.accounts.fee_mint,
+                &ctx.accounts.treasury_fee_account,
+                &ctx.accounts.customer_fee_account,
+                &ctx.accounts.rebate_authority,
+                &ctx.accounts.fee_token_program,
+                &mut ctx.accounts.accounting,
+            ) else {
+                return err!(ErrorCode::PayoutAccountsRequired);
+            };
+            let treasury = TreasuryPayer {
+                fee_token_program: fee_token_program.to_account_info(),
+                fee_mint: fee_mint.to_account_info(),
+                decimals: fee_mint.decimals,
+                treasury_fee_account: treasury_fee_account.to_account_info(),
+                rebate_authority: rebate_authority.to_account_info(),
+                rebate_authority_bump: ctx.bumps.rebate_authority,
+            };
+            let now = ctx.accounts.config.clock()?.unix_timestamp;
+
+            // fees_credited_usd_cents is the total fees the customer has paid on the invoice
+            let rebate_usd_cents = (invoice.fees_credited_usd_cents as u128 * rebate_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+            donated_usd_cents = (rebate_usd_cents as u128 * donation_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+            if donated_usd_cents > 0 {
+                let charity_fee_account = ctx.accounts.charity_fee_account.as_ref().ok_or(ErrorCode::DonationAccountsRequired)?;
+                treasury.pay(charity_fee_account.to_account_info(), donated_usd_cents)?;
+            }
+            treasury.pay(customer_fee_account.to_account_info(), rebate_usd_cents - donated_usd_cents)?;
+            accounting.current(now).refunds_usd_cents += rebate_usd_cents;
+
+            let creator_share_usd_cents =
+                (invoice.fees_credited_usd_cents as u128 * redemption_info.creator_share_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+            if creator_share_usd_cents > 0 {
+                let asset_metadata = ctx.accounts.asset_metadata.as_ref().ok_or(ErrorCode::AssetMetadataRequired)?;
+                // The creators' fee accounts follow the escrowed certificates' accounts
+                let escrowed_attachments = redemption_info.attachments.iter().filter(|attachment| attachment.escrowed).count();
+                let creator_fee_accounts = ctx.remaining_accounts.get(2 * escrowed_attachments..).unwrap_or_default();
+                let paid = pay_creator_shares(
+                    &treasury,
+                    &verified_creators(asset_metadata)?,
+                    creator_fee_accounts,
+                    &fee_mint.key(),
+                    creator_share_usd_cents,
+                )?;
+                accounting.current(now).payouts_usd_cents += paid;
+            }
+        }
+
+        // The bond is held in redemption_info, which this program owns, so the donated share can be moved out of it directly
+        let redemption_info = ctx.accounts.redemption_info.to_account_info();
+        let bond = ctx.accounts.redemption_info.bond_lamports.min(redemption_info.lamports());
+        let donated_lamports = (bond as u128 * donation_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        if donated_lamports > 0 {
+            let charity = ctx.accounts.charity.as_ref().ok_or(ErrorCode::DonationAccountsRequired)?;
+            **redemption_info.try_borrow_mut_lamports()? -= donated_lamports;
+            **charity.try_borrow_mut_lamports()? += donated_lamports;
+            ctx.accounts.redemption_info.bond_lamports -= donated_lamports;
+        }
+
+        if donated_usd_cents > 0 || donated_lamports > 0 {
+            ctx.accounts.receipt.donated_usd_cents = donated_usd_cents;
+            ctx.accounts.receipt.donated_lamports = donated_lamports;
+
+            let redemption_info = &mut ctx.accounts.redemption_info;
+            emit!(DonationMade {
+                redemption_info: redemption_key,
+                redemption_id: redemption_info.redemption_id,
+                event_sequence: redemption_info.next_event_sequence(),
+                charity: ctx.accounts.config.charity,
+                donated_usd_cents,
+                donated_lamports,
+            });
+        }
+
+        release_escrow(
+            EscrowRelease::Burn,
+            &mut ctx.accounts.baxus_escrow_account,
+            &ctx.accounts.token_mint_account,
+            ctx.accounts.redemption_info.escrow_bump,
+            RentRefund {
+                redemption_info: ctx.accounts.redemption_info.to_account_info(),
+                customer: ctx.accounts.customer_payment_account.to_account_info(),
+                treasury: ctx.accounts.treasury.as_ref().map(|treasury| treasury.to_account_info()),
+                treasury_share_bps: ctx.accounts.config.treasury_rent_share_bps,
+            },
+            &ctx.accounts.token_program,
+            &[],
+        )?;
+
+        burn_escrowed_attachments(
+            &ctx.accounts.redemption_info,
+            ctx.remaining_accounts,
+            &ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.certificate_token_program.as_ref().map(|program| program.to_account_info()).as_ref(),
+            &ctx.accounts.customer_payment_account.to_account_info(),
         )?;
 
+        // Accounts still in an older, smaller layout can't hold the archived record, so they're closed whatever the config says
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        if ctx.accounts.config.archive_retention_secs > 0 && redemption_info.to_account_info().data_len() >= 8 + RedemptionInfo::INIT_SPACE {
+            redemption_info.archived_at = ctx.accounts.config.clock()?.unix_timestamp;
+        } else {
+            redemption_info.close(ctx.accounts.customer_payment_account.to_account_info())?;
+        }
+
         Ok(())
     }
 
-    pub fn burn_asset_token(ctx: Context<BurnAssetToken>) -> ProgramResult{
+    // The customer rates a burned redemption from one to five stars, committing to their comment with comment_hash, or all zeroes for none
+    pub fn submit_feedback(ctx: Context<SubmitFeedback>, rating: u8, comment_hash: [u8; 32]) -> Result<()> {
+        require!((1..=MAX_RATING).contains(&rating), ErrorCode::InvalidRating);
 
-        anchor_spl::token::burn(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(), 
-                anchor_spl::token::Burn {
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.rating = rating;
+        receipt.comment_hash = comment_hash;
+        receipt.rated_at = ctx.accounts.config.clock()?.unix_timestamp;
+
+        let reputation = &mut ctx.accounts.reputation;
+        reputation.rating_counts[rating as usize - 1] += 1;
+        reputation.bump = ctx.bumps.reputation;
+
+        emit!(FeedbackSubmitted {
+            receipt: receipt.key(),
+            token_mint: receipt.token_mint,
+            rating,
+            comment_hash,
+        });
+
+        Ok(())
+    }
+
+    // Resize an existing RedemptionInfo account to the current layout - accounts opened before we right-sized RedemptionInfo were allocated twice the
+    // space they needed, so this hands the excess rent back to the customer, and ops pays for the extra space when an older layout is too short.
+    // The bonds held on top of the rent stay where they are
+    pub fn migrate_redemption_info(ctx: Context<MigrateRedemptionInfo>) -> Result<()> {
+        let redemption_info = ctx.accounts.redemption_info.to_account_info();
+        let space = 8 + RedemptionInfo::INIT_SPACE;
+        let required = Rent::get()?
+            .minimum_balance(space)
+            .saturating_add(ctx.accounts.redemption_info.bond_lamports)
+            .saturating_add(ctx.accounts.redemption_info.appeal_bond_lamports);
+
+        let balance = redemption_info.lamports();
+        if balance > required {
+            **redemption_info.try_borrow_mut_lamports()? -= balance - required;
+            **ctx.accounts.customer_payment_account.try_borrow_mut_lamports()? += balance - required;
+        } else if balance < required {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: redemption_info.clone(),
+                    },
+                ),
+                required - balance,
+            )?;
+        }
+        redemption_info.realloc(space, false)?;
+
+        Ok(())
+    }
+
+    // The ciphertext is encrypted off-chain to config.ops_encryption_key - we can't check it on-chain, but ops can check the decrypted contact
+    // details against the contact_commitment stored at initialization
+    pub fn reveal_contact(ctx: Context<RevealContact>, ciphertext: Vec<u8>) -> Result<()> {
+        check_cpi_caller(&ctx.accounts.config, &ctx.accounts.instructions)?;
+        require!(ciphertext.len() <= MAX_CONTACT_CIPHERTEXT_LEN, ErrorCode::ContactCiphertextTooLong);
+
+        let contact_reveal = &mut ctx.accounts.contact_reveal;
+        contact_reveal.redemption_info = ctx.accounts.redemption_info.key();
+        contact_reveal.customer_payment_account = ctx.accounts.customer_payment_account.key();
+        contact_reveal.ciphertext = ciphertext;
+
+        Ok(())
+    }
+
+    // The customer commits to new contact details, e.g. after moving. Any details they revealed for the old commitment are deleted, so they
+    // can reveal the new ones
+    pub fn update_shipping_commitment(ctx: Context<UpdateShippingCommitment>, contact_commitment: [u8; 32]) -> Result<()> {
+        check_cpi_caller(&ctx.accounts.config, &ctx.accounts.instructions)?;
+        require!(contact_commitment != [0; 32], ErrorCode::NoContactCommitment);
+
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        let previous_commitment = redemption_info.contact_commitment;
+        redemption_info.contact_commitment = contact_commitment;
+
+        emit!(ShippingCommitmentUpdated {
+            redemption_info: redemption_info.key(),
+            redemption_id: redemption_info.redemption_id,
+            event_sequence: redemption_info.next_event_sequence(),
+            previous_commitment,
+            contact_commitment,
+        });
+
+        Ok(())
+    }
+
+    // The customer picks the guardians who can recover the redemption for them, and how many of them have to agree. Guardians can't be
+    // changed while a recovery is pending, since approvals refer to guardians by position
+    pub fn set_guardians(ctx: Context<SetGuardians>, guardians: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        require!(guardians.len() <= MAX_GUARDIANS, ErrorCode::TooManyGuardians);
+        require!(threshold > 0 && threshold as usize <= guardians.len(), ErrorCode::InvalidGuardianThreshold);
+        require!(guardians.iter().enumerate().all(|(i, guardian)| !guardians[..i].contains(guardian)), ErrorCode::DuplicateGuardian);
+
+        let guardian_set = &mut ctx.accounts.guardian_set;
+        guardian_set.guardians = guardians;
+        guardian_set.threshold = threshold;
+        guardian_set.bump = ctx.bumps.guardian_set;
+
+        Ok(())
+    }
+
+    // A guardian approves moving the redemption to a new wallet and token account. The first approval opens the recovery request and starts
+    // the timelock, and every later approval has to be for the same destination
+    pub fn approve_recovery(
+        ctx: Context<ApproveRecovery>,
+        new_customer_payment_account: Pubkey,
+        new_customer_token_account: Pubkey,
+    ) -> Result<()> {
+        let guardian = ctx.accounts.guardian.key();
+        let position = ctx.accounts.guardian_set.guardians.iter().position(|entry| *entry == guardian).ok_or(ErrorCode::NotAGuardian)?;
+
+        let recovery_request = &mut ctx.accounts.recovery_request;
+        if recovery_request.requested_at == 0 {
+            recovery_request.new_customer_payment_account = new_customer_payment_account;
+            recovery_request.new_customer_token_account = new_customer_token_account;
+            recovery_request.proposer = guardian;
+            recovery_request.requested_at = ctx.accounts.config.clock()?.unix_timestamp;
+            recovery_request.bump = ctx.bumps.recovery_request;
+        } else {
+            require!(
+                recovery_request.new_customer_payment_account == new_customer_payment_account
+                    && recovery_request.new_customer_token_account == new_customer_token_account,
+                ErrorCode::RecoveryMismatch
+            );
+        }
+        recovery_request.approvals |= 1 << position;
+
+        Ok(())
+    }
+
+    // The admin moves the redemption to the wallet and token account its guardians approved, once enough of them have and the timelock has
+    // passed. The redemption comes off the old wallet's customer registry and isn't added to the new one's
+    pub fn execute_recovery(ctx: Context<ExecuteRecovery>) -> Result<()> {
+        let recovery_request = &ctx.accounts.recovery_request;
+        require!(
+            recovery_request.approvals.count_ones() >= ctx.accounts.guardian_set.threshold as u32,
+            ErrorCode::RecoveryThresholdNotMet
+        );
+        require!(
+            ctx.accounts.config.clock()?.unix_timestamp >= recovery_request.requested_at.saturating_add(ctx.accounts.config.recovery_timelock_secs),
+            ErrorCode::RecoveryTimelockActive
+        );
+
+        let redemption_key = ctx.accounts.redemption_info.key();
+        deregister_customer_redemption(&ctx.accounts.redemption_info, ctx.accounts.customer_registry.as_mut(), &redemption_key)?;
+
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        let previous_customer = redemption_info.customer_payment_account;
+        redemption_info.in_customer_registry = false;
+        redemption_info.customer_payment_account = recovery_request.new_customer_payment_account;
+        redemption_info.customer_token_account = recovery_request.new_customer_token_account;
+
+        emit!(CustomerRecovered {
+            redemption_info: redemption_key,
+            redemption_id: redemption_info.redemption_id,
+            event_sequence: redemption_info.next_event_sequence(),
+            previous_customer,
+            customer: redemption_info.customer_payment_account,
+        });
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::CustomerRecovery, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // The customer stops a pending recovery of their redemption, which refunds the guardian who opened it
+    pub fn cancel_recovery(_ctx: Context<CancelRecovery>) -> Result<()> {
+        Ok(())
+    }
+
+    // Once the redemption has been closed, the customer can delete their encrypted contact details and reclaim the rent
+    pub fn close_contact_reveal(_ctx: Context<CloseContactReveal>) -> Result<()> {
+        Ok(())
+    }
+
+    // Anyone can expire a redemption that's still sitting in Deposited once its deadline has passed - the token goes back to the customer, but
+    // their bond is slashed to the treasury
+    pub fn expire_redemption<'info>(ctx: Context<'_, '_, '_, 'info, ExpireRedemption<'info>>) -> Result<()> {
+        let redemption_info = &ctx.accounts.redemption_info;
+        require!(
+            redemption_info.expires_at > 0 && ctx.accounts.config.clock()?.unix_timestamp >= redemption_info.expires_at,
+            ErrorCode::RedemptionNotExpired
+        );
+
+        let redemption_key = ctx.accounts.redemption_info.key();
+        ctx.accounts.status_index.remove(&mut ctx.accounts.status_index_page, &redemption_key);
+        remove_priority(&mut ctx.accounts.redemption_info, ctx.accounts.priority_index.as_mut(), &redemption_key)?;
+        deregister_customer_redemption(&ctx.accounts.redemption_info, ctx.accounts.customer_registry.as_mut(), &redemption_key)?;
+        ctx.accounts.receipt.record(
+            ctx.accounts.token_mint_account.key(),
+            &ctx.accounts.redemption_info,
+            RedemptionOutcome::Expired,
+            ctx.accounts.config.clock()?.unix_timestamp,
+            ctx.bumps.receipt,
+        );
+
+        release_escrow(
+            EscrowRelease::Transfer(ctx.accounts.customer_token_account.to_account_info()),
+            &mut ctx.accounts.baxus_escrow_account,
+            &ctx.accounts.token_mint_account,
+            ctx.accounts.redemption_info.escrow_bump,
+            RentRefund {
+                redemption_info: ctx.accounts.redemption_info.to_account_info(),
+                customer: ctx.accounts.customer_payment_account.to_account_info(),
+                treasury: Some(ctx.accounts.treasury.to_account_info()),
+                treasury_share_bps: ctx.accounts.config.treasury_rent_share_bps,
+            },
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+
+        // redemption_info is owned by this program, so we can move the bond out of it directly - the close constraint then refunds the rent.
+        // The account is closed in this instruction, so it doesn't matter if taking the bond leaves it below rent exemption. Whoever expired
+        // the redemption is paid back the receipt's rent out of the bond first
+        let redemption_info = ctx.accounts.redemption_info.to_account_info();
+        let bond = ctx.accounts.redemption_info.bond_lamports.min(redemption_info.lamports());
+        let receipt_rent = ctx.accounts.receipt.to_account_info().lamports().min(bond);
+        if bond > 0 {
+            **redemption_info.try_borrow_mut_lamports()? -= bond;
+            **ctx.accounts.payer.try_borrow_mut_lamports()? += receipt_rent;
+            **ctx.accounts.treasury.try_borrow_mut_lamports()? += bond - receipt_rent;
+        }
+
+        Ok(())
+    }
+
+    // Move a long-dwelling redemption's token from its escrow account into the BAXUS cold vault, and close the escrow account
+    pub fn consolidate_to_cold_vault<'info>(ctx: Context<'_, '_, '_, 'info, ConsolidateToColdVault<'info>>) -> Result<()> {
+        require!(
+            ctx.accounts.config.clock()?.unix_timestamp
+                >= ctx.accounts.redemption_info.status_changed_at.saturating_add(ctx.accounts.config.cold_vault_min_dwell_secs),
+            ErrorCode::DwellTooShort
+        );
+
+        release_escrow(
+            EscrowRelease::Transfer(ctx.accounts.cold_vault_account.to_account_info()),
+            &mut ctx.accounts.baxus_escrow_account,
+            &ctx.accounts.token_mint_account,
+            ctx.accounts.redemption_info.escrow_bump,
+            RentRefund {
+                redemption_info: ctx.accounts.redemption_info.to_account_info(),
+                customer: ctx.accounts.customer_payment_account.to_account_info(),
+                treasury: ctx.accounts.treasury.as_ref().map(|treasury| treasury.to_account_info()),
+                treasury_share_bps: ctx.accounts.config.treasury_rent_share_bps,
+            },
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+
+        ctx.accounts.redemption_info.cold_vault_account = ctx.accounts.cold_vault_account.key();
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.ops_authority.key(), AuditAction::ColdVaultConsolidation, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // The cold vault multisig moves a redemption's token back into a fresh escrow account so it can be returned or burned
+    pub fn recall_from_cold_vault(ctx: Context<RecallFromColdVault>) -> Result<()> {
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: ctx.accounts.cold_vault_account.to_account_info(),
                     mint: ctx.accounts.token_mint_account.to_account_info(),
                     to: ctx.accounts.baxus_escrow_account.to_account_info(),
-                    authority: ctx.accounts.baxus_escrow_account.to_account_info(),
-                }, 
-                &[&[
-                    ctx.accounts.token_mint_account.key().as_ref(), 
-                    &[ctx.accounts.redemption_info.escrow_bump],
-                ]]
-            ), 
-            1)?;
-
-        // Add anchor_spl::token::close() instruction, since you can't use the close attribute in the baxus_escrow_account account
-        anchor_spl::token::close_account(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(), 
-                anchor_spl::token::CloseAccount {
-                    account: ctx.accounts.baxus_escrow_account.to_account_info(),
-                    destination: ctx.accounts.customer_payment_account.to_account_info(),
-                    authority: ctx.accounts.baxus_escrow_account.to_account_info(),
-                }, 
-                &[&[
-                    ctx.accounts.token_mint_account.key().as_ref(), 
-                    &[ctx.accounts.redemption_info.escrow_bump],
-                ]]
-            ),
+                    authority: ctx.accounts.cold_vault_authority.to_account_info(),
+                }),
+            AssetAmount::WHOLE.get(),
+            ctx.accounts.token_mint_account.decimals,
         )?;
 
+        ctx.accounts.redemption_info.cold_vault_account = Pubkey::default();
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.cold_vault_authority.key(), AuditAction::ColdVaultRecall, Clock::get()?.slot);
+
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-// Anchor requires an underscore prefix for any variable name that isn't used in a function
-#[instruction()]
-pub struct InitializeRedemption<'info> {
-    #[account(
-        init, 
-        payer = customer_payment_account, 
-        // We will initialize the redemption_info account to live at a PDA, and we will need to store the bump so that when we call return or burn, we make sure we're using the correct redemption_info
-        seeds = [token_mint_account.key().as_ref(), b"redemption".as_ref()],
-        bump,
-        // Allocate double the space we currently need in case we need to re-deploy with more fields in RedemptionInfo (Solana might allow you to dynamically resize on 
-        // re-deploy, but who knows)
-        // TO DO: Discuss costs of doing that, whether or not we want more than 2* the necessary space, etc etc
-        space = 8 + 2*(32 + 32 + 1 + 1))
-    ]
-    pub redemption_info: Account<'info, RedemptionInfo>,
+    // Move a redemption on to the next status in its lifecycle, and from its old status index into the new one. Redemptions holding collateral
+    // or a verified KYC proof can skip KycApproved. The collection's hook program is told about the transition, and if the memo program is
+    // passed, a short memo is written for the customer's wallet history
+    pub fn advance_status(ctx: Context<AdvanceStatus>) -> Result<()> {
+        check_operator(&ctx.accounts.config, &ctx.accounts.redemption_info, ctx.accounts.partner.as_deref(), &ctx.accounts.authority.key())?;
 
-    #[account(mut, constraint = customer_token_account.mint == token_mint_account.key())]
-    pub customer_token_account: Account<'info, TokenAccount>,
+        let redemption_key = ctx.accounts.redemption_info.key();
+        ctx.accounts.current_status_index.remove(&mut ctx.accounts.current_status_index_page, &redemption_key);
+        ctx.accounts.next_status_index.insert(&mut ctx.accounts.next_status_index_page, redemption_key)?;
+        ctx.accounts.redemption_info.status_index_page = ctx.accounts.next_status_index_page.page;
 
-    #[account(mut)]
-    pub customer_payment_account: Signer<'info>,
+        let clock = ctx.accounts.config.clock()?;
+        let now = clock.unix_timestamp;
+        require!(ctx.accounts.redemption_info.cooldown_elapsed(&ctx.accounts.config, clock.slot), ErrorCode::TransitionCooldown);
+        require!(ctx.accounts.redemption_info.held_since == 0, ErrorCode::RedemptionOnHold);
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        if ctx.accounts.next_status_index.status == RedemptionStatus::Shipped {
+            require!(!redemption_info.enhanced_approval || ctx.accounts.enhanced_approver.is_some(), ErrorCode::EnhancedApprovalRequired);
+            require!(redemption_info.jurisdiction != [0; 2] && redemption_info.invoice_paid, ErrorCode::InvoiceNotPaid);
+            require!(redemption_info.storage_fees_cleared(&ctx.accounts.config, now), ErrorCode::StorageFeesOutstanding);
+            require!(redemption_info.shipment_documents_hash != [0; 32], ErrorCode::ShipmentDocumentsRequired);
+            remove_priority(redemption_info, ctx.accounts.priority_index.as_mut(), &redemption_key)?;
+            // The customer is about to have the bottle, so they can no longer cancel for a breach before it shipped
+            redemption_info.penalty_free_cancellation = false;
+        }
 
-    // We will need to provide the account containing the NFT's mint for the creation of the baxus_escrow_account
-    pub token_mint_account: Account<'info, Mint>,
+        // Bank the time the redemption spent overdue in the status it's leaving, so storage isn't charged for it
+        redemption_info.storage_paused_secs += redemption_info.overdue_secs(&ctx.accounts.config, now);
+        if redemption_info.past_deadline(&ctx.accounts.config, now) {
+            redemption_info.sla_missed = true;
+        }
+        redemption_info.record_transition(ctx.accounts.next_status_index.status, &clock);
+        emit!(redemption_info.status_changed(redemption_key));
+        call_transition_hook(redemption_info, redemption_key, ctx.accounts.hook_program.as_ref())?;
+        write_status_memo(redemption_info, ctx.accounts.memo_program.as_ref())?;
 
-    #[account(
-        init, 
-        payer = customer_payment_account, 
-        // TO DO: Make sure we are using meaningful/scalable seeds and bump
-        seeds = [token_mint_account.key().as_ref()], 
-        bump, 
-        token::mint = token_mint_account,
-        token::authority = baxus_escrow_account)
-    ]
-    pub baxus_escrow_account: Account<'info, TokenAccount>,
+        Ok(())
+    }
 
-    // Include a Token Program account because we need to ask it transfer the NFT from the customer_token_account to the baxus_escrow_account
-    pub token_program: Program<'info, Token>,
+    // Add or change the duty rate for a jurisdiction. Redemptions can only ship to jurisdictions in the table, so duty-free jurisdictions
+    // still need an entry with a zero rate
+    pub fn set_duty_rate(ctx: Context<SetDutyRate>, jurisdiction: [u8; 2], rate_bps: u16) -> Result<()> {
+        require!(rate_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidDutyRate);
 
-    // The Token Program requires that we include a Rent Sysvar account
-    pub rent: Sysvar<'info, Rent>,
+        let duty_rates = &mut ctx.accounts.duty_rates;
+        duty_rates.bump = ctx.bumps.duty_rates;
+        match duty_rates.rates.iter_mut().find(|rate| rate.jurisdiction == jurisdiction) {
+            Some(rate) => rate.rate_bps = rate_bps,
+            None => {
+                require!(duty_rates.rates.len() < MAX_DUTY_RATES, ErrorCode::DutyRateTableFull);
+                duty_rates.rates.push(DutyRate { jurisdiction, rate_bps });
+            }
+        }
 
-    // Include a System Program account because we need it in order to create baxus_escrow_account
-    pub system_program: Program<'info, System>
-}
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::DutyRateChange, Clock::get()?.slot);
 
-#[derive(Accounts)]
-pub struct ReturnAssetToken<'info> {
-    #[account(
-        mut,
-        seeds = [token_mint_account.key().as_ref(), b"redemption".as_ref()],
-        bump = redemption_info.redemption_bump,
-        close = customer_payment_account)
-    ]
-    pub redemption_info: Account<'info, RedemptionInfo>,
+        Ok(())
+    }
 
-    // The customer_token_account must be mutable in order for it to accept the token
-    #[account(
-        mut, 
-        constraint = customer_token_account.owner == *customer_payment_account.key,
-        constraint = redemption_info.customer_token_account == customer_token_account.key())
-    ]
-    pub customer_token_account: Account<'info, TokenAccount>,
+    // The customer sets the ISO 3166-1 alpha-2 code of the jurisdiction the asset is shipping to, which puts the duty they owe on it on the invoice
+    // An all-zero code applies the jurisdiction from the customer's profile that was locked in when the redemption opened
+    pub fn set_jurisdiction(ctx: Context<SetJurisdiction>, jurisdiction: [u8; 2]) -> Result<()> {
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        let jurisdiction = if jurisdiction == [0; 2] { redemption_info.profile_jurisdiction } else { jurisdiction };
+        let rate_bps = ctx.accounts.duty_rates.rate_bps(jurisdiction).ok_or(ErrorCode::UnsupportedJurisdiction)?;
 
-    #[account(constraint = redemption_info.customer_payment_account == customer_payment_account.key())] 
-    pub customer_payment_account: SystemAccount<'info>,
+        redemption_info.jurisdiction = jurisdiction;
+        let duty_usd_cents = (redemption_info.valuation_usd_cents as u128 * rate_bps as u128 / BPS_DENOMINATOR as u128) as u64;
 
-    #[account(mut)]
-    pub token_mint_account: Account<'info, Mint>,
+        let invoice = &mut ctx.accounts.invoice;
+        invoice.set_item(LineItemKind::Duty, duty_usd_cents)?;
+        redemption_info.invoice_paid = invoice.amount_due(redemption_info.fees_waived) == 0;
 
-    #[account(
-        mut,
-        // TO DO: Confirm that we are okay using the mint as a seed, which implies that there will only ever be one token for a given mint
-        seeds = [token_mint_account.key().as_ref()], 
-        bump = redemption_info.escrow_bump)
-    ]
-    pub baxus_escrow_account: Account<'info, TokenAccount>,
+        Ok(())
+    }
 
-    pub token_program: Program<'info, Token>,
-}
+    // The customer saves the choices initialize_redemption should default to for their redemptions, replacing any earlier ones. jurisdiction
+    // can be all zeroes for none, and fee_currency the default pubkey for none. Redemptions lock in the fee currency when they open, and
+    // their invoices can then only be settled in it or in the config's fee mint
+    pub fn set_customer_profile(ctx: Context<SetCustomerProfile>, jurisdiction: [u8; 2], insure: bool, fee_currency: Pubkey) -> Result<()> {
+        require!(
+            jurisdiction == [0; 2] || ctx.accounts.duty_rates.rate_bps(jurisdiction).is_some(),
+            ErrorCode::UnsupportedJurisdiction
+        );
+        // The config's fee mint is always accepted, and any other token has to be whitelisted
+        require!(
+            fee_currency == Pubkey::default()
+                || fee_currency == ctx.accounts.config.fee_mint
+                || ctx.accounts.fee_currency.as_ref().is_some_and(|currency| currency.mint == fee_currency),
+            ErrorCode::FeeCurrencyNotWhitelisted
+        );
 
-#[derive(Accounts)]
-pub struct BurnAssetToken<'info> {
-    #[account(
-        mut,
-        seeds = [token_mint_account.key().as_ref(), b"redemption".as_ref()],
-        bump = redemption_info.redemption_bump,
-        // After the asset token is burned, we can close the RedemptionInfo account and send its rent back to the customer
-        close = customer_payment_account)
-    ]
-    pub redemption_info: Account<'info, RedemptionInfo>,
+        let customer_profile = &mut ctx.accounts.customer_profile;
+        customer_profile.jurisdiction = jurisdiction;
+        customer_profile.insure = insure;
+        customer_profile.fee_currency = fee_currency;
+        customer_profile.bump = ctx.bumps.customer_profile;
 
-    // Include customer_token_account so we can properly constrain the redemption_info account, and make sure it is associated with the correct customer_payment_account
-    #[account(
-        constraint = customer_token_account.owner == *customer_payment_account.key,
-        constraint = redemption_info.customer_token_account == customer_token_account.key())
-    ]
-    pub customer_token_account: Account<'info, TokenAccount>,
+        Ok(())
+    }
 
-    #[account(constraint = redemption_info.customer_payment_account == customer_payment_account.key())]
-    pub customer_payment_account: SystemAccount<'info>,
+    // Open the invoice for a redemption, starting with the base fee it was opened with (and the express fee for express redemptions)
+    pub fn open_invoice(ctx: Context<OpenInvoice>) -> Result<()> {
+        let invoice = &mut ctx.accounts.invoice;
+        invoice.redemption_info = ctx.accounts.redemption_info.key();
+        invoice.bump = ctx.bumps.invoice;
+        invoice.add_item(LineItemKind::BaseFee, ctx.accounts.redemption_info.base_fee_usd_cents)?;
+        if ctx.accounts.redemption_info.express_fee_usd_cents > 0 {
+            invoice.add_item(LineItemKind::ExpressFee, ctx.accounts.redemption_info.express_fee_usd_cents)?;
+        }
 
-    #[account(mut)]
-    pub token_mint_account: Account<'info, Mint>,
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        redemption_info.invoice_paid = invoice.amount_due(redemption_info.fees_waived) == 0;
 
-    #[account(
-        mut,
-        // TO DO: Confirm that we are okay using the mint as a seed, which implies that there will only ever be one token for a given mint
-        seeds = [token_mint_account.key().as_ref()], 
-        bump = redemption_info.escrow_bump)
-    ]
-    pub baxus_escrow_account: Account<'info, TokenAccount>,
+        Ok(())
+    }
 
-    pub token_program: Program<'info, Token>,
-}
+    // Ops add shipping quotes, insurance and penalties to a redemption's invoice. Items can be added after the invoice has been settled, in
+    // which case the customer has to settle it again before the asset can ship
+    pub fn add_invoice_item(ctx: Context<AddInvoiceItem>, kind: LineItemKind, amount_usd_cents: u64) -> Result<()> {
+        let invoice = &mut ctx.accounts.invoice;
+        invoice.add_item(kind, amount_usd_cents)?;
 
-#[account]
-pub struct RedemptionInfo {
-    customer_token_account: Pubkey,
-    customer_payment_account: Pubkey,
-    escrow_bump: u8,
-    redemption_bump: u8,
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        redemption_info.invoice_paid = invoice.amount_due(redemption_info.fees_waived) == 0;
+
+        Ok(())
+    }
+
+    // The insurance pool pays out the coverage on an insured asset that was lost in transit, in the fee mint. The pool signing is what approves
+    // the claim, so only redemptions that have shipped but not been delivered can be claimed on, and only once
+    pub fn pay_insurance_claim(ctx: Context<PayInsuranceClaim>) -> Result<()> {
+        let amount = usd_cents_to_fee_amount(ctx.accounts.redemption_info.coverage_usd_cents, ctx.accounts.fee_mint.decimals)?;
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: ctx.accounts.insurance_pool_fee_account.to_account_info(),
+                    mint: ctx.accounts.fee_mint.to_account_info(),
+                    to: ctx.accounts.customer_fee_account.to_account_info(),
+                    authority: ctx.accounts.insurance_pool.to_account_info(),
+                }),
+            amount,
+            ctx.accounts.fee_mint.decimals,
+        )?;
+
+        ctx.accounts.redemption_info.insurance_claimed = true;
+        ctx.accounts.accounting.current(ctx.accounts.config.clock()?.unix_timestamp).payouts_usd_cents += ctx.accounts.redemption_info.coverage_usd_cents;
+
+        Ok(())
+    }
+
+    // Pay everything outstanding on a redemption's invoice to the treasury, in the config's fee mint, after billing the storage fee accrued so
+    // far. Fees are dropped from the amount due if the redemption's fees were waived
+    pub fn settle_invoice(ctx: Context<SettleInvoice>) -> Result<()> {
+        accrue_storage_fee(&mut ctx.accounts.invoice, &mut ctx.accounts.redemption_info, &ctx.accounts.config, ctx.accounts.config.clock()?.unix_timestamp)?;
+
+        let amount_due = ctx.accounts.invoice.amount_due(ctx.accounts.redemption_info.fees_waived);
+        let amount = usd_cents_to_fee_amount(amount_due, ctx.accounts.fee_mint.decimals)?;
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: ctx.accounts.payer_fee_account.to_account_info(),
+                    mint: ctx.accounts.fee_mint.to_account_info(),
+                    to: ctx.accounts.treasury_fee_account.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                }),
+            amount,
+            ctx.accounts.fee_mint.decimals,
+        )?;
+
+        ctx.accounts.invoice.paid_usd_cents += amount_due;
+        ctx.accounts.redemption_info.invoice_paid = true;
+        let fees_paid = credit_partner_share(&mut ctx.accounts.invoice, &ctx.accounts.redemption_info, ctx.accounts.partner.as_mut())?;
+        ctx.accounts.accounting.current(ctx.accounts.config.clock()?.unix_timestamp).fees_usd_cents += fees_paid;
+
+        Ok(())
+    }
+
+    // Pay everything outstanding on a redemption's invoice in a whitelisted fee currency, converted at its current Pyth price. The payer caps
+    // how many tokens they're willing to spend with max_amount, so they're protected from the price moving before the transaction lands
+    pub fn settle_invoice_in_currency(ctx: Context<SettleInvoiceInCurrency>, max_amount: u64) -> Result<()> {
+        let now = ctx.accounts.config.clock()?.unix_timestamp;
+        accrue_storage_fee(&mut ctx.accounts.invoice, &mut ctx.accounts.redemption_info, &ctx.accounts.config, now)?;
+
+        let price = load_price(&ctx.accounts.price_feed, now, ctx.accounts.fee_currency.max_price_age)?;
+
+        let amount_due = ctx.accounts.invoice.amount_due(ctx.accounts.redemption_info.fees_waived);
+        let amount = usd_cents_to_token_amount(amount_due, ctx.accounts.payment_mint.decimals, &price)?;
+        require!(amount <= max_amount, ErrorCode::SlippageExceeded);
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: ctx.accounts.payer_token_account.to_account_info(),
+                    mint: ctx.accounts.payment_mint.to_account_info(),
+                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                }),
+            amount,
+            ctx.accounts.payment_mint.decimals,
+        )?;
+
+        ctx.accounts.invoice.paid_usd_cents += amount_due;
+        ctx.accounts.redemption_info.invoice_paid = true;
+        let fees_paid = credit_partner_share(&mut ctx.accounts.invoice, &ctx.accounts.redemption_info, ctx.accounts.partner.as_mut())?;
+        ctx.accounts.accounting.current(ctx.accounts.config.clock()?.unix_timestamp).fees_usd_cents += fees_paid;
+
+        Ok(())
+    }
+
+    // Whitelist a token that invoices can be paid in, or change its price feed. price_feed is the Pyth account pricing the token in US dollars
+    pub fn set_fee_currency(ctx: Context<SetFeeCurrency>, price_feed: Pubkey, max_price_age: u64) -> Result<()> {
+        let fee_currency = &mut ctx.accounts.fee_currency;
+        fee_currency.mint = ctx.accounts.payment_mint.key();
+        fee_currency.price_feed = price_feed;
+        fee_currency.max_price_age = max_price_age;
+        fee_currency.bump = ctx.bumps.fee_currency;
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::FeeCurrencyChange, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Anchor can't leave an instruction out of its dispatcher depending on a feature, so the localnet-mocks instructions are dispatched from
+    // here instead. Every other build has no instructions beyond the ones above
+    pub fn fallback<'info>(program_id: &Pubkey, accounts: &'info [AccountInfo<'info>], data: &[u8]) -> Result<()> {
+        #[cfg(feature = "localnet-mocks")]
+        return mocks::dispatch(program_id, accounts, data);
+
+        #[cfg(not(feature = "localnet-mocks"))]
+        {
+            let _ = (program_id, accounts, data);
+            err!(anchor_lang::error::ErrorCode::InstructionFallbackNotFound)
+        }
+    }
+
+    // Take a token off the fee currency whitelist
+    pub fn remove_fee_currency(ctx: Context<RemoveFeeCurrency>) -> Result<()> {
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::FeeCurrencyChange, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Record a physical handoff of the asset to a new custodian. The first handoff (out of the vault) is recorded by an operator, and each
+    // one after that by the current custodian or an operator. Nothing can be handed on once the asset has reached the customer
+    pub fn record_custody_handoff(ctx: Context<RecordCustodyHandoff>, stage: CustodyStage, custodian: Pubkey) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let custody_log = &mut ctx.accounts.custody_log;
+        custody_log.redemption_info = ctx.accounts.redemption_info.key();
+        custody_log.bump = ctx.bumps.custody_log;
+
+        if let Some(last) = custody_log.entries.last() {
+            require!(last.stage != CustodyStage::Customer, ErrorCode::CustodyComplete);
+        }
+        if !matches!(custody_log.entries.last(), Some(last) if last.custodian == authority) {
+            check_operator(&ctx.accounts.config, &ctx.accounts.redemption_info, ctx.accounts.partner.as_deref(), &authority)
+                .map_err(|_| error!(ErrorCode::UnauthorizedCustodian))?;
+        }
+
+        require!(custody_log.entries.len() < MAX_CUSTODY_ENTRIES, ErrorCode::CustodyLogFull);
+        custody_log.entries.push(CustodyEntry {
+            stage,
+            custodian,
+            recorded_by: authority,
+            recorded_at: ctx.accounts.config.clock()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // The customer consents to their asset token being burned as soon as the redemption is delivered, giving up the dispute window. There's
+    // no taking it back, since ops may already be relying on it to settle
+    pub fn grant_burn_consent(ctx: Context<GrantBurnConsent>) -> Result<()> {
+        ctx.accounts.redemption_info.burn_consent = true;
+
+        Ok(())
+    }
+
+    // The customer chooses the share of their rebate and bond to donate to the config's charity when the token is burned, or 0 to donate
+    // nothing
+    pub fn set_donation(ctx: Context<SetDonation>, donation_bps: u16) -> Result<()> {
+        require!(ctx.accounts.config.charity != Pubkey::default(), ErrorCode::DonationsDisabled);
+        require!(donation_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidDonationShare);
+        ctx.accounts.redemption_info.donation_bps = donation_bps;
+
+        Ok(())
+    }
+
+    // The customer proves they passed KYC with an approved provider. The verifier program fails the CPI if the proof doesn't check out
+    pub fn verify_kyc_proof(ctx: Context<VerifyKycProof>, proof: [u8; GROTH16_PROOF_LEN]) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(config.kyc_verifier != Pubkey::default(), ErrorCode::ZkKycDisabled);
+
+        let redemption_key = ctx.accounts.redemption_info.key();
+        let mut data = anchor_lang::solana_program::hash::hash(b"global:verify_proof").to_bytes()[..8].to_vec();
+        data.extend_from_slice(&proof);
+        for public_input in kyc_public_inputs(config, redemption_key, ctx.accounts.customer_payment_account.key()) {
+            data.extend_from_slice(&public_input);
+        }
+
+        invoke(
+            &Instruction { program_id: config.kyc_verifier, accounts: vec![], data },
+            &[ctx.accounts.kyc_verifier.to_account_info()],
+        )?;
+
+        ctx.accounts.redemption_info.kyc_verified = true;
+
+        Ok(())
+    }
+
+    // The customer pauses shipment of their redemption, which stops its SLA clock until they release the hold
+    pub fn request_hold(ctx: Context<RequestHold>) -> Result<()> {
+        ctx.accounts.redemption_info.held_since = ctx.accounts.config.clock()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    // The customer lets shipment go ahead again. The time on hold is banked, so it never counts against BAXUS's SLA for the current status,
+    // and if the config says so, isn't charged as storage
+    pub fn release_hold(ctx: Context<ReleaseHold>) -> Result<()> {
+        let now = ctx.accounts.config.clock()?.unix_timestamp;
+        let config = &ctx.accounts.config;
+        let redemption_info = &mut ctx.accounts.redemption_info;
+
+        let held_secs = now - redemption_info.held_since;
+        redemption_info.status_held_secs += held_secs;
+        if config.hold_pauses_storage {
+            redemption_info.storage_paused_secs += held_secs;
+        }
+        redemption_info.held_since = 0;
+
+        Ok(())
+    }
+
+    // The customer posts collateral so their redemption can ship before KYC clears
+    pub fn post_collateral(ctx: Context<PostCollateral>) -> Result<()> {
+        let collateral_bps = ctx.accounts.config.expedite_collateral_bps;
+        require!(collateral_bps > 0, ErrorCode::ExpediteUnavailable);
+
+        let collateral_usd_cents =
+            (ctx.accounts.redemption_info.valuation_usd_cents as u128 * collateral_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        let amount = usd_cents_to_fee_amount(collateral_usd_cents, ctx.accounts.fee_mint.decimals)?;
+        require!(amount > 0, ErrorCode::ExpediteUnavailable);
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: ctx.accounts.customer_fee_account.to_account_info(),
+                    mint: ctx.accounts.fee_mint.to_account_info(),
+                    to: ctx.accounts.collateral_account.to_account_info(),
+                    authority: ctx.accounts.customer_payment_account.to_account_info(),
+                }),
+            amount,
+            ctx.accounts.fee_mint.decimals,
+        )?;
+
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        redemption_info.collateral_amount = amount;
+        redemption_info.collateral_bump = ctx.bumps.collateral_account;
+
+        Ok(())
+    }
+
+    // Hand a redemption's collateral back to the customer, or once the asset has been delivered to a customer who then failed KYC, to the
+    // treasury. Either way the collateral account is closed and its rent goes back to the customer
+    pub fn settle_collateral(ctx: Context<SettleCollateral>, outcome: CollateralOutcome) -> Result<()> {
+        check_operator(&ctx.accounts.config, &ctx.accounts.redemption_info, ctx.accounts.partner.as_deref(), &ctx.accounts.authority.key())?;
+        if outcome == CollateralOutcome::Liquidate {
+            require!(ctx.accounts.redemption_info.status == RedemptionStatus::Delivered, ErrorCode::CollateralNotLiquidatable);
+            require!(!ctx.accounts.redemption_info.kyc_verified, ErrorCode::KycVerified);
+        }
+
+        let redemption_key = ctx.accounts.redemption_info.key();
+        let signer_seeds: &[&[u8]] = &[redemption_key.as_ref(), COLLATERAL_SEED, &[ctx.accounts.redemption_info.collateral_bump]];
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: ctx.accounts.collateral_account.to_account_info(),
+                    mint: ctx.accounts.fee_mint.to_account_info(),
+                    to: ctx.accounts.destination_fee_account.to_account_info(),
+                    authority: ctx.accounts.collateral_account.to_account_info(),
+                },
+                &[signer_seeds]),
+            ctx.accounts.redemption_info.collateral_amount,
+            ctx.accounts.fee_mint.decimals,
+        )?;
+
+        anchor_spl::token_interface::close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::CloseAccount {
+                    account: ctx.accounts.collateral_account.to_account_info(),
+                    destination: ctx.accounts.customer_payment_account.to_account_info(),
+                    authority: ctx.accounts.collateral_account.to_account_info(),
+                },
+                &[signer_seeds]),
+        )?;
+
+        ctx.accounts.redemption_info.collateral_amount = 0;
+
+        Ok(())
+    }
+
+    // Buy the asset back from the customer, taking its token into the cold vault and closing the redemption, and escrow the payment in the
+    // fee mint, to be paid out in tranche_count equal tranches, tranche_interval_secs apart
+    pub fn start_buyback_payout<'info>(
+        ctx: Context<'_, '_, '_, 'info, StartBuybackPayout<'info>>,
+        amount_usd_cents: u64,
+        tranche_count: u8,
+        tranche_interval_secs: i64,
+    ) -> Result<()> {
+        require!(tranche_count > 0 && tranche_interval_secs >= 0, ErrorCode::InvalidTrancheSchedule);
+        require!(amount_usd_cents <= ctx.accounts.redemption_info.valuation_usd_cents, ErrorCode::BuybackExceedsValuation);
+        let amount = usd_cents_to_fee_amount(amount_usd_cents, ctx.accounts.fee_mint.decimals)?;
+
+        let redemption_key = ctx.accounts.redemption_info.key();
+        ctx.accounts.status_index.remove(&mut ctx.accounts.status_index_page, &redemption_key);
+        remove_priority(&mut ctx.accounts.redemption_info, ctx.accounts.priority_index.as_mut(), &redemption_key)?;
+        deregister_customer_redemption(&ctx.accounts.redemption_info, ctx.accounts.customer_registry.as_mut(), &redemption_key)?;
+        ctx.accounts.receipt.record(
+            ctx.accounts.token_mint_account.key(),
+            &ctx.accounts.redemption_info,
+            RedemptionOutcome::BoughtBack { amount_usd_cents },
+            ctx.accounts.config.clock()?.unix_timestamp,
+            ctx.bumps.receipt,
+        );
+
+        release_escrow(
+            EscrowRelease::Transfer(ctx.accounts.cold_vault_account.to_account_info()),
+            &mut ctx.accounts.baxus_escrow_account,
+            &ctx.accounts.token_mint_account,
+            ctx.accounts.redemption_info.escrow_bump,
+            RentRefund {
+                redemption_info: ctx.accounts.redemption_info.to_account_info(),
+                customer: ctx.accounts.customer_payment_account.to_account_info(),
+                treasury: ctx.accounts.treasury.as_ref().map(|treasury| treasury.to_account_info()),
+                treasury_share_bps: ctx.accounts.config.treasury_rent_share_bps,
+            },
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.fee_token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: ctx.accounts.ops_fee_account.to_account_info(),
+                    mint: ctx.accounts.fee_mint.to_account_info(),
+                    to: ctx.accounts.payout_escrow.to_account_info(),
+                    authority: ctx.accounts.ops_authority.to_account_info(),
+                }),
+            amount,
+            ctx.accounts.fee_mint.decimals,
+        )?;
+
+        let buyback_payout = &mut ctx.accounts.buyback_payout;
+        buyback_payout.token_mint = ctx.accounts.token_mint_account.key();
+        buyback_payout.opened_at = ctx.accounts.redemption_info.created_at;
+        buyback_payout.customer = ctx.accounts.redemption_info.customer_payment_account;
+        buyback_payout.funder = ctx.accounts.ops_authority.key();
+        buyback_payout.amount_usd_cents = amount_usd_cents;
+        buyback_payout.amount = amount;
+        buyback_payout.tranche_count = tranche_count;
+        buyback_payout.tranches_claimed = 0;
+        buyback_payout.tranche_interval_secs = tranche_interval_secs;
+        buyback_payout.started_at = ctx.accounts.config.clock()?.unix_timestamp;
+        buyback_payout.escrow_bump = ctx.bumps.payout_escrow;
+        buyback_payout.bump = ctx.bumps.buyback_payout;
+
+        Ok(())
+    }
+
+    // Pay the customer every tranche of their buy-back that has unlocked since they last claimed
+    pub fn claim_tranche(ctx: Context<ClaimTranche>) -> Result<()> {
+        let now = ctx.accounts.config.clock()?.unix_timestamp;
+        let buyback_payout = &ctx.accounts.buyback_payout;
+        let unlocked = buyback_payout.tranches_unlocked(now);
+        require!(unlocked > buyback_payout.tranches_claimed, ErrorCode::NoTrancheUnlocked);
+        let amount = buyback_payout.amount_through(unlocked) - buyback_payout.amount_through(buyback_payout.tranches_claimed);
+
+        let token_mint = buyback_payout.token_mint;
+        let opened_at = buyback_payout.opened_at.to_le_bytes();
+        let escrow_bump = [buyback_payout.escrow_bump];
+        let signer_seeds: &[&[u8]] = &[BUYBACK_ESCROW_SEED, token_mint.as_ref(), opened_at.as_ref(), &escrow_bump];
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: ctx.accounts.payout_escrow.to_account_info(),
+                    mint: ctx.accounts.fee_mint.to_account_info(),
+                    to: ctx.accounts.customer_fee_account.to_account_info(),
+                    authority: ctx.accounts.payout_escrow.to_account_info(),
+                },
+                &[signer_seeds]),
+            amount,
+            ctx.accounts.fee_mint.decimals,
+        )?;
+
+        ctx.accounts.buyback_payout.tranches_claimed = unlocked;
+        if unlocked == ctx.accounts.buyback_payout.tranche_count {
+            anchor_spl::token_interface::close_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token_interface::CloseAccount {
+                        account: ctx.accounts.payout_escrow.to_account_info(),
+                        destination: ctx.accounts.funder.to_account_info(),
+                        authority: ctx.accounts.payout_escrow.to_account_info(),
+                    },
+                    &[signer_seeds]),
+            )?;
+            ctx.accounts.buyback_payout.close(ctx.accounts.funder.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    // The brand behind the redemption's collection approves burning its token. Approving again just refreshes the approval
+    pub fn approve_burn(ctx: Context<ApproveBurn>) -> Result<()> {
+        let burn_approval = &mut ctx.accounts.burn_approval;
+        burn_approval.redemption_info = ctx.accounts.redemption_info.key();
+        burn_approval.redemption_opened_at = ctx.accounts.redemption_info.created_at;
+        burn_approval.approved_at = ctx.accounts.config.clock()?.unix_timestamp;
+        burn_approval.bump = ctx.bumps.burn_approval;
+
+        Ok(())
+    }
+
+    // Append a condition oracle's summary of the sensor data for a shipment in transit
+    pub fn record_condition_report(ctx: Context<RecordConditionReport>, params: ConditionReportParams) -> Result<()> {
+        require!(ctx.accounts.config.condition_oracles.contains(&ctx.accounts.oracle.key()), ErrorCode::UnregisteredOracle);
+        require!(params.window_start <= params.window_end, ErrorCode::InvalidConditionReport);
+
+        let condition_log = &mut ctx.accounts.condition_log;
+        condition_log.redemption_info = ctx.accounts.redemption_info.key();
+        condition_log.bump = ctx.bumps.condition_log;
+
+        require!(condition_log.reports.len() < MAX_CONDITION_REPORTS, ErrorCode::ConditionLogFull);
+        condition_log.reports.push(ConditionReport {
+            oracle: ctx.accounts.oracle.key(),
+            recorded_at: ctx.accounts.config.clock()?.unix_timestamp,
+            window_start: params.window_start,
+            window_end: params.window_end,
+            min_temperature_centi_c: params.min_temperature_centi_c,
+            max_temperature_centi_c: params.max_temperature_centi_c,
+            max_humidity_bps: params.max_humidity_bps,
+            excursion_secs: params.excursion_secs,
+            readings_hash: params.readings_hash,
+        });
+
+        Ok(())
+    }
+
+    // Attach a certificate NFT to a redemption, and if escrow is set, move it into an escrow account to be burned along with the asset
+    pub fn attach_certificate(ctx: Context<AttachCertificate>, kind: CertificateKind, escrow: bool) -> Result<()> {
+        let certificate_mint = ctx.accounts.certificate_mint.key();
+        require_keys_neq!(certificate_mint, ctx.accounts.token_mint_account.key(), ErrorCode::InvalidCertificate);
+
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        require!(redemption_info.attachments.iter().all(|attachment| attachment.mint != certificate_mint), ErrorCode::CertificateAlreadyAttached);
+        require!(redemption_info.attachments.len() < MAX_ATTACHMENTS, ErrorCode::TooManyAttachments);
+
+        if escrow {
+            let Some(certificate_escrow) = &ctx.accounts.certificate_escrow else {
+                return err!(ErrorCode::AttachmentAccountsRequired);
+            };
+
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token_interface::TransferChecked {
+                        from: ctx.accounts.customer_certificate_account.to_account_info(),
+                        mint: ctx.accounts.certificate_mint.to_account_info(),
+                        to: certificate_escrow.to_account_info(),
+                        authority: ctx.accounts.customer_payment_account.to_account_info(),
+                    }),
+                AssetAmount::WHOLE.get(),
+                ctx.accounts.certificate_mint.decimals,
+            )?;
+        }
+
+        redemption_info.attachments.push(Attachment {
+            mint: certificate_mint,
+            kind,
+            escrowed: escrow,
+            escrow_bump: if escrow { ctx.bumps.certificate_escrow } else { 0 },
+        });
+
+        Ok(())
+    }
+
+    // Take a certificate off a redemption, handing it back to the customer if it was escrowed. The customer or an operator can detach
+    // certificates
+    pub fn detach_certificate(ctx: Context<DetachCertificate>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        if authority != ctx.accounts.redemption_info.customer_payment_account {
+            check_operator(&ctx.accounts.config, &ctx.accounts.redemption_info, ctx.accounts.partner.as_deref(), &authority)?;
+        }
+
+        let certificate_mint = ctx.accounts.certificate_mint.key();
+        let index = ctx
+            .accounts
+            .redemption_info
+            .attachments
+            .iter()
+            .position(|attachment| attachment.mint == certificate_mint)
+            .ok_or(ErrorCode::CertificateNotAttached)?;
+        let attachment = ctx.accounts.redemption_info.attachments.remove(index);
+
+        if attachment.escrowed {
+            let (Some(certificate_escrow), Some(customer_certificate_account)) =
+                (&ctx.accounts.certificate_escrow, &ctx.accounts.customer_certificate_account)
+            else {
+                return err!(ErrorCode::AttachmentAccountsRequired);
+            };
+            let signer_seeds: &[&[u8]] = &[certificate_mint.as_ref(), ATTACHMENT_ESCROW_SEED, &[attachment.escrow_bump]];
+
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token_interface::TransferChecked {
+                        from: certificate_escrow.to_account_info(),
+                        mint: ctx.accounts.certificate_mint.to_account_info(),
+                        to: customer_certificate_account.to_account_info(),
+                        authority: certificate_escrow.to_account_info(),
+                    },
+                    &[signer_seeds]),
+                AssetAmount::WHOLE.get(),
+                ctx.accounts.certificate_mint.decimals,
+            )?;
+
+            anchor_spl::token_interface::close_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token_interface::CloseAccount {
+                        account: certificate_escrow.to_account_info(),
+                        destination: ctx.accounts.customer_payment_account.to_account_info(),
+                        authority: certificate_escrow.to_account_info(),
+                    },
+                    &[signer_seeds]),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Freeze a redemption pending a compliance review, which stops it from being advanced, returned, burned, cancelled or expired. Appealing
+    // a rejection freezes the redemption the same way
+    pub fn flag_suspicious(ctx: Context<FlagSuspicious>) -> Result<()> {
+        ctx.accounts.redemption_info.frozen_by_compliance = true;
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.compliance_authority.key(), AuditAction::ComplianceFreeze, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Release a redemption frozen by flag_suspicious. It takes both the compliance authority and the admin, so neither can release one alone
+    pub fn unfreeze(ctx: Context<Unfreeze>) -> Result<()> {
+        ctx.accounts.redemption_info.frozen_by_compliance = false;
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::ComplianceUnfreeze, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Record the outcome of BAXUS's risk check on a customer wallet, replacing any earlier score. Higher scores are riskier
+    pub fn set_risk_score(ctx: Context<SetRiskScore>, score: u8) -> Result<()> {
+        let risk_score = &mut ctx.accounts.risk_score;
+        risk_score.score = score;
+        risk_score.assessed_at = ctx.accounts.config.clock()?.unix_timestamp;
+        risk_score.bump = ctx.bumps.risk_score;
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.compliance_authority.key(), AuditAction::RiskScoreChange, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Record BAXUS's appraised value for an asset, which gets locked into any redemption opened for it afterwards
+    pub fn set_appraisal(ctx: Context<SetAppraisal>, usd_value_cents: u64) -> Result<()> {
+        let appraisal = &mut ctx.accounts.appraisal;
+        appraisal.usd_value_cents = usd_value_cents;
+        appraisal.appraised_at = ctx.accounts.config.clock()?.unix_timestamp;
+        appraisal.bump = ctx.bumps.appraisal;
+
+        Ok(())
+    }
+
+    // Record a hash of the internal ticket or WMS record for the redemption, so support can tie it to the warehouse paperwork during audits
+    pub fn set_ops_reference(ctx: Context<SetOpsReference>, ops_reference: [u8; 32]) -> Result<()> {
+        check_operator(&ctx.accounts.config, &ctx.accounts.redemption_info, ctx.accounts.partner.as_deref(), &ctx.accounts.authority.key())?;
+
+        ctx.accounts.redemption_info.ops_reference = ops_reference;
+
+        Ok(())
+    }
+
+    // Commit a hash of the shipping manifest and customs documents for a redemption, which it needs before it can ship. Ops can correct it
+    // up until the asset ships
+    pub fn commit_shipment_documents(ctx: Context<CommitShipmentDocuments>, documents_hash: [u8; 32]) -> Result<()> {
+        check_operator(&ctx.accounts.config, &ctx.accounts.redemption_info, ctx.accounts.partner.as_deref(), &ctx.accounts.authority.key())?;
+
+        ctx.accounts.redemption_info.shipment_documents_hash = documents_hash;
+
+        Ok(())
+    }
+
+    // Set how urgently a redemption should ship, moving it to its place in the priority index, or out of it for a priority of 0. Operators can
+    // set any priority, and the customer of an express redemption up to EXPRESS_MAX_PRIORITY
+    pub fn set_priority(ctx: Context<SetPriority>, priority: u8) -> Result<()> {
+        let redemption_info = &ctx.accounts.redemption_info;
+        let customer_set =
+            ctx.accounts.authority.key() == redemption_info.customer_payment_account && redemption_info.service_tier == ServiceTier::Express;
+        if customer_set {
+            require!(priority <= EXPRESS_MAX_PRIORITY, ErrorCode::PriorityTooHigh);
+        } else {
+            check_operator(&ctx.accounts.config, redemption_info, ctx.accounts.partner.as_deref(), &ctx.accounts.authority.key())?;
+        }
+
+        let redemption_key = ctx.accounts.redemption_info.key();
+        let priority_index = &mut ctx.accounts.priority_index;
+        priority_index.bump = ctx.bumps.priority_index;
+        priority_index.remove(&redemption_key);
+        if priority > 0 {
+            priority_index.insert(redemption_key, priority, customer_set)?;
+        }
+        ctx.accounts.redemption_info.priority = priority;
+
+        Ok(())
+    }
+
+    // Drop a redemption from the priority index if it's been closed, shipped or lost its priority, which happens when a redemption is unwound
+    // before it ships
+    pub fn prune_priority_index(ctx: Context<PrunePriorityIndex>, redemption: Pubkey) -> Result<()> {
+        let redemption_info = ctx.accounts.redemption_info.try_borrow_data()?;
+        let stale = match RedemptionInfo::try_deserialize(&mut &redemption_info[..]) {
+            Ok(redemption_info) => redemption_info.priority == 0 || redemption_info.status >= RedemptionStatus::Shipped,
+            // Closed, or still on a layout from before priorities existed
+            Err(_) => true,
+        };
+        require!(stale, ErrorCode::PriorityEntryNotStale);
+
+        ctx.accounts.priority_index.remove(&redemption);
+
+        Ok(())
+    }
+
+    // Re-emit a redemption's current state as a RedemptionResynced event, so an indexer that missed events can heal its record of it. Nothing
+    // changes on the redemption, and the event carries the sequence number of the last real event rather than taking a new one, so resyncing
+    // as often as needed never leaves a gap in the redemption's event sequence
+    pub fn resync_redemption(ctx: Context<ResyncRedemption>) -> Result<()> {
+        check_operator(&ctx.accounts.config, &ctx.accounts.redemption_info, ctx.accounts.partner.as_deref(), &ctx.accounts.authority.key())?;
+
+        emit!(ctx.accounts.redemption_info.resynced(ctx.accounts.redemption_info.key()));
+
+        Ok(())
+    }
+
+    // Anyone can flag a redemption that has stayed in its current status for longer than the SLA deadline the admin set for that status. Each
+    // status can only be flagged once per redemption
+    pub fn flag_sla_breach(ctx: Context<FlagSlaBreach>) -> Result<()> {
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        let status = redemption_info.status;
+        let deadline = ctx.accounts.config.sla_deadline(status, redemption_info.service_tier);
+        let now = ctx.accounts.config.clock()?.unix_timestamp;
+        let breached_at = redemption_info.sla_clock_started_at(now) + deadline;
+        require!(deadline > 0 && now >= breached_at, ErrorCode::SlaNotBreached);
+
+        let status_bit = status.bit();
+        require!(redemption_info.sla_breaches & status_bit == 0, ErrorCode::SlaBreachAlreadyFlagged);
+        redemption_info.sla_breaches |= status_bit;
+
+        // Remedies are locked in from the policy in force at the first breach, so later config changes can't take them away
+        if redemption_info.sla_breached_at == 0 {
+            let config = &ctx.accounts.config;
+            redemption_info.sla_breached_at = breached_at;
+            redemption_info.fees_waived = config.sla_waive_fees;
+            redemption_info.compensation_per_day = config.sla_compensation_per_day;
+            // A breach flagged after the asset shipped is still compensated, but can't be cancelled - the customer already has the bottle
+            redemption_info.penalty_free_cancellation = config.sla_allow_cancellation && status < RedemptionStatus::Shipped;
+        }
+
+        emit!(SlaBreached {
+            redemption_info: redemption_info.key(),
+            redemption_id: redemption_info.redemption_id,
+            event_sequence: redemption_info.next_event_sequence(),
+            status,
+            breached_at,
+            service_tier: redemption_info.service_tier,
+        });
+
+        Ok(())
+    }
+
+    // The treasury pays out whatever SLA compensation the redemption has accrued since it was last paid. Compensation accrues for every full day
+    // since the first breach, and stops once the redemption is delivered
+    pub fn pay_sla_compensation(ctx: Context<PaySlaCompensation>) -> Result<()> {
+        let redemption_info = &ctx.accounts.redemption_info;
+        let accrued_until = if redemption_info.status == RedemptionStatus::Delivered {
+            redemption_info.status_changed_at
+        } else {
+            ctx.accounts.config.clock()?.unix_timestamp
+        };
+        let days = (accrued_until - redemption_info.sla_breached_at).max(0) as u64 / SECONDS_PER_DAY;
+        let owed = days
+            .checked_mul(redemption_info.compensation_per_day)
+            .ok_or(ErrorCode::AmountOverflow)?
+            .saturating_sub(redemption_info.compensation_paid);
+
+        if owed > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.treasury.to_account_info(),
+                        to: ctx.accounts.customer_payment_account.to_account_info(),
+                    }),
+                owed,
+            )?;
+        }
+
+        let redemption_info = &mut ctx.accounts.redemption_info;
+        redemption_info.compensation_paid = redemption_info.compensation_paid.checked_add(owed).ok_or(ErrorCode::AmountOverflow)?;
+        let period = ctx.accounts.accounting.current(ctx.accounts.config.clock()?.unix_timestamp);
+        period.compensation_lamports = period.compensation_lamports.checked_add(owed).ok_or(ErrorCode::AmountOverflow)?;
+
+        Ok(())
+    }
+
+    // If the SLA policy allowed it when the redemption breached its SLA, the customer can cancel the redemption and get their token back, along
+    // with their bond and the rent for the escrow and redemption_info accounts
+    pub fn cancel_redemption<'info>(ctx: Context<'_, '_, '_, 'info, CancelRedemption<'info>>) -> Result<()> {
+        check_cpi_caller(&ctx.accounts.config, &ctx.accounts.instructions)?;
+
+        let redemption_key = ctx.accounts.redemption_info.key();
+        ctx.accounts.status_index.remove(&mut ctx.accounts.status_index_page, &redemption_key);
+        remove_priority(&mut ctx.accounts.redemption_info, ctx.accounts.priority_index.as_mut(), &redemption_key)?;
+        deregister_customer_redemption(&ctx.accounts.redemption_info, ctx.accounts.customer_registry.as_mut(), &redemption_key)?;
+        ctx.accounts.receipt.record(
+            ctx.accounts.token_mint_account.key(),
+            &ctx.accounts.redemption_info,
+            RedemptionOutcome::Cancelled,
+            ctx.accounts.config.clock()?.unix_timestamp,
+            ctx.bumps.receipt,
+        );
+
+        release_escrow(
+            EscrowRelease::Transfer(ctx.accounts.customer_token_account.to_account_info()),
+            &mut ctx.accounts.baxus_escrow_account,
+            &ctx.accounts.token_mint_account,
+            ctx.accounts.redemption_info.escrow_bump,
+            RentRefund {
+                redemption_info: ctx.accounts.redemption_info.to_account_info(),
+                customer: ctx.accounts.customer_payment_account.to_account_info(),
+                treasury: ctx.accounts.treasury.as_ref().map(|treasury| treasury.to_account_info()),
+                treasury_share_bps: ctx.accounts.config.treasury_rent_share_bps,
+            },
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+
+        Ok(())
+    }
+
+    // The customer takes their token back from a redemption BAXUS hasn't moved on for the config's dead_man_secs, along with their bond and
+    // the rent for the escrow and redemption_info accounts
+    pub fn self_return<'info>(ctx: Context<'_, '_, '_, 'info, SelfReturn<'info>>) -> Result<()> {
+        check_cpi_caller(&ctx.accounts.config, &ctx.accounts.instructions)?;
+        let dead_man_secs = ctx.accounts.config.dead_man_secs;
+        require!(dead_man_secs > 0, ErrorCode::SelfReturnDisabled);
+        require!(
+            ctx.accounts.config.clock()?.unix_timestamp >= ctx.accounts.redemption_info.status_changed_at.saturating_add(dead_man_secs),
+            ErrorCode::RedemptionNotAbandoned
+        );
+
+        let redemption_key = ctx.accounts.redemption_info.key();
+        ctx.accounts.status_index.remove(&mut ctx.accounts.status_index_page, &redemption_key);
+        remove_priority(&mut ctx.accounts.redemption_info, ctx.accounts.priority_index.as_mut(), &redemption_key)?;
+        deregister_customer_redemption(&ctx.accounts.redemption_info, ctx.accounts.customer_registry.as_mut(), &redemption_key)?;
+        ctx.accounts.receipt.record(
+            ctx.accounts.token_mint_account.key(),
+            &ctx.accounts.redemption_info,
+            RedemptionOutcome::Returned,
+            ctx.accounts.config.clock()?.unix_timestamp,
+            ctx.bumps.receipt,
+        );
+
+        release_escrow(
+            EscrowRelease::Transfer(ctx.accounts.customer_token_account.to_account_info()),
+            &mut ctx.accounts.baxus_escrow_account,
+            &ctx.accounts.token_mint_account,
+            ctx.accounts.redemption_info.escrow_bump,
+            RentRefund {
+                redemption_info: ctx.accounts.redemption_info.to_account_info(),
+                customer: ctx.accounts.customer_payment_account.to_account_info(),
+                treasury: ctx.accounts.treasury.as_ref().map(|treasury| treasury.to_account_info()),
+                treasury_share_bps: ctx.accounts.config.treasury_rent_share_bps,
+            },
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+
+        Ok(())
+    }
+
+    // Create the index account for one status - the admin has to do this once for every status, and create at least one page for it, before
+    // redemptions can be opened
+    pub fn initialize_status_index(ctx: Context<InitializeStatusIndex>, status: RedemptionStatus) -> Result<()> {
+        let status_index = &mut ctx.accounts.status_index;
+        status_index.status = status;
+        status_index.bump = ctx.bumps.status_index;
+
+        Ok(())
+    }
+
+    // Add the next page to a status's index, for when its existing pages fill up. Pages are never compacted or closed, so a status's page
+    // count only ever grows and the rent the admin pays for a page stays locked in it, even once the page is empty
+    pub fn initialize_status_index_page(ctx: Context<InitializeStatusIndexPage>) -> Result<()> {
+        let status_index = &mut ctx.accounts.status_index;
+        let status_index_page = &mut ctx.accounts.status_index_page;
+        status_index_page.status = status_index.status;
+        status_index_page.page = status_index.page_count;
+        status_index_page.bump = ctx.bumps.status_index_page;
+        status_index.page_count += 1;
+
+        Ok(())
+    }
+
+    // Create the Config and AuditLog accounts - only the program's upgrade authority can do this, and it becomes the first admin
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.set_params(params)?;
+        config.paused = false;
+        config.bump = ctx.bumps.config;
+        config.audit_log_bump = ctx.bumps.audit_log;
+
+        ctx.accounts.audit_log.load_init()?;
+
+        Ok(())
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        ctx.accounts.config.set_params(params)?;
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::ConfigChange, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // The treasury pays a partner everything it has been credited from fees on its redemptions since it was last paid, in the fee mint
+    pub fn settle_partner_share(ctx: Context<SettlePartnerShare>) -> Result<()> {
+        let partner = &ctx.accounts.partner;
+        let owed = partner.share_accrued_usd_cents - partner.share_settled_usd_cents;
+
+        if owed > 0 {
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token_interface::TransferChecked {
+                        from: ctx.accounts.treasury_fee_account.to_account_info(),
+                        mint: ctx.accounts.fee_mint.to_account_info(),
+                        to: ctx.accounts.partner_fee_account.to_account_info(),
+                        authority: ctx.accounts.treasury.to_account_info(),
+                    }),
+                usd_cents_to_fee_amount(owed, ctx.accounts.fee_mint.decimals)?,
+                ctx.accounts.fee_mint.decimals,
+            )?;
+        }
+
+        ctx.accounts.partner.share_settled_usd_cents += owed;
+        ctx.accounts.accounting.current(ctx.accounts.config.clock()?.unix_timestamp).payouts_usd_cents += owed;
+
+        Ok(())
+    }
+
+    // Record a redemption BAXUS handled off-chain before the program existed
+    pub fn import_legacy_redemption(ctx: Context<ImportLegacyRedemption>, params: LegacyRedemptionParams) -> Result<()> {
+        require!(params.closed_at >= params.opened_at, ErrorCode::InvalidLegacyRedemption);
+
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.token_mint = params.token_mint;
+        receipt.customer = params.customer;
+        receipt.outcome = params.outcome;
+        receipt.opened_at = params.opened_at;
+        receipt.closed_at = params.closed_at;
+        receipt.valuation_usd_cents = params.valuation_usd_cents;
+        receipt.ops_reference = params.ops_reference;
+        receipt.imported = true;
+        receipt.bump = ctx.bumps.receipt;
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::LegacyImport, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Set up the redemption policy for a collection, or change it. Redemptions that are already open keep the policy they were opened with
+    pub fn set_collection_config(ctx: Context<SetCollectionConfig>, collection: Pubkey, params: CollectionConfigParams) -> Result<()> {
+        let collection_config = &mut ctx.accounts.collection_config;
+        collection_config.collection = collection;
+        collection_config.bump = ctx.bumps.collection_config;
+        collection_config.set_params(params)?;
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::CollectionConfigChange, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Set up a partner brand, or change its settings. Partner IDs start at 1, since 0 marks BAXUS's own redemptions
+    pub fn set_partner(ctx: Context<SetPartner>, partner_id: u16, params: PartnerParams) -> Result<()> {
+        require!(partner_id != 0, ErrorCode::InvalidPartnerId);
+
+        let partner = &mut ctx.accounts.partner;
+        partner.partner_id = partner_id;
+        partner.bump = ctx.bumps.partner;
+        partner.set_params(params)?;
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::PartnerChange, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Top an account up to rent exemption at the current rent, paid by whoever calls this. The account has to belong to this program, or be a
+    // redemption's escrow token account
+    pub fn top_up_rent(ctx: Context<TopUpRent>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        require!(is_program_account(&account)?, ErrorCode::InvalidTopUpAccount);
+
+        let shortfall = Rent::get()?.minimum_balance(account.data_len()).saturating_sub(account.lamports());
+        require!(shortfall > 0, ErrorCode::AlreadyRentExempt);
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.contributor.to_account_info(),
+                    to: account.clone(),
+                }),
+            shortfall,
+        )?;
+
+        emit!(RentToppedUp {
+            account: account.key(),
+            contributor: ctx.accounts.contributor.key(),
+            lamports: shortfall,
+        });
+
+        Ok(())
+    }
+
+    // Close an empty escrow token account that has no redemption open for its mint, sending its rent to the treasury
+    pub fn close_orphaned_escrow(ctx: Context<CloseOrphanedEscrow>) -> Result<()> {
+        anchor_spl::token_interface::close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::CloseAccount {
+                    account: ctx.accounts.baxus_escrow_account.to_account_info(),
+                    destination: ctx.accounts.treasury.to_account_info(),
+                    authority: ctx.accounts.baxus_escrow_account.to_account_info(),
+                },
+                &[&[
+                    ctx.accounts.token_mint_account.key().as_ref(),
+                    &[ctx.bumps.baxus_escrow_account],
+                ]]),
+        )?;
+
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), AuditAction::OrphanedEscrowClosed, Clock::get()?.slot);
+
+        Ok(())
+    }
+
+    // Publish this epoch's Merkle root over the open redemptions, replacing the last epoch's
+    pub fn publish_snapshot(ctx: Context<PublishSnapshot>, root: [u8; 32], redemption_count: u32) -> Result<()> {
+        let clock = Clock::get()?;
+        let snapshot = &mut ctx.accounts.snapshot;
+        require!(snapshot.published_at == 0 || clock.epoch > snapshot.epoch, ErrorCode::SnapshotAlreadyPublished);
+
+        snapshot.epoch = clock.epoch;
+        snapshot.root = root;
+        snapshot.redemption_count = redemption_count;
+        snapshot.published_at = clock.unix_timestamp;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        emit!(SnapshotPublished {
+            epoch: clock.epoch,
+            root,
+            redemption_count,
+        });
+
+        Ok(())
+    }
+
+    // Attest that the asset token of each open redemption passed in remaining_accounts, as (redemption_info, custody token account) pairs, is
+    // in custody. restart begins a new attestation, and otherwise the pairs carry on from the last call's. The attestation only counts once
+    // complete_reserve_attestation has checked it covers every open redemption
+    pub fn attest_reserves<'info>(ctx: Context<'_, '_, '_, 'info, AttestReserves<'info>>, restart: bool) -> Result<()> {
+        let now = ctx.accounts.config.clock()?.unix_timestamp;
+        let proof_of_reserve = &mut ctx.accounts.proof_of_reserve;
+        proof_of_reserve.bump = ctx.bumps.proof_of_reserve;
+        if restart {
+            proof_of_reserve.started_at = now;
+            proof_of_reserve.attested_count = 0;
+            proof_of_reserve.root = [0; 32];
+            proof_of_reserve.last_redemption = Pubkey::default();
+            proof_of_reserve.completed = false;
+            proof_of_reserve.open_count = 0;
+        }
+        require!(!proof_of_reserve.completed, ErrorCode::ReserveAttestationCompleted);
+
+        require!(ctx.remaining_accounts.chunks_exact(2).remainder().is_empty(), ErrorCode::ReserveAccountsRequired);
+        for pair in ctx.remaining_accounts.chunks_exact(2) {
+            let (redemption_info, custody_account) = (&pair[0], &pair[1]);
+            // Redemptions have to come in ascending address order, so none can be counted twice
+            require!(redemption_info.key() > proof_of_reserve.last_redemption, ErrorCode::ReservesOutOfOrder);
+            let status = check_reserve(redemption_info, custody_account)?;
+            // Delivered redemptions aren't counted as open, so attesting them could stand in for an open one whose token is missing
+            require!(status != RedemptionStatus::Delivered, ErrorCode::ReserveNotOpen);
+
+            proof_of_reserve.root = hashv(&[&proof_of_reserve.root, redemption_info.key.as_ref(), custody_account.key.as_ref()]).to_bytes();
+            proof_of_reserve.attested_count += 1;
+            proof_of_reserve.last_redemption = redemption_info.key();
+        }
+        proof_of_reserve.attested_at = now;
+
+        Ok(())
+    }
+
+    // Close the current reserve attestation once it covers every open redemption, going by the status indexes of the statuses before
+    // Delivered. Redemptions opened before status indexes existed aren't counted until they next change status, so an attestation that
+    // includes them can't be completed until then
+    pub fn complete_reserve_attestation(ctx: Context<CompleteReserveAttestation>) -> Result<()> {
+        let open_count = [
+            &ctx.accounts.deposited_index,
+            &ctx.accounts.kyc_approved_index,
+            &ctx.accounts.packed_index,
+            &ctx.accounts.shipped_index,
+        ]
+        .iter()
+        .map(|status_index| status_index.count)
+        .sum::<u64>();
+
+        let proof_of_reserve = &mut ctx.accounts.proof_of_reserve;
+        require!(!proof_of_reserve.completed, ErrorCode::ReserveAttestationCompleted);
+        require!(u64::from(proof_of_reserve.attested_count) == open_count, ErrorCode::ReservesIncomplete);
+
+        proof_of_reserve.completed = true;
+        proof_of_reserve.open_count = open_count;
+        proof_of_reserve.completed_at = ctx.accounts.config.clock()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    // Check the invariants between a redemption and its other accounts. Nothing is written, so this can be simulated for free
+    pub fn assert_invariants(ctx: Context<AssertInvariants>) -> Result<()> {
+        let redemption_info = &ctx.accounts.redemption_info;
+        let redemption_key = redemption_info.key();
+        let mint = ctx.accounts.token_mint_account.key();
+
+        let (_, redemption_bump) = Pubkey::find_program_address(&[mint.as_ref(), REDEMPTION_SEED], &crate::ID);
+        check_invariant(redemption_info.redemption_bump == redemption_bump, "redemption_info bump is canonical")?;
+        let (_, escrow_bump) = Pubkey::find_program_address(&[mint.as_ref()], &crate::ID);
+        check_invariant(redemption_info.escrow_bump.get() == escrow_bump, "escrow bump is canonical")?;
+
+        let info = redemption_info.to_account_info();
+        let rent = Rent::get()?.minimum_balance(info.data_len());
+        check_invariant(info.lamports() >= rent + redemption_info.bond_lamports, "redemption_info holds its rent and bond")?;
+
+        // Burned redemptions kept for their record no longer have a token or any other accounts
+        if redemption_info.archived_at != 0 {
+            check_invariant(redemption_info.status == RedemptionStatus::Delivered, "only delivered redemptions are archived")?;
+            check_invariant(ctx.accounts.token_mint_account.supply == 0, "archived redemption's token is burned")?;
+            return Ok(());
+        }
+
+        let custody_account = ctx.accounts.custody_account.as_ref().ok_or(ErrorCode::InvariantAccountsRequired)?;
+        check_invariant(check_reserve(&info, custody_account).is_ok(), "the asset token is in custody")?;
+
+        if redemption_info.collateral_amount > 0 {
+            let collateral_account = ctx.accounts.collateral_account.as_ref().ok_or(ErrorCode::InvariantAccountsRequired)?;
+            let (_, collateral_bump) = Pubkey::find_program_address(&[redemption_key.as_ref(), COLLATERAL_SEED], &crate::ID);
+            check_invariant(redemption_info.collateral_bump == collateral_bump, "collateral bump is canonical")?;
+            check_invariant(collateral_account.amount == redemption_info.collateral_amount, "collateral account holds the collateral")?;
+        }
+
+        check_invariant(
+            redemption_info.priority == 0 || redemption_info.status < RedemptionStatus::Shipped,
+            "shipped redemptions are out of the priority index",
+        )?;
+
+        if redemption_info.in_customer_registry {
+            let customer_registry = ctx.accounts.customer_registry.as_ref().ok_or(ErrorCode::InvariantAccountsRequired)?;
+            check_invariant(customer_registry.redemptions.contains(&redemption_key), "redemption is listed in the customer's registry")?;
+        }
+
+        Ok(())
+    }
+
+    // Record this week's physical stock-take of the bottles backing open escrows, replacing last week's. document_hash is the hash of the signed
+    // stock-take document, which BAXUS publishes off-chain
+    pub fn attest_inventory(ctx: Context<AttestInventory>, bottle_count: u32, document_hash: [u8; 32]) -> Result<()> {
+        let now = ctx.accounts.config.clock()?.unix_timestamp;
+        let week = now as u64 / SECONDS_PER_WEEK;
+        let inventory_attestation = &mut ctx.accounts.inventory_attestation;
+        require!(inventory_attestation.attested_at == 0 || week > inventory_attestation.week, ErrorCode::InventoryAlreadyAttested);
+
+        inventory_attestation.week = week;
+        inventory_attestation.bottle_count = bottle_count;
+        inventory_attestation.document_hash = document_hash;
+        inventory_attestation.attested_at = now;
+        inventory_attestation.bump = ctx.bumps.inventory_attestation;
+
+        emit!(InventoryAttested {
+            week,
+            bottle_count,
+            document_hash,
+        });
+
+        Ok(())
+    }
+
+    // Close an archived redemption once the config's retention period is up, sending its rent back to the customer
+    pub fn purge_archived(ctx: Context<PurgeArchived>) -> Result<()> {
+        let purge_at = ctx.accounts.redemption_info.archived_at + ctx.accounts.config.archive_retention_secs;
+        require!(ctx.accounts.config.clock()?.unix_timestamp >= purge_at, ErrorCode::RetentionNotElapsed);
+
+        Ok(())
+    }
+
+    // Close a Delivered redemption whose escrow account is empty or already closed, along with the escrow account if it's still there. Only
+    // redemptions that finalize by burning can be collected, and never while the token is in the cold vault
+    pub fn gc_redemption(ctx: Context<GcRedemption>) -> Result<()> {
+        let redemption_key = ctx.accounts.redemption_info.key();
+        ctx.accounts.status_index.remove(&mut ctx.accounts.status_index_page, &redemption_key);
+        deregister_customer_redemption(&ctx.accounts.redemption_info, ctx.accounts.customer_registry.as_mut(), &redemption_key)?;
+
+        let escrow = ctx.accounts.baxus_escrow_account.to_account_info();
+        if escrow.data_is_empty() {
+            return Ok(());
+        }
+
+        let amount = {
+            let data = escrow.try_borrow_data()?;
+            StateWithExtensions::<TokenAccountState>::unpack(&data)?.base.amount
+        };
+        require!(amount == 0, ErrorCode::EscrowNotEmpty);
+
+        anchor_spl::token_interface::close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::CloseAccount {
+                    account: escrow.clone(),
+                    destination: ctx.accounts.customer_payment_account.to_account_info(),
+                    authority: escrow,
+                },
+                &[&[
+                    ctx.accounts.token_mint_account.key().as_ref(),
+                    &[ctx.accounts.redemption_info.escrow_bump.get()],
+                ]]),
+        )?;
+
+        Ok(())
+    }
+
+    // Move the accounting on to the current month, creating the Accounting account if it doesn't exist yet. Instructions that record money
+    // moving do this themselves, so this only matters for closing out months that ended quietly
+    pub fn rollover_period(ctx: Context<RolloverPeriod>) -> Result<()> {
+        let accounting = &mut ctx.accounts.accounting;
+        accounting.bump = ctx.bumps.accounting;
+        accounting.roll_to(ctx.accounts.config.clock()?.unix_timestamp);
+
+        Ok(())
+    }
+
+    // Pausing only stops new redemptions from being opened - redemptions that are already in flight can still be returned or burned
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.config.paused = paused;
+
+        let action = if paused { AuditAction::Pause } else { AuditAction::Unpause };
+        ctx.accounts.audit_log.load_mut()?.append(ctx.accounts.admin.key(), action, Clock::get()?.slot);
+
+        Ok(())
+    }
+}
+
+// How initialize_redemption has to move the asset into escrow, as worked out by check_redemption_request
+pub struct DepositPlan {
+    // The escrow account is created frozen and has to be thawed with the freeze authority PDA first
+    pub thaw_escrow: bool,
+    // The customer token account is frozen, and has to be thawed with the freeze authority PDA before the token can leave it
+    pub thaw_customer_account: bool,
+    // The customer token account's CPI guard means the escrow account has to move the token as the customer's delegate
+    pub transfer_as_delegate: bool,
+}
+
+// Return the asset token to the customer for return_asset_token and finalize_rejection, leaving a receipt with the given outcome
+fn return_to_customer<'info>(ctx: Context<'_, '_, '_, 'info, ReturnAssetToken<'info>>, outcome: RedemptionOutcome) -> Result<()> {
+    check_operator(&ctx.accounts.config, &ctx.accounts.redemption_info, ctx.accounts.partner.as_deref(), &ctx.accounts.authority.key())?;
+
+    let redemption_key = ctx.accounts.redemption_info.key();
+    ctx.accounts.status_index.remove(&mut ctx.accounts.status_index_page, &redemption_key);
+    remove_priority(&mut ctx.accounts.redemption_info, ctx.accounts.priority_index.as_mut(), &redemption_key)?;
+    deregister_customer_redemption(&ctx.accounts.redemption_info, ctx.accounts.customer_registry.as_mut(), &redemption_key)?;
+    ctx.accounts.receipt.record(
+        ctx.accounts.token_mint_account.key(),
+        &ctx.accounts.redemption_info,
+        outcome,
+        ctx.accounts.config.clock()?.unix_timestamp,
+        ctx.bumps.receipt,
+    );
+
+    release_escrow(
+        EscrowRelease::Transfer(ctx.accounts.customer_token_account.to_account_info()),
+        &mut ctx.accounts.baxus_escrow_account,
+        &ctx.accounts.token_mint_account,
+        ctx.accounts.redemption_info.escrow_bump,
+        RentRefund {
+            redemption_info: ctx.accounts.redemption_info.to_account_info(),
+            customer: ctx.accounts.customer_payment_account.to_account_info(),
+            treasury: ctx.accounts.treasury.as_ref().map(|treasury| treasury.to_account_info()),
+            treasury_share_bps: ctx.accounts.config.treasury_rent_share_bps,
+        },
+        &ctx.accounts.token_program,
+        ctx.remaining_accounts,
+    )?;
+
+    Ok(())
+}
+
+// Open a redemption for initialize_redemption_v2 and initialize_redemption_for, once they've checked who is calling. marketplace is the
+// marketplace program that opened the redemption at purchase time, or the default pubkey
+fn open_redemption<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitializeRedemption<'info>>,
+    contact_commitment: [u8; 32],
+    insure: Option<bool>,
+    service_tier: ServiceTier,
+    idempotency_key: [u8; 32],
+    marketplace: Pubkey,
+) -> Result<()> {
+    // redemption_info is init_if_needed so that retries get this far - only a retry with the same key and customer gets to succeed
+    let existing = &ctx.accounts.redemption_info;
+    if existing.customer_token_account != Pubkey::default() {
+        require!(
+            idempotency_key != [0; 32]
+                && existing.idempotency_key == idempotency_key
+                && existing.customer_payment_account == ctx.accounts.customer_payment_account.key(),
+            ErrorCode::RedemptionAlreadyOpen
+        );
+        return Ok(());
+    }
+
+    let deposit_plan = check_redemption_request(
+        &ctx.accounts.config,
+        &ctx.accounts.token_mint_account,
+        &ctx.accounts.customer_token_account,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.baxus_escrow_account.key(),
+        &ctx.accounts.freeze_authority.key(),
+    )?;
+    check_risk_score(&ctx.accounts.config, ctx.accounts.risk_score.as_deref())?;
+
+    let redemption_counter = &mut ctx.accounts.redemption_counter;
+    redemption_counter.bump = ctx.bumps.redemption_counter;
+    redemption_counter.last_id += 1;
+
+    let redemption_info = &mut ctx.accounts.redemption_info;
+    redemption_info.redemption_id = redemption_counter.last_id;
+    redemption_info.customer_token_account = ctx.accounts.customer_token_account.key();
+    redemption_info.customer_payment_account = ctx.accounts.customer_payment_account.key();
+    redemption_info.contact_commitment = contact_commitment;
+    redemption_info.idempotency_key = idempotency_key;
+    redemption_info.marketplace = marketplace;
+    redemption_info.escrow_bump = EscrowBump::new(
+        &ctx.accounts.token_mint_account.key(),
+        &ctx.accounts.baxus_escrow_account.key(),
+        ctx.bumps.baxus_escrow_account,
+    )?;
+    redemption_info.redemption_bump = ctx.bumps.redemption_info;
+    redemption_info.record_transition(RedemptionStatus::Deposited, &ctx.accounts.config.clock()?);
+
+    let asset_snapshot = snapshot_asset_metadata(&ctx.accounts.token_mint_account.to_account_info())?;
+    redemption_info.asset_name = asset_snapshot.name;
+    redemption_info.asset_symbol = asset_snapshot.symbol;
+    redemption_info.asset_collection = asset_snapshot.collection;
+
+    // Assets whose collection can't be read, like legacy SPL Token mints, are looked up under the default pubkey, so a CollectionConfig the
+    // admin sets up for it is their policy
+    let collection_config = load_collection_config(&ctx.accounts.collection_config, &redemption_info.asset_collection)?;
+
+    if let Some(partner) = &ctx.accounts.partner {
+        require!(
+            ctx.accounts.partner_authority.as_ref().is_some_and(|authority| authority.key() == partner.authority),
+            ErrorCode::PartnerSignatureRequired
+        );
+        require!(
+            partner.allowed_collections.is_empty() || redemption_info.asset_collection != Pubkey::default(),
+            ErrorCode::CollectionUnknown
+        );
+        require!(partner.allows_collection(&redemption_info.asset_collection), ErrorCode::CollectionNotAllowed);
+        redemption_info.partner_id = partner.partner_id;
+    }
+
+    redemption_info.valuation_usd_cents = ctx.accounts.appraisal.usd_value_cents;
+    redemption_info.valued_at = ctx.accounts.appraisal.appraised_at;
+
+    // The customer's saved choices fill in for ones they didn't make when opening the redemption
+    let profile = ctx.accounts.customer_profile.as_deref();
+    let insure = insure.unwrap_or(profile.is_some_and(|customer_profile| customer_profile.insure));
+    if let Some(customer_profile) = profile {
+        redemption_info.profile_jurisdiction = customer_profile.jurisdiction;
+        redemption_info.fee_currency = customer_profile.fee_currency;
+    }
+
+    let premium_usd_cents = if insure {
+        require!(ctx.accounts.config.insurance_pool != Pubkey::default(), ErrorCode::InsuranceUnavailable);
+        redemption_info.insured = true;
+        redemption_info.coverage_usd_cents = redemption_info.valuation_usd_cents;
+        (redemption_info.valuation_usd_cents as u128 * ctx.accounts.config.insurance_premium_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    } else {
+        0
+    };
+
+    let now = ctx.accounts.config.clock()?.unix_timestamp;
+    let config = &ctx.accounts.config;
+    redemption_info.created_at = now;
+    let redemption_ttl = collection_config.as_ref().map_or(config.redemption_ttl, |collection_config| collection_config.redemption_ttl);
+    redemption_info.expires_at = if redemption_ttl > 0 { now + redemption_ttl } else { 0 };
+    match &collection_config {
+        Some(collection_config) => {
+            redemption_info.base_fee_usd_cents = collection_config.base_fee_usd_cents;
+            redemption_info.finalize_mode = collection_config.finalize_mode;
+            redemption_info.required_attestations = collection_config.required_attestations;
+            redemption_info.hook_program = collection_config.hook_program;
+            redemption_info.creator_share_bps = collection_config.creator_share_bps;
+            redemption_info.brand_authority = collection_config.brand_authority;
+            redemption_info.enhanced_approval = collection_config.requires_enhanced_approval(redemption_info.valuation_usd_cents);
+        }
+        None => redemption_info.base_fee_usd_cents = config.base_fee_usd_cents,
+    }
+
+    redemption_info.service_tier = service_tier;
+    if service_tier == ServiceTier::Express {
+        redemption_info.express_fee_usd_cents = config.express_fee_usd_cents;
+    }
+
+    emit!(RedemptionOpened {
+        redemption_info: redemption_info.key(),
+        redemption_id: redemption_info.redemption_id,
+        event_sequence: redemption_info.next_event_sequence(),
+        service_tier,
+        marketplace,
+    });
+    redemption_info.bond_lamports = config.bond_lamports;
+
+    let redemption_key = ctx.accounts.redemption_info.key();
+    ctx.accounts.status_index.insert(&mut ctx.accounts.status_index_page, redemption_key)?;
+    ctx.accounts.redemption_info.status_index_page = ctx.accounts.status_index_page.page;
+
+    let customer_registry = &mut ctx.accounts.customer_registry;
+    customer_registry.customer = ctx.accounts.customer_payment_account.key();
+    customer_registry.bump = ctx.bumps.customer_registry;
+    customer_registry.insert(redemption_key)?;
+    ctx.accounts.redemption_info.in_customer_registry = true;
+
+    let escrow_link = &mut ctx.accounts.escrow_link;
+    escrow_link.redemption_info = redemption_key;
+    escrow_link.token_mint = ctx.accounts.token_mint_account.key();
+    escrow_link.bump = ctx.bumps.escrow_link;
+
+    emit!(ctx.accounts.redemption_info.status_changed(redemption_key));
+    call_transition_hook(&ctx.accounts.redemption_info, redemption_key, ctx.accounts.hook_program.as_ref())?;
+    write_status_memo(&ctx.accounts.redemption_info, ctx.accounts.memo_program.as_ref())?;
+
+    if config.bond_lamports > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.redemption_info.to_account_info(),
+                }),
+            config.bond_lamports,
+        )?;
+    }
+
+    if premium_usd_cents > 0 {
+        let (Some(fee_mint), Some(payer_fee_account), Some(insurance_pool_fee_account), Some(fee_token_program)) = (
+            &ctx.accounts.fee_mint,
+            &ctx.accounts.payer_fee_account,
+            &ctx.accounts.insurance_pool_fee_account,
+            &ctx.accounts.fee_token_program,
+        ) else {
+            return err!(ErrorCode::InsuranceUnavailable);
+        };
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                fee_token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: payer_fee_account.to_account_info(),
+                    mint: fee_mint.to_account_info(),
+                    to: insurance_pool_fee_account.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                }),
+            usd_cents_to_fee_amount(premium_usd_cents, fee_mint.decimals)?,
+            fee_mint.decimals,
+        )?;
+    }
+
+    if deposit_plan.thaw_escrow {
+        anchor_spl::token_interface::thaw_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::ThawAccount {
+                    account: ctx.accounts.baxus_escrow_account.to_account_info(),
+                    mint: ctx.accounts.token_mint_account.to_account_info(),
+                    authority: ctx.accounts.freeze_authority.to_account_info(),
+                },
+                &[&[FREEZE_AUTHORITY_SEED, &[ctx.bumps.freeze_authority]]]),
+        )?;
+    }
+
+    // The customer's account is left thawed once the token has left it, so the token can be returned to it
+    if deposit_plan.thaw_customer_account {
+        anchor_spl::token_interface::thaw_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::ThawAccount {
+                    account: ctx.accounts.customer_token_account.to_account_info(),
+                    mint: ctx.accounts.token_mint_account.to_account_info(),
+                    authority: ctx.accounts.freeze_authority.to_account_info(),
+                },
+                &[&[FREEZE_AUTHORITY_SEED, &[ctx.bumps.freeze_authority]]]),
+        )?;
+    }
+
+    if deposit_plan.transfer_as_delegate {
+        transfer_asset(
+            &ctx.accounts.token_program,
+            ctx.accounts.customer_token_account.to_account_info(),
+            &ctx.accounts.token_mint_account,
+            ctx.accounts.baxus_escrow_account.to_account_info(),
+            ctx.accounts.baxus_escrow_account.to_account_info(),
+            ctx.remaining_accounts,
+            &[&[
+                ctx.accounts.token_mint_account.key().as_ref(),
+                &[ctx.bumps.baxus_escrow_account],
+            ]],
+        )?;
+    } else {
+        transfer_asset(
+            &ctx.accounts.token_program,
+            ctx.accounts.customer_token_account.to_account_info(),
+            &ctx.accounts.token_mint_account,
+            ctx.accounts.baxus_escrow_account.to_account_info(),
+            ctx.accounts.customer_payment_account.to_account_info(),
+            ctx.remaining_accounts,
+            &[],
+        )?;
+    }
+
+    Ok(())
+}
+
+// Make sure the customer has passed BAXUS's risk check recently enough, if the config requires one
+fn check_risk_score(config: &Config, risk_score: Option<&RiskScore>) -> Result<()> {
+    if !config.risk_check_required {
+        return Ok(());
+    }
+
+    let risk_score = risk_score.ok_or(ErrorCode::RiskScoreRequired)?;
+    require!(risk_score.score <= config.max_risk_score, ErrorCode::RiskScoreTooHigh);
+    if config.risk_score_max_age_secs > 0 {
+        let now = config.clock()?.unix_timestamp;
+        require!(now - risk_score.assessed_at <= config.risk_score_max_age_secs, ErrorCode::RiskScoreStale);
+    }
+
+    Ok(())
+}
+
+// Checks shared by initialize_redemption and validate_redemption_request, so the pre-flight simulation can't drift from the real deposit
+fn check_redemption_request(
+    config: &Config,
+    token_mint_account: &InterfaceAccount<Mint>,
+    customer_token_account: &InterfaceAccount<TokenAccount>,
+    payer: &AccountInfo,
+    baxus_escrow_account: &Pubkey,
+    freeze_authority: &Pubkey,
+) -> Result<DepositPlan> {
+    require!(!config.paused, ErrorCode::ProgramPaused);
+
+    // Wrapped SOL isn't an asset, and its token accounts hold their balance as lamports, which the escrow accounting and close logic don't
+    // account for
+    require!(
+        token_mint_account.key() != anchor_spl::token::spl_token::native_mint::ID
+            && token_mint_account.key() != anchor_spl::token_2022::spl_token_2022::native_mint::ID,
+        ErrorCode::NativeMintNotSupported
+    );
+
+    // The admin decides which token programs' assets are accepted. A program also has to be one the token interface accounts can be
+    // owned by, which for now is SPL Token and Token-2022
+    require!(config.allowed_token_programs.contains(token_mint_account.to_account_info().owner), ErrorCode::TokenProgramNotAllowed);
+
+    validate_mint_extensions(&token_mint_account.to_account_info(), config)?;
+
+    require!(customer_token_account.amount >= AssetAmount::WHOLE.get(), ErrorCode::AssetNotInCustomerAccount);
+    require!(payer.lamports() >= config.bond_lamports, ErrorCode::InsufficientFundsForBond);
+
+    // Nobody but the customer should be able to move the token or close the account it's returned to, which would leave audits with a
+    // third party in the asset's history. The escrow account is the one delegate we accept, since CPI-guarded accounts need it below
+    let delegate: Option<Pubkey> = customer_token_account.delegate.into();
+    require!(delegate.is_none() || delegate == Some(*baxus_escrow_account), ErrorCode::ForeignDelegate);
+    let close_authority: Option<Pubkey> = customer_token_account.close_authority.into();
+    require!(close_authority.is_none() || close_authority == Some(customer_token_account.owner), ErrorCode::ForeignCloseAuthority);
+
+    // Some Token-2022 mints create every new token account frozen, which would leave the escrow account unable to receive the token. We can
+    // only thaw it if BAXUS made this program's freeze authority PDA the mint's freeze authority
+    let thaw_escrow = frozen_by_default(&token_mint_account.to_account_info())?;
+    if thaw_escrow {
+        require!(
+            token_mint_account.freeze_authority == Some(*freeze_authority).into(),
+            ErrorCode::EscrowFrozenByDefault
+        );
+    }
+
+    // The customer's account is frozen too on mints that create every account frozen. We can only thaw it if the mint's freeze authority is
+    // our freeze authority PDA - accounts frozen by anything else, such as Token Metadata's edition PDA, stay frozen
+    let thaw_customer_account = customer_token_account.is_frozen();
+    if thaw_customer_account {
+        require!(token_mint_account.freeze_authority == Some(*freeze_authority).into(), ErrorCode::AssetFrozen);
+    }
+
+    // Token-2022 accounts with the CPI guard enabled won't let their owner transfer out of them through a CPI. The customer can still approve
+    // the escrow account as a delegate for the token in an earlier instruction of the same transaction, and then the escrow account moves
+    // the token itself
+    let transfer_as_delegate = cpi_guard_enabled(&customer_token_account.to_account_info())?;
+    if transfer_as_delegate {
+        require!(
+            customer_token_account.delegate == Some(*baxus_escrow_account).into() && customer_token_account.delegated_amount >= AssetAmount::WHOLE.get(),
+            ErrorCode::CpiGuardEnabled
+        );
+    }
+
+    Ok(DepositPlan { thaw_escrow, thaw_customer_account, transfer_as_delegate })
+}
+
+// Enforce the config's CPI-caller policy on a customer-facing instruction. When we're called through a CPI, the transaction-level instruction
+// currently executing belongs to the outermost program in the call chain - the program the customer's wallet actually showed them. That's
+// only our direct caller when it called us straight from the transaction, so a trusted caller can't be used to launder a deeper chain
+fn check_cpi_caller(config: &Config, instructions: &AccountInfo) -> Result<()> {
+    let stack_height = get_stack_height();
+    if config.cpi_policy == CpiPolicy::AllowAll || stack_height <= TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(());
+    }
+    require!(stack_height == TRANSACTION_LEVEL_STACK_HEIGHT + 1, ErrorCode::CpiCallerNotAllowed);
+
+    let current_index = load_current_index_checked(instructions)?;
+    let caller = load_instruction_at_checked(current_index as usize, instructions)?.program_id;
+    require!(
+        config.cpi_policy == CpiPolicy::TrustedCallersOnly && config.trusted_callers.contains(&caller),
+        ErrorCode::CpiCallerNotAllowed
+    );
+
+    Ok(())
+}
+
+// CPI into the redemption's hook program, if it has one, to tell it the redemption just moved into its current status. The payload is the
+// instruction discriminator for an Anchor instruction named on_redemption_transition, followed by the redemption's key and new status
+fn call_transition_hook(redemption_info: &RedemptionInfo, redemption_key: Pubkey, hook_program: Option<&UncheckedAccount>) -> Result<()> {
+    if redemption_info.hook_program == Pubkey::default() {
+        return Ok(());
+    }
+
+    let hook_program = hook_program.filter(|hook_program| hook_program.key() == redemption_info.hook_program).ok_or(ErrorCode::HookProgramRequired)?;
+
+    let mut data = anchor_lang::solana_program::hash::hash(b"global:on_redemption_transition").to_bytes()[..8].to_vec();
+    data.extend_from_slice(redemption_key.as_ref());
+    data.push(redemption_info.status as u8);
+
+    invoke(
+        &Instruction { program_id: redemption_info.hook_program, accounts: vec![], data },
+        &[hook_program.to_account_info()],
+    )?;
+
+    Ok(())
+}
+
+// Length of a groth16 proof over BN254 with uncompressed points: A (64 bytes), B (128 bytes) and C (64 bytes)
+pub const GROTH16_PROOF_LEN: usize = 256;
+
+// The public inputs a KYC proof is checked against, as big-endian BN254 scalars: the approved providers' root, and a hash of the redemption
+// and customer wallet. The hash has its top byte cleared so it's always less than the scalar field modulus
+fn kyc_public_inputs(config: &Config, redemption_key: Pubkey, customer: Pubkey) -> [[u8; 32]; 2] {
+    let mut binding = hashv(&[redemption_key.as_ref(), customer.as_ref()]).to_bytes();
+    binding[0] = 0;
+
+    [config.kyc_providers_root, binding]
+}
+
+// Where the asset token goes when it leaves its escrow account
+pub enum EscrowRelease<'info> {
+    // To another token account
+    Transfer(AccountInfo<'info>),
+    Burn,
+}
+
+// Where the rent from a closed escrow account goes. It's split between the customer and the treasury by the config's
+// treasury_rent_share_bps, passing through redemption_info on the way, since that's an account the program owns and so can pay out of
+pub struct RentRefund<'info> {
+    pub redemption_info: AccountInfo<'info>,
+    pub customer: AccountInfo<'info>,
+    // Only needed if the treasury takes a share
+    pub treasury: Option<AccountInfo<'info>>,
+    pub treasury_share_bps: u16,
+}
+
+impl<'info> RentRefund<'info> {
+    // Pay out rent that's been moved into redemption_info
+    fn pay_out(self, rent: u64) -> Result<()> {
+        let treasury_share = (rent as u128 * self.treasury_share_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        if treasury_share > 0 {
+            let treasury = self.treasury.ok_or(ErrorCode::TreasuryRequired)?;
+            **self.redemption_info.try_borrow_mut_lamports()? -= treasury_share;
+            **treasury.try_borrow_mut_lamports()? += treasury_share;
+        }
+
+        **self.redemption_info.try_borrow_mut_lamports()? -= rent - treasury_share;
+        **self.customer.try_borrow_mut_lamports()? += rent - treasury_share;
+
+        Ok(())
+    }
+}
+
+// Move the asset token out of its escrow account and close the escrow account, refunding its rent. Returning, burning, expiring, cancelling,
+// self-returning and consolidating a redemption all go through here, so the escrow signer and close logic only live in one place. We close
+// the escrow account with a CPI because the close attribute doesn't work on token accounts
+fn release_escrow<'info>(
+    release: EscrowRelease<'info>,
+    escrow: &mut InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    escrow_bump: EscrowBump,
+    rent_refund: RentRefund<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    hook_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    let mint_key = mint.key();
+    let bump = [escrow_bump.get()];
+    let signer_seeds: &[&[u8]] = &[mint_key.as_ref(), &bump];
+
+    match release {
+        EscrowRelease::Transfer(to) => transfer_asset(
+            token_program,
+            escrow.to_account_info(),
+            mint,
+            to,
+            escrow.to_account_info(),
+            hook_accounts,
+            &[signer_seeds],
+        )?,
+        EscrowRelease::Burn => anchor_spl::token_interface::burn(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                anchor_spl::token_interface::Burn {
+                    mint: mint.to_account_info(),
+                    from: escrow.to_account_info(),
+                    authority: escrow.to_account_info(),
+                },
+                &[signer_seeds]),
+            AssetAmount::WHOLE.get(),
+        )?,
+    }
+
+    require_escrow_drained(escrow)?;
+
+    let rent = escrow.to_account_info().lamports();
+    anchor_spl::token_interface::close_account(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            anchor_spl::token_interface::CloseAccount {
+                account: escrow.to_account_info(),
+                destination: rent_refund.redemption_info.clone(),
+                authority: escrow.to_account_info(),
+            },
+            &[signer_seeds]),
+    )?;
+
+    rent_refund.pay_out(rent)
+}
+
+// Move the asset token, approving the transfer with WNS first if it's a WNS mint. hook_accounts are the instruction's remaining accounts,
+// which for a mint with a transfer hook have to include the hook program and everything its extra account metas list
+fn transfer_asset<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    hook_accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if transfer_hook_program(&mint.to_account_info())? == Some(wns_program::ID) {
+        approve_wns_transfer(&mint.key(), hook_accounts)?;
+    }
+
+    invoke_transfer_checked(
+        token_program.key,
+        from,
+        mint.to_account_info(),
+        to,
+        authority,
+        hook_accounts,
+        AssetAmount::WHOLE.get(),
+        mint.decimals,
+        signer_seeds,
+    )?;
+
+    Ok(())
+}
+
+// Approve a WNS transfer at a sale price of zero, so no royalties are due. hook_accounts start with the WNS program, followed by
+// approve_transfer's accounts in order - the payer and authority sign the transaction themselves, so their signatures carry through
+fn approve_wns_transfer(mint: &Pubkey, hook_accounts: &[AccountInfo]) -> Result<()> {
+    let accounts = hook_accounts.get(..1 + WNS_APPROVE_TRANSFER_ACCOUNTS).ok_or(ErrorCode::WnsAccountsRequired)?;
+    require_keys_eq!(accounts[0].key(), wns_program::ID, ErrorCode::WnsAccountsRequired);
+    require_keys_eq!(accounts[3].key(), *mint, ErrorCode::WnsAccountsRequired);
+
+    let mut data = anchor_lang::solana_program::hash::hash(b"global:approve_transfer").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&0u64.to_le_bytes());
+    let metas = accounts[1..]
+        .iter()
+        .map(|account| AccountMeta { pubkey: account.key(), is_signer: account.is_signer, is_writable: account.is_writable })
+        .collect();
+
+    invoke(&Instruction { program_id: wns_program::ID, accounts: metas, data }, accounts)?;
+
+    Ok(())
+}
+
+// The transfer hook program of a Token-2022 mint, if it has one
+fn transfer_hook_program(mint: &AccountInfo) -> Result<Option<Pubkey>> {
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(None);
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    Ok(transfer_hook::get_program_id(&mint_state))
+}
+
+// Check that an escrow token account is empty before closing it. The token program refuses to close an account with a balance anyway, but
+// checking here means a change that leaves tokens behind (a multi-asset escrow, an amount other than one) fails with an error that says so
+fn require_escrow_drained(escrow: &mut InterfaceAccount<TokenAccount>) -> Result<()> {
+    escrow.reload()?;
+    require!(escrow.amount == 0, ErrorCode::EscrowNotDrained);
+
+    Ok(())
+}
+
+// Write a memo describing the status the redemption just moved into, if the caller passed the memo program. Wallets show memos in their
+// transaction history, so it's kept short and readable rather than machine-parseable
+fn write_status_memo<'info>(redemption_info: &RedemptionInfo, memo_program: Option<&Program<'info, Memo>>) -> Result<()> {
+    let Some(memo_program) = memo_program else {
+        return Ok(());
+    };
+
+    let mut memo = format!("BAXUS: {}", redemption_info.status.label());
+    if redemption_info.status == RedemptionStatus::Shipped {
+        memo.push_str(", documents committed");
+    }
+
+    anchor_spl::memo::build_memo(CpiContext::new(memo_program.to_account_info(), anchor_spl::memo::BuildMemo {}), memo.as_bytes())
+}
+
+// Burn every escrowed certificate attached to a redemption, and close its escrow account. The remaining accounts hold each escrowed
+// certificate's mint and escrow account (both writable), in pairs, in the order the certificates were attached. Each certificate goes to
+// the token program that owns its mint, which is either the asset's token program or certificate_token_program
+fn burn_escrowed_attachments<'info>(
+    redemption_info: &RedemptionInfo,
+    remaining_accounts: &[AccountInfo<'info>],
+    token_program: &AccountInfo<'info>,
+    certificate_token_program: Option<&AccountInfo<'info>>,
+    customer_payment_account: &AccountInfo<'info>,
+) -> Result<()> {
+    let mut accounts = remaining_accounts.chunks_exact(2);
+    for attachment in redemption_info.attachments.iter().filter(|attachment| attachment.escrowed) {
+        let pair = accounts.next().ok_or(ErrorCode::AttachmentAccountsRequired)?;
+        let (certificate_mint, certificate_escrow) = (&pair[0], &pair[1]);
+        require_keys_eq!(certificate_mint.key(), attachment.mint, ErrorCode::InvalidAttachmentEscrow);
+
+        let signer_seeds: &[&[u8]] = &[attachment.mint.as_ref(), ATTACHMENT_ESCROW_SEED, &[attachment.escrow_bump]];
+        let escrow_key = Pubkey::create_program_address(signer_seeds, &crate::ID).map_err(|_| error!(ErrorCode::InvalidAttachmentEscrow))?;
+        require_keys_eq!(certificate_escrow.key(), escrow_key, ErrorCode::InvalidAttachmentEscrow);
+
+        let certificate_program = if certificate_mint.owner == token_program.key {
+            token_program
+        } else {
+            certificate_token_program
+                .filter(|program| certificate_mint.owner == program.key)
+                .ok_or(ErrorCode::CertificateTokenProgramRequired)?
+        };
+
+        anchor_spl::token_interface::burn(
+            CpiContext::new_with_signer(
+                certificate_program.clone(),
+                anchor_spl::token_interface::Burn {
+                    mint: certificate_mint.clone(),
+                    from: certificate_escrow.clone(),
+                    authority: certificate_escrow.clone(),
+                },
+                &[signer_seeds]),
+            AssetAmount::WHOLE.get(),
+        )?;
+
+        anchor_spl::token_interface::close_account(
+            CpiContext::new_with_signer(
+                certificate_program.clone(),
+                anchor_spl::token_interface::CloseAccount {
+                    account: certificate_escrow.clone(),
+                    destination: customer_payment_account.clone(),
+                    authority: certificate_escrow.clone(),
+                },
+                &[signer_seeds]),
+        )?;
+    }
+
+    Ok(())
+}
+
+// The accounts for paying out of the treasury's fee account, with the rebate authority PDA the treasury has approved as its delegate
+pub struct TreasuryPayer<'info> {
+    fee_token_program: AccountInfo<'info>,
+    fee_mint: AccountInfo<'info>,
+    decimals: u8,
+    treasury_fee_account: AccountInfo<'info>,
+    rebate_authority: AccountInfo<'info>,
+    rebate_authority_bump: u8,
+}
+
+impl<'info> TreasuryPayer<'info> {
+    // Pay an amount in US cents into a fee account, doing nothing if it comes to less than one base unit
+    pub fn pay(&self, to: AccountInfo<'info>, usd_cents: u64) -> Result<()> {
+        let amount = usd_cents_to_fee_amount(usd_cents, self.decimals)?;
+        if amount == 0 {
+            return Ok(());
+        }
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.fee_token_program.clone(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.treasury_fee_account.clone(),
+                    mint: self.fee_mint.clone(),
+                    to,
+                    authority: self.rebate_authority.clone(),
+                },
+                &[&[REBATE_AUTHORITY_SEED, &[self.rebate_authority_bump]]]),
+            amount,
+            self.decimals,
+        )
+    }
+}
+
+// Check that a token account holds a redemption's asset token, and is where the redemption says its token is held, returning its status
+fn check_reserve(redemption_info: &AccountInfo, custody_account: &AccountInfo) -> Result<RedemptionStatus> {
+    require_keys_eq!(*redemption_info.owner, crate::ID, ErrorCode::InvalidReserve);
+    require!(
+        *custody_account.owner == anchor_spl::token::ID || *custody_account.owner == anchor_spl::token_2022::ID,
+        ErrorCode::InvalidReserve
+    );
+
+    let custody = {
+        let data = custody_account.try_borrow_data()?;
+        StateWithExtensions::<TokenAccountState>::unpack(&data)?.base
+    };
+    let redemption = VersionedRedemptionInfo::try_deserialize(&mut &redemption_info.try_borrow_data()?[..])?;
+
+    let redemption_key = Pubkey::create_program_address(
+        &[custody.mint.as_ref(), REDEMPTION_SEED, &[redemption.redemption_bump]],
+        &crate::ID,
+    )
+    .map_err(|_| error!(ErrorCode::InvalidReserve))?;
+    require_keys_eq!(redemption_info.key(), redemption_key, ErrorCode::InvalidReserve);
+
+    let custody_key = if redemption.cold_vault_account != Pubkey::default() {
+        redemption.cold_vault_account
+    } else {
+        Pubkey::create_program_address(&[custody.mint.as_ref(), &[redemption.escrow_bump.get()]], &crate::ID)
+            .map_err(|_| error!(ErrorCode::InvalidReserve))?
+    };
+    require_keys_eq!(custody_account.key(), custody_key, ErrorCode::InvalidReserve);
+    require!(custody.amount == AssetAmount::WHOLE.get(), ErrorCode::ReserveMissing);
+
+    Ok(redemption.status)
+}
+
+// Fail with InvariantViolated, logging the invariant that doesn't hold
+fn check_invariant(holds: bool, invariant: &str) -> Result<()> {
+    if !holds {
+        msg!("Invariant violated: {}", invariant);
+        return err!(ErrorCode::InvariantViolated);
+    }
+
+    Ok(())
+}
+
+// The master edition and edition number of a print, from its Metaplex edition account, or None if the asset is a master edition
+fn print_edition_of(edition: &AccountInfo) -> Result<Option<(Pubkey, u64)>> {
+    // Metaplex's account key for a print's Edition account
+    const EDITION_V1: u8 = 1;
+
+    let data = edition.try_borrow_data()?;
+    if data.first() != Some(&EDITION_V1) {
+        return Ok(None);
+    }
+
+    let mut rest = &data[1..];
+    let (parent, edition_number) = <(Pubkey, u64)>::deserialize(&mut rest).map_err(|_| error!(ErrorCode::InvalidAssetMetadata))?;
+    Ok(Some((parent, edition_number)))
+}
+
+// The verified creators listed in an asset's Metaplex metadata account, with their shares
+fn verified_creators(metadata: &AccountInfo) -> Result<Vec<(Pubkey, u8)>> {
+    let data = metadata.try_borrow_data()?;
+    // Skip the key, update authority and mint, then the name, symbol, URI and seller fee basis points, to get to the creators
+    let mut rest = data.get(1 + 32 + 32..).ok_or(ErrorCode::InvalidAssetMetadata)?;
+    let parse = |rest: &mut &[u8]| -> std::io::Result<Option<Vec<(Pubkey, bool, u8)>>> {
+        for _ in 0..3 {
+            String::deserialize(rest)?;
+        }
+        u16::deserialize(rest)?;
+        Option::deserialize(rest)
+    };
+    let creators = parse(&mut rest).map_err(|_| error!(ErrorCode::InvalidAssetMetadata))?.unwrap_or_default();
+
+    Ok(creators.into_iter().filter(|(_, verified, _)| *verified).map(|(address, _, share)| (address, share)).collect())
+}
+
+// Split a share of a redemption's fees between the asset's verified creators in proportion to their metadata shares, paying each into the fee
+// account passed for them, in the same order. Returns what was paid out, which is nothing if the asset has no verified creators
+fn pay_creator_shares<'info>(
+    treasury: &TreasuryPayer<'info>,
+    creators: &[(Pubkey, u8)],
+    creator_fee_accounts: &[AccountInfo<'info>],
+    fee_mint: &Pubkey,
+    usd_cents: u64,
+) -> Result<u64> {
+    let total_shares: u64 = creators.iter().map(|(_, share)| *share as u64).sum();
+    if total_shares == 0 {
+        return Ok(0);
+    }
+    require!(creator_fee_accounts.len() >= creators.len(), ErrorCode::CreatorAccountsRequired);
+
+    let mut paid = 0;
+    for ((creator, share), creator_fee_account) in creators.iter().zip(creator_fee_accounts) {
+        require!(
+            *creator_fee_account.owner == anchor_spl::token::ID || *creator_fee_account.owner == anchor_spl::token_2022::ID,
+            ErrorCode::InvalidCreatorAccount
+        );
+        {
+            let data = creator_fee_account.try_borrow_data()?;
+            let token_account = StateWithExtensions::<TokenAccountState>::unpack(&data)?.base;
+            require!(token_account.mint == *fee_mint && token_account.owner == *creator, ErrorCode::InvalidCreatorAccount);
+        }
+
+        let creator_usd_cents = (usd_cents as u128 * *share as u128 / total_shares as u128) as u64;
+        treasury.pay(creator_fee_account.clone(), creator_usd_cents)?;
+        paid += creator_usd_cents;
+    }
+
+    Ok(paid)
+}
+
+// Whether an account is owned by this program, or is a redemption's escrow token account - the token account PDA of its own mint
+fn is_program_account(account: &AccountInfo) -> Result<bool> {
+    if *account.owner == crate::ID {
+        return Ok(true);
+    }
+    if *account.owner != anchor_spl::token::ID && *account.owner != anchor_spl::token_2022::ID {
+        return Ok(false);
+    }
+
+    let data = account.try_borrow_data()?;
+    let Ok(token_account) = StateWithExtensions::<TokenAccountState>::unpack(&data) else {
+        return Ok(false);
+    };
+    let (escrow_key, _) = Pubkey::find_program_address(&[token_account.base.mint.as_ref()], &crate::ID);
+
+    Ok(escrow_key == account.key())
+}
+
+// Make sure initialize_redemption_for is being called through a CPI from a whitelisted marketplace, and return the marketplace's program ID
+fn check_marketplace_caller(config: &Config, instructions: &AccountInfo) -> Result<Pubkey> {
+    require!(get_stack_height() > TRANSACTION_LEVEL_STACK_HEIGHT, ErrorCode::MarketplaceNotAllowed);
+
+    let current_index = load_current_index_checked(instructions)?;
+    let caller = load_instruction_at_checked(current_index as usize, instructions)?.program_id;
+    require!(config.marketplaces.contains(&caller), ErrorCode::MarketplaceNotAllowed);
+
+    Ok(caller)
+}
+
+// Check the Token-2022 extensions of the asset's mint against the BAXUS metadata authority and collection in the config. Legacy SPL Token
+// mints keep their collection in Metaplex metadata, which we don't read, so they fail the collection check whenever the config sets one
+fn validate_mint_extensions(mint: &AccountInfo, config: &Config) -> Result<()> {
+    if *mint.owner != anchor_spl::token_2022::ID {
+        if config.collection != Pubkey::default() {
+            enforce_validation(config, mint.key(), ErrorCode::CollectionUnknown)?;
+        }
+        return Ok(());
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+
+    // Non-transferable tokens can only get into an account by being minted there, so they could never be moved into escrow
+    require!(mint_state.get_extension::<NonTransferable>().is_err(), ErrorCode::NonTransferableMint);
+
+    // The metadata and collection checks below are BAXUS policy rather than something escrow depends on, so they're only enforced in strict
+    // mode
+    let mut in_collection = false;
+
+    if let Ok(metadata_pointer) = mint_state.get_extension::<MetadataPointer>() {
+        if config.metadata_authority != Pubkey::default() {
+            // We only read metadata stored in the mint itself - metadata held by another program can't be read from the mint's extensions
+            if Option::<Pubkey>::from(metadata_pointer.metadata_address) != Some(mint.key()) {
+                enforce_validation(config, mint.key(), ErrorCode::UnsupportedMetadataLocation)?;
+            } else {
+                let metadata = mint_state.get_variable_len_extension::<TokenMetadata>()?;
+                if Option::<Pubkey>::from(metadata.update_authority) != Some(config.metadata_authority) {
+                    enforce_validation(config, mint.key(), ErrorCode::InvalidMetadataAuthority)?;
+                }
+                if metadata.name.is_empty() || metadata.symbol.is_empty() {
+                    enforce_validation(config, mint.key(), ErrorCode::IncompleteMetadata)?;
+                }
+
+                // Token-2022 metadata has no native notion of a collection, so BAXUS mints can record theirs under the "collection" key
+                let collection = config.collection.to_string();
+                in_collection = metadata.additional_metadata.iter().any(|(key, value)| key == "collection" && *value == collection);
+            }
+        }
+    }
+
+    if config.collection != Pubkey::default() {
+        in_collection = in_collection || token_group_of(mint.key, &mint_data) == Some(config.collection);
+        if !in_collection {
+            enforce_validation(config, mint.key(), ErrorCode::InvalidCollection)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Fail a validation check that didn't pass in strict mode. In permissive mode the failure is only reported in a ValidationWarning event, so a
+// new check can be watched for a while before it starts turning customers away
+fn enforce_validation(config: &Config, mint: Pubkey, error: ErrorCode) -> Result<()> {
+    if config.strict_mode {
+        return Err(error.into());
+    }
+
+    emit!(ValidationWarning { mint, error_code: error.into() });
+
+    Ok(())
+}
+
+// Bill the storage fee for every full day the redemption has been stored so far, replacing the StorageFee line item from any earlier settlement.
+// Nothing accrues once the asset has shipped
+fn accrue_storage_fee(invoice: &mut Invoice, redemption_info: &mut RedemptionInfo, config: &Config, now: i64) -> Result<()> {
+    if redemption_info.status >= RedemptionStatus::Shipped {
+        return Ok(());
+    }
+
+    let days = redemption_info.storage_days(config, now);
+    let storage_fee = days.checked_mul(config.storage_fee_per_day_usd_cents).ok_or(ErrorCode::AmountOverflow)?;
+    if storage_fee > 0 {
+        invoice.set_item(LineItemKind::StorageFee, storage_fee)?;
+    }
+    redemption_info.storage_days_billed = days;
+
+    Ok(())
+}
+
+// Load the CollectionConfig for the asset's collection, or None if the admin hasn't set one up. The account has to be passed in whether it
+// exists or not, so customers can't dodge their collection's policy by leaving it out
+fn load_collection_config(account: &AccountInfo, collection: &Pubkey) -> Result<Option<CollectionConfig>> {
+    let (collection_config_key, _) = Pubkey::find_program_address(&[COLLECTION_CONFIG_SEED, collection.as_ref()], &crate::ID);
+    require_keys_eq!(account.key(), collection_config_key, ErrorCode::InvalidCollectionConfig);
+
+    if *account.owner != crate::ID {
+        return Ok(None);
+    }
+    let data = account.try_borrow_data()?;
+    Ok(Some(CollectionConfig::try_deserialize(&mut &data[..])?))
+}
+
+// Check that the signer can operate on the redemption - the BAXUS ops authority can operate on any redemption, and a partner's authority only
+// on redemptions opened through that partner
+fn check_operator(config: &Config, redemption_info: &RedemptionInfo, partner: Option<&Partner>, authority: &Pubkey) -> Result<()> {
+    if *authority == config.ops_authority {
+        return Ok(());
+    }
+
+    match partner {
+        Some(partner) if redemption_info.partner_id != 0
+            && partner.partner_id == redemption_info.partner_id
+            && partner.authority == *authority => Ok(()),
+        _ => err!(ErrorCode::UnauthorizedOperator),
+    }
+}
+
+// Take a redemption that's shipping or leaving the queue some other way out of the priority index, if it has a priority
+fn remove_priority(redemption_info: &mut RedemptionInfo, priority_index: Option<&mut Account<PriorityIndex>>, redemption: &Pubkey) -> Result<()> {
+    if redemption_info.priority == 0 {
+        return Ok(());
+    }
+
+    priority_index.ok_or(ErrorCode::PriorityIndexRequired)?.remove(redemption);
+    redemption_info.priority = 0;
+
+    Ok(())
+}
+
+// Take a closing redemption off its customer's registry. Redemptions opened before customer registries existed were never listed in one
+fn deregister_customer_redemption(
+    redemption_info: &RedemptionInfo,
+    customer_registry: Option<&mut Account<CustomerRegistry>>,
+    redemption: &Pubkey,
+) -> Result<()> {
+    if !redemption_info.in_customer_registry {
+        return Ok(());
+    }
+
+    customer_registry.ok_or(ErrorCode::CustomerRegistryRequired)?.remove(redemption);
+
+    Ok(())
+}
+
+// Credit the partner a redemption was opened through with its share of whatever fees the settlement just paid, and return those fees. The
+// invoice keeps track of the fees already credited, so fees added after an earlier settlement are only credited once
+fn credit_partner_share(invoice: &mut Invoice, redemption_info: &RedemptionInfo, partner: Option<&mut Account<Partner>>) -> Result<u64> {
+    let fees = invoice.fees_total(redemption_info.fees_waived);
+    let fees_paid = fees.saturating_sub(invoice.fees_credited_usd_cents);
+    invoice.fees_credited_usd_cents = invoice.fees_credited_usd_cents.max(fees);
+
+    if redemption_info.partner_id == 0 {
+        return Ok(fees_paid);
+    }
+
+    let partner = partner.ok_or(ErrorCode::PartnerRequired)?;
+    partner.fees_usd_cents += fees_paid;
+    partner.share_accrued_usd_cents += (fees_paid as u128 * partner.fee_share_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+
+    Ok(fees_paid)
+}
+
+// Convert an amount in US cents to base units of the fee mint, which is pegged to the US dollar
+fn usd_cents_to_fee_amount(usd_cents: u64, decimals: u8) -> Result<u64> {
+    let amount = if decimals >= 2 {
+        10u64.checked_pow(decimals as u32 - 2).and_then(|scale| usd_cents.checked_mul(scale))
+    } else {
+        Some(usd_cents / 10u64.pow(2 - decimals as u32))
+    };
+    amount.ok_or_else(|| error!(ErrorCode::AmountOverflow))
+}
+
+// Load a token's US dollar price from its price account, which has to have been published in the last max_age seconds. That's a Pyth
+// price account, or in localnet-mocks builds, a MockPriceFeed
+fn load_price(price_feed: &AccountInfo, now: i64, max_age: u64) -> Result<Price> {
+    if cfg!(feature = "localnet-mocks") && *price_feed.owner == crate::ID {
+        let mock_price_feed = MockPriceFeed::try_deserialize(&mut &price_feed.try_borrow_data()?[..])?;
+        require!(now.saturating_sub(mock_price_feed.publish_time) <= max_age as i64, ErrorCode::StalePrice);
+        return Ok(Price {
+            price: mock_price_feed.price,
+            conf: mock_price_feed.conf,
+            expo: mock_price_feed.expo,
+            publish_time: mock_price_feed.publish_time,
+        });
+    }
+
+    let price_feed = pyth_sdk_solana::load_price_feed_from_account_info(price_feed).map_err(|_| error!(ErrorCode::InvalidPriceFeed))?;
+    price_feed.get_price_no_older_than(now, max_age).ok_or_else(|| error!(ErrorCode::StalePrice))
+}
+
+// Convert an amount in US cents to base units of a token priced in US dollars by Pyth. We value the token at the bottom of Pyth's confidence
+// interval and round up, so the treasury is never short-changed
+fn usd_cents_to_token_amount(usd_cents: u64, decimals: u8, price: &Price) -> Result<u64> {
+    let token_price = price.price.checked_sub(price.conf as i64).filter(|token_price| *token_price > 0).ok_or(ErrorCode::InvalidPrice)?;
+
+    // amount = usd_cents / 100 * 10^decimals / (token_price * 10^expo)
+    let scale = |exponent: u32| 10u128.checked_pow(exponent).ok_or(ErrorCode::AmountOverflow);
+    let mut numerator = (usd_cents as u128).checked_mul(scale(decimals as u32)?).ok_or(ErrorCode::AmountOverflow)?;
+    let mut denominator = token_price as u128 * 100;
+    if price.expo < 0 {
+        numerator = numerator.checked_mul(scale(price.expo.unsigned_abs())?).ok_or(ErrorCode::AmountOverflow)?;
+    } else {
+        denominator = denominator.checked_mul(scale(price.expo as u32)?).ok_or(ErrorCode::AmountOverflow)?;
+    }
+
+    u64::try_from(numerator.div_ceil(denominator)).map_err(|_| error!(ErrorCode::AmountOverflow))
+}
+
+// The parts of an asset's metadata we keep a copy of in redemption_info
+pub struct AssetSnapshot {
+    pub name: String,
+    pub symbol: String,
+    pub collection: Pubkey,
+}
+
+// Read the asset's name, symbol and collection from its Token-2022 native metadata and token group membership. Legacy SPL Token mints keep
+// their metadata in the Metaplex metadata program, which we don't read, so their snapshot is left empty
+fn snapshot_asset_metadata(mint: &AccountInfo) -> Result<AssetSnapshot> {
+    let mut snapshot = AssetSnapshot { name: String::new(), symbol: String::new(), collection: Pubkey::default() };
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(snapshot);
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    let metadata_in_mint = mint_state
+        .get_extension::<MetadataPointer>()
+        .is_ok_and(|metadata_pointer| Option::<Pubkey>::from(metadata_pointer.metadata_address) == Some(mint.key()));
+
+    if metadata_in_mint {
+        let metadata = mint_state.get_variable_len_extension::<TokenMetadata>()?;
+        snapshot.name = truncate(&metadata.name, MAX_ASSET_NAME_LEN);
+        snapshot.symbol = truncate(&metadata.symbol, MAX_ASSET_SYMBOL_LEN);
+        if let Some((_, collection)) = metadata.additional_metadata.iter().find(|(key, _)| key == "collection") {
+            snapshot.collection = Pubkey::from_str(collection).unwrap_or_default();
+        }
+    }
+
+    if snapshot.collection == Pubkey::default() {
+        snapshot.collection = token_group_of(mint.key, &mint_data).unwrap_or_default();
+    }
+
+    Ok(snapshot)
+}
+
+// Cut a string down to at most max_len bytes without splitting a character
+fn truncate(value: &str, max_len: usize) -> String {
+    let mut end = value.len().min(max_len);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    value[..end].to_string()
+}
+
+// Whether a mint is a Token-2022 mint whose new token accounts start out frozen
+fn frozen_by_default(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(false);
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    Ok(mint_state
+        .get_extension::<DefaultAccountState>()
+        .is_ok_and(|default_account_state| default_account_state.state == AccountState::Frozen as u8))
+}
+
+// The interest-bearing extension's rate state for a Token-2022 mint that has one. Wallets scale the amount they display by the interest
+// accrued, which is all this affects - every amount the program checks or moves is in raw base units
+fn interest_rate_of(mint: &AccountInfo) -> Result<Option<InterestRate>> {
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(None);
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+    Ok(mint_state.get_extension::<InterestBearingConfig>().ok().map(|interest_bearing_config| InterestRate {
+        initialization_timestamp: interest_bearing_config.initialization_timestamp.into(),
+        pre_update_average_rate_bps: interest_bearing_config.pre_update_average_rate.into(),
+        last_update_timestamp: interest_bearing_config.last_update_timestamp.into(),
+        current_rate_bps: interest_bearing_config.current_rate.into(),
+    }))
+}
+
+// Whether a token account is a Token-2022 account with the CPI guard extension switched on
+fn cpi_guard_enabled(token_account: &AccountInfo) -> Result<bool> {
+    if *token_account.owner != anchor_spl::token_2022::ID {
+        return Ok(false);
+    }
+
+    let token_account_data = token_account.try_borrow_data()?;
+    let token_account_state = StateWithExtensions::<TokenAccountState>::unpack(&token_account_data)?;
+    Ok(token_account_state.get_extension::<CpiGuard>().is_ok_and(|cpi_guard| cpi_guard.lock_cpi.into()))
+}
+
+// Token-2022 extension types that the spl-token-2022 version we build against doesn't know about yet
+const GROUP_MEMBER_POINTER_EXTENSION: u16 = 22;
+const TOKEN_GROUP_MEMBER_EXTENSION: u16 = 23;
+
+// Return the token group a Token-2022 mint is a member of, if its group member pointer points at the mint itself. The token group program only
+// lets the group's update authority add members, so membership can't be spoofed
+fn token_group_of(mint: &Pubkey, mint_data: &[u8]) -> Option<Pubkey> {
+    // GroupMemberPointer is the pointer's authority followed by the member address
+    let pointer = get_mint_extension_bytes(mint_data, GROUP_MEMBER_POINTER_EXTENSION)?;
+    if pointer.get(32..64)? != mint.as_ref() {
+        return None;
+    }
+
+    // TokenGroupMember is the member's mint, then its group, then its member number
+    let member = get_mint_extension_bytes(mint_data, TOKEN_GROUP_MEMBER_EXTENSION)?;
+    if member.get(0..32)? != mint.as_ref() {
+        return None;
+    }
+    let mut group = [0; 32];
+    group.copy_from_slice(member.get(32..64)?);
+    Some(Pubkey::new_from_array(group))
+}
+
+// Walk the type-length-value entries that follow the base mint and account type byte, and return the value of the given extension type
+fn get_mint_extension_bytes(mint_data: &[u8], extension_type: u16) -> Option<&[u8]> {
+    // Token-2022 pads mints out to the length of a token account (165 bytes) before the account type byte
+    let mut tlv_data = mint_data.get(165 + 1..)?;
+    while tlv_data.len() >= 4 {
+        let entry_type = u16::from_le_bytes([tlv_data[0], tlv_data[1]]);
+        let entry_length = u16::from_le_bytes([tlv_data[2], tlv_data[3]]) as usize;
+        if entry_type == 0 {
+            return None;
+        }
+        let value = tlv_data.get(4..4 + entry_length)?;
+        if entry_type == extension_type {
+            return Some(value);
+        }
+        tlv_data = &tlv_data[4 + entry_length..];
+    }
+    None
+}
+
+#[derive(Accounts)]
+// Anchor requires an underscore prefix for any variable name that isn't used in a function
+#[instruction()]
+pub struct InitializeRedemption<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        // We will initialize the redemption_info account to live at a PDA, and we will need to store the bump so that when we call return or burn, we make sure we're using the correct redemption_info
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump,
+        // Allocate exactly the space RedemptionInfo needs - if we add fields later, existing accounts can be grown with migrate_redemption_info
+        space = 8 + RedemptionInfo::INIT_SPACE)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    // The customer has to own the token account. Signing the transfer isn't proof of that on its own, since a CPI-guarded account's token is
+    // moved by the escrow account as its delegate
+    #[account(
+        mut,
+        constraint = customer_token_account.mint == token_mint_account.key() @ ErrorCode::AssetMintMismatch,
+        constraint = customer_token_account.owner == customer_payment_account.key() @ ErrorCode::CustomerTokenAccountNotOwned)
+    ]
+    pub customer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // The owner of customer_token_account, which authorizes the transfer into escrow - this can be a PDA signing through a CPI
+    pub customer_payment_account: Signer<'info>,
+
+    // Funds the new accounts and the bond - usually the same account as customer_payment_account, unless the customer can't pay for things itself
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // We will need to provide the account containing the NFT's mint for the creation of the baxus_escrow_account
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        // TO DO: Make sure we are using meaningful/scalable seeds and bump
+        seeds = [token_mint_account.key().as_ref()], 
+        bump, 
+        token::mint = token_mint_account,
+        token::authority = baxus_escrow_account)
+    ]
+    pub baxus_escrow_account: InterfaceAccount<'info, TokenAccount>,
+
+    // BAXUS can make this PDA the freeze authority of mints whose token accounts are frozen by default, so the program can thaw the escrow
+    // account and the customer's account
+    /// CHECK: only used as a signer
+    #[account(seeds = [FREEZE_AUTHORITY_SEED], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+
+    // New redemptions can't be opened while the program is paused
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Assets have to have been appraised by BAXUS before they can be redeemed
+    #[account(seeds = [token_mint_account.key().as_ref(), APPRAISAL_SEED], bump = appraisal.bump)]
+    pub appraisal: Account<'info, Appraisal>,
+
+    // The customer's risk check, which is only needed if the config requires one
+    #[account(seeds = [customer_payment_account.key().as_ref(), RISK_SCORE_SEED], bump = risk_score.bump)]
+    pub risk_score: Option<Account<'info, RiskScore>>,
+
+    // The customer's saved defaults, if they have a profile and want it applied
+    #[account(seeds = [customer_payment_account.key().as_ref(), CUSTOMER_PROFILE_SEED], bump = customer_profile.bump)]
+    pub customer_profile: Option<Account<'info, CustomerProfile>>,
+
+    // The accounts for paying the insurance premium are only needed if the asset is insured - because the customer asked, or because their
+    // profile opts in and they left insure unset
+    #[account(address = config.fee_mint @ ErrorCode::FeeMintMismatch)]
+    pub fee_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub payer_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = insurance_pool_fee_account.mint == config.fee_mint @ ErrorCode::FeeMintMismatch,
+        constraint = insurance_pool_fee_account.owner == config.insurance_pool @ ErrorCode::InsurancePoolMismatch)
+    ]
+    pub insurance_pool_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub fee_token_program: Option<Interface<'info, TokenInterface>>,
+
+    // The partner brand the redemption is opened through, if any
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The partner's authority has to co-sign, since opening through a partner lets it operate the redemption and take a share of its fees
+    pub partner_authority: Option<Signer<'info>>,
+
+    // The asset collection's CollectionConfig PDA, which doesn't have to exist
+    /// CHECK: checked in load_collection_config
+    pub collection_config: UncheckedAccount<'info>,
+
+    // The redemption's hook program, if its collection has one
+    /// CHECK: checked against the redemption's hook program in call_transition_hook
+    pub hook_program: Option<UncheckedAccount<'info>>,
+
+    // Only needed to write a status memo
+    pub memo_program: Option<Program<'info, Memo>>,
+
+    // New redemptions start out in the Deposited status
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_SEED, &[RedemptionStatus::Deposited as u8]],
+        bump = status_index.bump)
+    ]
+    pub status_index: Account<'info, StatusIndex>,
+
+    // Any page of the Deposited index with room for the redemption
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[RedemptionStatus::Deposited as u8], &status_index_page.page.to_le_bytes()],
+        bump = status_index_page.bump)
+    ]
+    pub status_index_page: Account<'info, StatusIndexPage>,
+
+    // The customer's registry is created along with their first redemption
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [customer_payment_account.key().as_ref(), CUSTOMER_REGISTRY_SEED],
+        bump,
+        space = 8 + CustomerRegistry::INIT_SPACE)
+    ]
+    pub customer_registry: Account<'info, CustomerRegistry>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [baxus_escrow_account.key().as_ref(), ESCROW_LINK_SEED],
+        bump,
+        space = 8 + EscrowLink::INIT_SPACE)
+    ]
+    pub escrow_link: Account<'info, EscrowLink>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [REDEMPTION_COUNTER_SEED],
+        bump,
+        space = 8 + RedemptionCounter::INIT_SPACE)
+    ]
+    pub redemption_counter: Account<'info, RedemptionCounter>,
+
+    // Used to find out which program called us, if this instruction was invoked through a CPI
+    /// CHECK: checked to be the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    // Include a Token Program account because we need to ask it transfer the NFT from the customer_token_account to the baxus_escrow_account
+    pub token_program: Interface<'info, TokenInterface>,
+
+    // The Token Program requires that we include a Rent Sysvar account
+    pub rent: Sysvar<'info, Rent>,
+
+    // Include a System Program account because we need it in order to create baxus_escrow_account
+    pub system_program: Program<'info, System>
+}
+
+// Takes the same accounts as InitializeRedemption, but nothing has to be writable or signed
+#[derive(Accounts)]
+pub struct ValidateRedemptionRequest<'info> {
+    // A redemption for this mint must not already be open
+    /// CHECK: only checked to be empty
+    #[account(
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump,
+        constraint = redemption_info.data_is_empty() @ ErrorCode::RedemptionAlreadyOpen)
+    ]
+    pub redemption_info: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = customer_token_account.mint == token_mint_account.key() @ ErrorCode::AssetMintMismatch,
+        constraint = customer_token_account.owner == customer_payment_account.key() @ ErrorCode::CustomerTokenAccountNotOwned)
+    ]
+    pub customer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: the customer can be any kind of account
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    pub payer: SystemAccount<'info>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: only checked to be empty
+    #[account(
+        seeds = [token_mint_account.key().as_ref()],
+        bump,
+        constraint = baxus_escrow_account.data_is_empty() @ ErrorCode::EscrowAccountInUse)
+    ]
+    pub baxus_escrow_account: UncheckedAccount<'info>,
+
+    /// CHECK: only used for its address
+    #[account(seeds = [FREEZE_AUTHORITY_SEED], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Assets have to have been appraised by BAXUS before they can be redeemed
+    #[account(seeds = [token_mint_account.key().as_ref(), APPRAISAL_SEED], bump = appraisal.bump)]
+    pub appraisal: Account<'info, Appraisal>,
+
+    // The customer's risk check, which is only needed if the config requires one
+    #[account(seeds = [customer_payment_account.key().as_ref(), RISK_SCORE_SEED], bump = risk_score.bump)]
+    pub risk_score: Option<Account<'info, RiskScore>>,
+}
+
+#[derive(Accounts)]
+pub struct ReturnAssetToken<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = !redemption_info.frozen_by_compliance @ ErrorCode::FrozenByCompliance,
+        constraint = !redemption_info.has_escrowed_attachments() @ ErrorCode::AttachmentsEscrowed,
+        constraint = redemption_info.collateral_amount == 0 @ ErrorCode::CollateralOutstanding,
+        close = customer_payment_account)
+    ]
+    pub redemption_info: Account<'info, VersionedRedemptionInfo>,
+
+    // The customer_token_account must be mutable in order for it to accept the token
+    #[account(
+        mut, 
+        constraint = customer_token_account.owner == *customer_payment_account.key @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.customer_token_account == customer_token_account.key() @ ErrorCode::CustomerTokenAccountMismatch)
+    ]
+    pub customer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // The customer doesn't sign, so BAXUS can return the token to a customer who has gone quiet
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)] 
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        // TO DO: Confirm that we are okay using the mint as a seed, which implies that there will only ever be one token for a given mint
+        seeds = [token_mint_account.key().as_ref()], 
+        bump = redemption_info.escrow_bump.get())
+    ]
+    pub baxus_escrow_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_SEED, &[redemption_info.status as u8]],
+        bump = status_index.bump)
+    ]
+    pub status_index: Account<'info, StatusIndex>,
+
+    // The page of the status's index the redemption is listed on
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[redemption_info.status as u8], &redemption_info.status_index_page.to_le_bytes()],
+        bump = status_index_page.bump)
+    ]
+    pub status_index_page: Account<'info, StatusIndexPage>,
+
+    // Only needed if the redemption has a priority, which closing it takes out of the index
+    #[account(mut, seeds = [PRIORITY_INDEX_SEED], bump = priority_index.bump)]
+    pub priority_index: Option<Account<'info, PriorityIndex>>,
+
+    // Only needed if the redemption is listed in the customer's registry
+    #[account(mut, seeds = [customer_payment_account.key().as_ref(), CUSTOMER_REGISTRY_SEED], bump = customer_registry.bump)]
+    pub customer_registry: Option<Account<'info, CustomerRegistry>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed if the treasury takes a share of the escrow account's rent
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: Option<SystemAccount<'info>>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The BAXUS ops authority, or the authority of the redemption's partner, which pays the transaction fee and the receipt's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // Only needed by return_asset_token, which records the operator handing the token back
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: Option<AccountLoader<'info, AuditLog>>,
+
+    // Records how the redemption ended, keyed by the mint and opening time like every other receipt
+    #[account(
+        init,
+        payer = authority,
+        seeds = [RECEIPT_SEED, token_mint_account.key().as_ref(), redemption_info.created_at.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + RedemptionReceipt::INIT_SPACE)
+    ]
+    pub receipt: Account<'info, RedemptionReceipt>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RejectRedemption<'info> {
+    // Only a redemption that hasn't shipped, and hasn't already been rejected, can be rejected
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = !redemption_info.frozen_by_compliance @ ErrorCode::FrozenByCompliance,
+        constraint = redemption_info.status < RedemptionStatus::Shipped @ ErrorCode::AlreadyShipped,
+        constraint = redemption_info.rejection.is_none() @ ErrorCode::RedemptionRejected)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The BAXUS ops authority, or the authority of the redemption's partner
+    pub authority: Signer<'info>,
+
+    // Only needed if the redemption has a priority, which it loses when it's rejected
+    #[account(mut, seeds = [PRIORITY_INDEX_SEED], bump = priority_index.bump)]
+    pub priority_index: Option<Account<'info, PriorityIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct AppealRejection<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.rejection.is_some() @ ErrorCode::RedemptionNotRejected,
+        constraint = redemption_info.appeal_evidence_hash == [0; 32] @ ErrorCode::AlreadyAppealed)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveAppeal<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.appeal_evidence_hash != [0; 32] && redemption_info.appeal_resolved.is_none() @ ErrorCode::NoPendingAppeal)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = compliance_authority, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    // The appeal bond goes back to the customer if the rejection is overturned, or to the treasury if it's upheld
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: SystemAccount<'info>,
+
+    pub compliance_authority: Signer<'info>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BurnAssetToken<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        // After the asset token is burned, burn_asset_token archives the RedemptionInfo account or closes it and sends its rent back to the customer
+        // The token is only burned once the asset has been delivered, so the cooldown on Delivered can't be skipped
+        constraint = redemption_info.status == RedemptionStatus::Delivered @ ErrorCode::RedemptionNotFinished,
+        constraint = redemption_info.finalize_mode == FinalizeMode::Burn @ ErrorCode::WrongFinalizeMode,
+        constraint = !redemption_info.frozen_by_compliance @ ErrorCode::FrozenByCompliance,
+        constraint = redemption_info.collateral_amount == 0 @ ErrorCode::CollateralOutstanding)
+    ]
+    pub redemption_info: Account<'info, VersionedRedemptionInfo>,
+
+    // Include customer_token_account so we can properly constrain the redemption_info account, and make sure it is associated with the correct customer_payment_account
+    #[account(
+        constraint = customer_token_account.owner == *customer_payment_account.key @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.customer_token_account == customer_token_account.key() @ ErrorCode::CustomerTokenAccountMismatch)
+    ]
+    pub customer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        // TO DO: Confirm that we are okay using the mint as a seed, which implies that there will only ever be one token for a given mint
+        seeds = [token_mint_account.key().as_ref()], 
+        bump = redemption_info.escrow_bump.get())
+    ]
+    pub baxus_escrow_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_SEED, &[redemption_info.status as u8]],
+        bump = status_index.bump)
+    ]
+    pub status_index: Account<'info, StatusIndex>,
+
+    // The page of the status's index the redemption is listed on
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[redemption_info.status as u8], &redemption_info.status_index_page.to_le_bytes()],
+        bump = status_index_page.bump)
+    ]
+    pub status_index_page: Account<'info, StatusIndexPage>,
+
+    // Only needed if the redemption is listed in the customer's registry
+    #[account(mut, seeds = [customer_payment_account.key().as_ref(), CUSTOMER_REGISTRY_SEED], bump = customer_registry.bump)]
+    pub customer_registry: Option<Account<'info, CustomerRegistry>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed if the treasury takes a share of the escrow account's rent
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: Option<SystemAccount<'info>>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The BAXUS ops authority, or the authority of the redemption's partner, who pays for the receipt
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // Only needed if the redemption's valuation requires enhanced approval
+    #[account(address = config.enhanced_approver @ ErrorCode::NotEnhancedApprover)]
+    pub enhanced_approver: Option<Signer<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    // Only needed if an escrowed certificate's mint belongs to the other token program than the asset's
+    pub certificate_token_program: Option<Interface<'info, TokenInterface>>,
+
+    // The accounts for paying out of the treasury are only needed if the redemption was delivered within its SLA, or its collection shares
+    // fees with the asset's creators
+    #[account(seeds = [redemption_info.key().as_ref(), INVOICE_SEED], bump = invoice.bump)]
+    pub invoice: Option<Account<'info, Invoice>>,
+
+    #[account(address = config.fee_mint @ ErrorCode::FeeMintMismatch)]
+    pub fee_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = treasury_fee_account.mint == config.fee_mint @ ErrorCode::FeeMintMismatch,
+        constraint = treasury_fee_account.owner == config.treasury @ ErrorCode::TreasuryMismatch)
+    ]
+    pub treasury_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = customer_fee_account.mint == config.fee_mint @ ErrorCode::FeeMintMismatch,
+        constraint = customer_fee_account.owner == redemption_info.customer_payment_account @ ErrorCode::CustomerMismatch)
+    ]
+    pub customer_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    // The treasury approves this PDA as a delegate on its fee account to fund rebates and creator shares
+    /// CHECK: only used as a signer
+    #[account(seeds = [REBATE_AUTHORITY_SEED], bump)]
+    pub rebate_authority: Option<UncheckedAccount<'info>>,
+
+    pub fee_token_program: Option<Interface<'info, TokenInterface>>,
+
+    #[account(mut, seeds = [ACCOUNTING_SEED], bump = accounting.bump)]
+    pub accounting: Option<Account<'info, Accounting>>,
+
+    // The charity's wallet and fee account are only needed if the customer donates part of their bond or rebate
+    #[account(mut, address = config.charity @ ErrorCode::CharityMismatch)]
+    pub charity: Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = charity_fee_account.mint == config.fee_mint @ ErrorCode::FeeMintMismatch,
+        constraint = charity_fee_account.owner == config.charity @ ErrorCode::CharityMismatch)
+    ]
+    pub charity_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    // The asset's Metaplex metadata, which lists its creators - only needed to pay a creator share
+    /// CHECK: checked to be the mint's metadata PDA, and parsed in verified_creators
+    #[account(
+        seeds = [TOKEN_METADATA_SEED, token_metadata_program::ID.as_ref(), token_mint_account.key().as_ref()],
+        seeds::program = token_metadata_program::ID,
+        bump,
+        owner = token_metadata_program::ID @ ErrorCode::InvalidAssetMetadata)
+    ]
+    pub asset_metadata: Option<UncheckedAccount<'info>>,
+
+    // Only needed if the redemption's collection requires the brand to approve burns. An approval left over from an earlier redemption of
+    // the same asset doesn't count
+    #[account(
+        mut,
+        seeds = [redemption_info.key().as_ref(), BURN_APPROVAL_SEED],
+        bump = burn_approval.bump,
+        constraint = burn_approval.redemption_opened_at == redemption_info.created_at @ ErrorCode::StaleBurnApproval,
+        close = brand_authority)
+    ]
+    pub burn_approval: Option<Account<'info, BurnApproval>>,
+
+    /// CHECK: checked against redemption_info, and only receives the approval's rent
+    #[account(mut, address = redemption_info.brand_authority @ ErrorCode::UnauthorizedBrand)]
+    pub brand_authority: Option<UncheckedAccount<'info>>,
+
+    // The mint's Metaplex edition PDA, which is always passed so print editions can't be burned without their edition number on the receipt.
+    // For assets that aren't Metaplex assets it's an empty account
+    /// CHECK: checked to be the mint's edition PDA, and parsed in print_edition_of if Metaplex owns it
+    #[account(
+        seeds = [TOKEN_METADATA_SEED, token_metadata_program::ID.as_ref(), token_mint_account.key().as_ref(), TOKEN_METADATA_EDITION_SEED],
+        seeds::program = token_metadata_program::ID,
+        bump)
+    ]
+    pub edition: UncheckedAccount<'info>,
+
+    // Keyed by the mint and opening time like imported receipts
+    #[account(
+        init,
+        payer = authority,
+        seeds = [RECEIPT_SEED, token_mint_account.key().as_ref(), redemption_info.created_at.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + RedemptionReceipt::INIT_SPACE)
+    ]
+    pub receipt: Account<'info, RedemptionReceipt>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitFeedback<'info> {
+    // Only redemptions that ran their course on-chain can be rated, and only once
+    #[account(
+        mut,
+        seeds = [RECEIPT_SEED, receipt.token_mint.as_ref(), receipt.opened_at.to_le_bytes().as_ref()],
+        bump = receipt.bump,
+        has_one = customer @ ErrorCode::CustomerMismatch,
+        constraint = receipt.outcome == RedemptionOutcome::Burned && !receipt.imported @ ErrorCode::NotRateable,
+        constraint = receipt.rating == 0 @ ErrorCode::FeedbackAlreadySubmitted)
+    ]
+    pub receipt: Account<'info, RedemptionReceipt>,
+
+    #[account(
+        init_if_needed,
+        payer = customer,
+        seeds = [REPUTATION_SEED],
+        bump,
+        space = 8 + FulfillmentReputation::INIT_SPACE)
+    ]
+    pub reputation: Account<'info, FulfillmentReputation>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub customer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateRedemptionInfo<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)
+    ]
+    pub redemption_info: Account<'info, VersionedRedemptionInfo>,
+
+    /// CHECK: only receives the excess rent, and has to be the customer who opened the redemption
+    #[account(mut)]
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Ops migrates accounts in bulk, so it pays for the extra space rather than each customer having to sign
+    #[account(mut, constraint = payer.key() == config.ops_authority @ ErrorCode::UnauthorizedOperator)]
+    pub payer: Signer<'info>,
+
+    // Needed to transfer rent when the account grows
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealContact<'info> {
+    #[account(
+        init,
+        payer = customer_payment_account,
+        seeds = [redemption_info.key().as_ref(), CONTACT_SEED],
+        bump,
+        space = 8 + ContactReveal::INIT_SPACE)
+    ]
+    pub contact_reveal: Account<'info, ContactReveal>,
+
+    // Only the customer who opened the redemption can reveal their contact details, and only if they committed to them at initialization
+    #[account(
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.contact_commitment != [0; 32] @ ErrorCode::NoContactCommitment)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    #[account(mut)]
+    pub customer_payment_account: Signer<'info>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Used to find out which program called us, if this instruction was invoked through a CPI
+    /// CHECK: checked to be the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateShippingCommitment<'info> {
+    // The address can't change once ops have packed the asset for shipping
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.status < RedemptionStatus::Packed @ ErrorCode::AddressChangeTooLate)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    // Only needed if the customer revealed details for the old commitment
+    #[account(mut, seeds = [redemption_info.key().as_ref(), CONTACT_SEED], bump, close = customer_payment_account)]
+    pub contact_reveal: Option<Account<'info, ContactReveal>>,
+
+    #[account(mut)]
+    pub customer_payment_account: Signer<'info>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Used to find out which program called us, if this instruction was invoked through a CPI
+    /// CHECK: checked to be the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardians<'info> {
+    #[account(
+        init_if_needed,
+        payer = customer_payment_account,
+        seeds = [redemption_info.key().as_ref(), GUARDIAN_SET_SEED],
+        bump,
+        space = 8 + GuardianSet::INIT_SPACE)
+    ]
+    pub guardian_set: Account<'info, GuardianSet>,
+
+    #[account(
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    /// CHECK: we only check that no recovery request lives at this address
+    #[account(
+        seeds = [redemption_info.key().as_ref(), RECOVERY_SEED],
+        bump,
+        constraint = recovery_request.data_is_empty() @ ErrorCode::RecoveryPending)
+    ]
+    pub recovery_request: UncheckedAccount<'info>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub customer_payment_account: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRecovery<'info> {
+    // The guardian who opens the request pays its rent, and gets it back when the request is carried out or cancelled
+    #[account(
+        init_if_needed,
+        payer = guardian,
+        seeds = [redemption_info.key().as_ref(), RECOVERY_SEED],
+        bump,
+        space = 8 + RecoveryRequest::INIT_SPACE)
+    ]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+
+    #[account(seeds = [redemption_info.key().as_ref(), GUARDIAN_SET_SEED], bump = guardian_set.bump)]
+    pub guardian_set: Account<'info, GuardianSet>,
+
+    #[account(seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED], bump = redemption_info.redemption_bump)]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [redemption_info.key().as_ref(), RECOVERY_SEED],
+        bump = recovery_request.bump,
+        close = proposer)
+    ]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+
+    #[account(seeds = [redemption_info.key().as_ref(), GUARDIAN_SET_SEED], bump = guardian_set.bump)]
+    pub guardian_set: Account<'info, GuardianSet>,
+
+    #[account(mut, seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED], bump = redemption_info.redemption_bump)]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    // The new token account has to be able to take the asset token back if the redemption is returned
+    #[account(
+        address = recovery_request.new_customer_token_account @ ErrorCode::RecoveryMismatch,
+        token::mint = token_mint_account,
+        constraint = new_customer_token_account.owner == recovery_request.new_customer_payment_account @ ErrorCode::CustomerMismatch)
+    ]
+    pub new_customer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Only needed if the redemption is listed in the old wallet's customer registry
+    #[account(mut, seeds = [redemption_info.customer_payment_account.as_ref(), CUSTOMER_REGISTRY_SEED], bump = customer_registry.bump)]
+    pub customer_registry: Option<Account<'info, CustomerRegistry>>,
+
+    /// CHECK: checked against the recovery request, and only receives its rent
+    #[account(mut, address = recovery_request.proposer @ ErrorCode::RecoveryMismatch)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    #[account(
+        mut,
+        seeds = [redemption_info.key().as_ref(), RECOVERY_SEED],
+        bump = recovery_request.bump,
+        close = proposer)
+    ]
+    pub recovery_request: Account<'info, RecoveryRequest>,
+
+    #[account(
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: checked against the recovery request, and only receives its rent
+    #[account(mut, address = recovery_request.proposer @ ErrorCode::RecoveryMismatch)]
+    pub proposer: UncheckedAccount<'info>,
+
+    pub customer_payment_account: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseContactReveal<'info> {
+    #[account(mut, close = customer_payment_account)]
+    pub contact_reveal: Account<'info, ContactReveal>,
+
+    // The redemption_info account has to have been closed already, so ops are done with the contact details
+    /// CHECK: we only check that no account lives at this address any more
+    #[account(
+        address = contact_reveal.redemption_info @ ErrorCode::RedemptionInfoMismatch,
+        constraint = redemption_info.data_is_empty() @ ErrorCode::RedemptionStillOpen)
+    ]
+    pub redemption_info: UncheckedAccount<'info>,
+
+    // Only the customer who revealed the contact details can delete them
+    #[account(mut, constraint = contact_reveal.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireRedemption<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        // Only a redemption BAXUS never started on can expire - expires_at stays set after it moves on, and one that has shipped is in the
+        // customer's hands
+        constraint = redemption_info.status == RedemptionStatus::Deposited @ ErrorCode::RedemptionInProgress,
+        constraint = !redemption_info.frozen_by_compliance @ ErrorCode::FrozenByCompliance,
+        constraint = redemption_info.rejection.is_none() @ ErrorCode::RedemptionRejected,
+        constraint = !redemption_info.has_escrowed_attachments() @ ErrorCode::AttachmentsEscrowed,
+        constraint = redemption_info.collateral_amount == 0 @ ErrorCode::CollateralOutstanding,
+        close = customer_payment_account)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    #[account(
+        mut, 
+        constraint = customer_token_account.owner == *customer_payment_account.key @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.customer_token_account == customer_token_account.key() @ ErrorCode::CustomerTokenAccountMismatch)
+    ]
+    pub customer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)] 
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref()], 
+        bump = redemption_info.escrow_bump.get())
+    ]
+    pub baxus_escrow_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // The slashed bond is sent to the treasury configured by the admin
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_SEED, &[redemption_info.status as u8]],
+        bump = status_index.bump)
+    ]
+    pub status_index: Account<'info, StatusIndex>,
+
+    // The page of the status's index the redemption is listed on
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[redemption_info.status as u8], &redemption_info.status_index_page.to_le_bytes()],
+        bump = status_index_page.bump)
+    ]
+    pub status_index_page: Account<'info, StatusIndexPage>,
+
+    // Only needed if the redemption has a priority, which closing it takes out of the index
+    #[account(mut, seeds = [PRIORITY_INDEX_SEED], bump = priority_index.bump)]
+    pub priority_index: Option<Account<'info, PriorityIndex>>,
+
+    // Only needed if the redemption is listed in the customer's registry
+    #[account(mut, seeds = [customer_payment_account.key().as_ref(), CUSTOMER_REGISTRY_SEED], bump = customer_registry.bump)]
+    pub customer_registry: Option<Account<'info, CustomerRegistry>>,
+
+    // Records how the redemption ended, keyed by the mint and opening time like every other receipt
+    #[account(
+        init,
+        payer = payer,
+        seeds = [RECEIPT_SEED, token_mint_account.key().as_ref(), redemption_info.created_at.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + RedemptionReceipt::INIT_SPACE)
+    ]
+    pub receipt: Account<'info, RedemptionReceipt>,
+
+    // Whoever expires the redemption fronts the receipt's rent, and gets it back out of the slashed bond
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConsolidateToColdVault<'info> {
+    // Only redemptions stuck before shipping are vaulted, and never one compliance has frozen
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.status < RedemptionStatus::Shipped @ ErrorCode::AlreadyShipped,
+        constraint = !redemption_info.frozen_by_compliance @ ErrorCode::FrozenByCompliance)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    // The escrow account's rent goes back to the customer when it's closed
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref()],
+        bump = redemption_info.escrow_bump.get())
+    ]
+    pub baxus_escrow_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = ops_authority,
+        associated_token::mint = token_mint_account,
+        associated_token::authority = cold_vault_authority,
+        associated_token::token_program = token_program)
+    ]
+    pub cold_vault_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: only used as the owner of the cold vault account, and checked against the config
+    #[account(address = config.cold_vault_authority @ ErrorCode::ColdVaultAuthorityMismatch)]
+    pub cold_vault_authority: UncheckedAccount<'info>,
+
+    // self_return can't take a token back out of the cold vault, so nothing goes in while customers are relying on it
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = ops_authority,
+        constraint = config.dead_man_secs == 0 @ ErrorCode::DeadManSwitchEnabled)
+    ]
+    pub config: Account<'info, Config>,
+
+    // Only needed if the treasury takes a share of the escrow account's rent
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: Option<SystemAccount<'info>>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(mut)]
+    pub ops_authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecallFromColdVault<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.cold_vault_account == cold_vault_account.key() @ ErrorCode::ColdVaultMismatch)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    // Re-create the escrow account at the same PDA the redemption was opened with
+    #[account(
+        init,
+        payer = cold_vault_authority,
+        seeds = [token_mint_account.key().as_ref()],
+        bump,
+        token::mint = token_mint_account,
+        token::authority = baxus_escrow_account,
+        token::token_program = token_program)
+    ]
+    pub baxus_escrow_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub cold_vault_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = config.cold_vault_authority @ ErrorCode::ColdVaultAuthorityMismatch)]
+    pub cold_vault_authority: Signer<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdvanceStatus<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = !redemption_info.frozen_by_compliance @ ErrorCode::FrozenByCompliance,
+        constraint = redemption_info.rejection.is_none() @ ErrorCode::RedemptionRejected)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_SEED, &[redemption_info.status as u8]],
+        bump = current_status_index.bump)
+    ]
+    pub current_status_index: Account<'info, StatusIndex>,
+
+    // The page of the current status's index the redemption is listed on
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[redemption_info.status as u8], &redemption_info.status_index_page.to_le_bytes()],
+        bump = current_status_index_page.bump)
+    ]
+    pub current_status_index_page: Account<'info, StatusIndexPage>,
+
+    // Redemptions can only move one step forward at a time
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_SEED, &[next_status_index.status as u8]],
+        bump = next_status_index.bump,
+        constraint = redemption_info.can_advance_to(next_status_index.status) @ ErrorCode::InvalidStatusTransition)
+    ]
+    pub next_status_index: Account<'info, StatusIndex>,
+
+    // Any page of the next status's index with room for the redemption
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[next_status_index.status as u8], &next_status_index_page.page.to_le_bytes()],
+        bump = next_status_index_page.bump)
+    ]
+    pub next_status_index_page: Account<'info, StatusIndexPage>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The BAXUS ops authority, or the authority of the redemption's partner
+    pub authority: Signer<'info>,
+
+    // Only needed to ship a redemption that has a priority, which takes it out of the index
+    #[account(mut, seeds = [PRIORITY_INDEX_SEED], bump = priority_index.bump)]
+    pub priority_index: Option<Account<'info, PriorityIndex>>,
+
+    // Only needed if the redemption's valuation requires enhanced approval, and then only to ship it
+    #[account(address = config.enhanced_approver @ ErrorCode::NotEnhancedApprover)]
+    pub enhanced_approver: Option<Signer<'info>>,
+
+    // The redemption's hook program, if its collection has one
+    /// CHECK: checked against the redemption's hook program in call_transition_hook
+    pub hook_program: Option<UncheckedAccount<'info>>,
+
+    // Only needed to write a status memo
+    pub memo_program: Option<Program<'info, Memo>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRiskScore<'info> {
+    #[account(
+        init_if_needed,
+        payer = compliance_authority,
+        seeds = [customer.key().as_ref(), RISK_SCORE_SEED],
+        bump,
+        space = 8 + RiskScore::INIT_SPACE)
+    ]
+    pub risk_score: Account<'info, RiskScore>,
+
+    /// CHECK: the customer can be any kind of account
+    pub customer: UncheckedAccount<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = compliance_authority)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(mut)]
+    pub compliance_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAppraisal<'info> {
+    #[account(
+        init_if_needed,
+        payer = ops_authority,
+        seeds = [token_mint_account.key().as_ref(), APPRAISAL_SEED],
+        bump,
+        space = 8 + Appraisal::INIT_SPACE)
+    ]
+    pub appraisal: Account<'info, Appraisal>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = ops_authority)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub ops_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetDutyRate<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [DUTY_RATES_SEED],
+        bump,
+        space = 8 + DutyRates::INIT_SPACE)
+    ]
+    pub duty_rates: Account<'info, DutyRates>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GrantBurnConsent<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)
+    ]
+    pub redemption_info: Account<'info, VersionedRedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    pub customer_payment_account: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDonation<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.archived_at == 0 @ ErrorCode::RedemptionArchived)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    pub customer_payment_account: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyKycProof<'info> {
+    // There's nothing left to verify once the asset has shipped
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.status < RedemptionStatus::Shipped @ ErrorCode::AlreadyShipped)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: has to be the config's KYC verifier program
+    #[account(executable, address = config.kyc_verifier @ ErrorCode::KycVerifierMismatch)]
+    pub kyc_verifier: UncheckedAccount<'info>,
+
+    pub customer_payment_account: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetJurisdiction<'info> {
+    // The destination can't change once the asset has shipped
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.status < RedemptionStatus::Shipped @ ErrorCode::AlreadyShipped)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    // The duty can't be changed once the customer has started paying the invoice, since we can't refund part of a payment
+    #[account(
+        mut,
+        seeds = [redemption_info.key().as_ref(), INVOICE_SEED],
+        bump = invoice.bump,
+        constraint = invoice.paid_usd_cents == 0 @ ErrorCode::InvoiceAlreadyPaid)
+    ]
+    pub invoice: Account<'info, Invoice>,
+
+    #[account(seeds = [DUTY_RATES_SEED], bump = duty_rates.bump)]
+    pub duty_rates: Account<'info, DutyRates>,
+
+    pub customer_payment_account: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCustomerProfile<'info> {
+    #[account(
+        init_if_needed,
+        payer = customer,
+        seeds = [customer.key().as_ref(), CUSTOMER_PROFILE_SEED],
+        bump,
+        space = 8 + CustomerProfile::INIT_SPACE)
+    ]
+    pub customer_profile: Account<'info, CustomerProfile>,
+
+    // Only the jurisdictions BAXUS ships to can be saved
+    #[account(seeds = [DUTY_RATES_SEED], bump = duty_rates.bump)]
+    pub duty_rates: Account<'info, DutyRates>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // The whitelisted fee currency, only needed if the saved token isn't the config's fee mint
+    #[account(seeds = [FEE_CURRENCY_SEED, fee_currency.mint.as_ref()], bump = fee_currency.bump)]
+    pub fee_currency: Option<Account<'info, FeeCurrency>>,
+
+    #[account(mut)]
+    pub customer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenInvoice<'info> {
+    #[account(
+        init,
+        payer = payer,
+        seeds = [redemption_info.key().as_ref(), INVOICE_SEED],
+        bump,
+        space = 8 + Invoice::INIT_SPACE)
+    ]
+    pub invoice: Account<'info, Invoice>,
+
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddInvoiceItem<'info> {
+    #[account(mut, seeds = [redemption_info.key().as_ref(), INVOICE_SEED], bump = invoice.bump)]
+    pub invoice: Account<'info, Invoice>,
+
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = ops_authority)]
+    pub config: Account<'info, Config>,
+
+    pub ops_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PayInsuranceClaim<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.insured @ ErrorCode::NotInsured,
+        constraint = !redemption_info.insurance_claimed @ ErrorCode::InsuranceAlreadyClaimed,
+        constraint = redemption_info.status == RedemptionStatus::Shipped @ ErrorCode::NotInTransit)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = insurance_pool)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [ACCOUNTING_SEED], bump = accounting.bump)]
+    pub accounting: Account<'info, Accounting>,
+
+    #[account(address = config.fee_mint @ ErrorCode::FeeMintMismatch)]
+    pub fee_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, token::mint = fee_mint, token::authority = insurance_pool)]
+    pub insurance_pool_fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = fee_mint, token::authority = redemption_info.customer_payment_account)]
+    pub customer_fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub insurance_pool: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SettleInvoice<'info> {
+    #[account(mut, seeds = [redemption_info.key().as_ref(), INVOICE_SEED], bump = invoice.bump)]
+    pub invoice: Account<'info, Invoice>,
+
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [ACCOUNTING_SEED], bump = accounting.bump)]
+    pub accounting: Account<'info, Accounting>,
+
+    #[account(address = config.fee_mint @ ErrorCode::FeeMintMismatch)]
+    pub fee_mint: InterfaceAccount<'info, Mint>,
+
+    // Needed to credit the partner's share of the fees if the redemption was opened through a partner
+    #[account(mut, constraint = partner.partner_id == redemption_info.partner_id @ ErrorCode::PartnerMismatch)]
+    pub partner: Option<Account<'info, Partner>>,
+
+    // Anyone can settle the invoice on the customer's behalf
+    #[account(mut, token::mint = fee_mint, token::authority = payer)]
+    pub payer_fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = config.treasury,
+        associated_token::token_program = token_program)
+    ]
+    pub treasury_fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SettleInvoiceInCurrency<'info> {
+    #[account(mut, seeds = [redemption_info.key().as_ref(), INVOICE_SEED], bump = invoice.bump)]
+    pub invoice: Account<'info, Invoice>,
+
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [ACCOUNTING_SEED], bump = accounting.bump)]
+    pub accounting: Account<'info, Accounting>,
+
+    #[account(seeds = [FEE_CURRENCY_SEED, payment_mint.key().as_ref()], bump = fee_currency.bump)]
+    pub fee_currency: Account<'info, FeeCurrency>,
+
+    // The customer's profile can hold their invoices to one fee currency
+    #[account(
+        constraint = redemption_info.fee_currency == Pubkey::default() || redemption_info.fee_currency == payment_mint.key() @ ErrorCode::FeeCurrencyMismatch)
+    ]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: checked against the fee currency, and parsed as a Pyth price account
+    #[account(address = fee_currency.price_feed @ ErrorCode::InvalidPriceFeed)]
+    pub price_feed: UncheckedAccount<'info>,
+
+    // Needed to credit the partner's share of the fees if the redemption was opened through a partner
+    #[account(mut, constraint = partner.partner_id == redemption_info.partner_id @ ErrorCode::PartnerMismatch)]
+    pub partner: Option<Account<'info, Partner>>,
+
+    // Anyone can settle the invoice on the customer's behalf
+    #[account(mut, token::mint = payment_mint, token::authority = payer)]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // The treasury may not hold this token yet, so the payer creates its account if needed
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program)
+    ]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: only used as the owner of the treasury token account, and checked against the config
+    #[account(address = config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeCurrency<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [FEE_CURRENCY_SEED, payment_mint.key().as_ref()],
+        bump,
+        space = 8 + FeeCurrency::INIT_SPACE)
+    ]
+    pub fee_currency: Account<'info, FeeCurrency>,
+
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Instructions for integration tests on a local validator, which only exist in localnet-mocks builds. They're dispatched by the program's
+// fallback, and encoded like any other Anchor instruction - the discriminator is the first 8 bytes of sha256("global:<name>")
+#[cfg(feature = "localnet-mocks")]
+mod mocks {
+    use super::*;
+    use anchor_lang::solana_program::hash::hash;
+    use std::collections::BTreeSet;
+
+    pub fn dispatch<'info>(program_id: &Pubkey, accounts: &'info [AccountInfo<'info>], data: &[u8]) -> Result<()> {
+        let (discriminator, mut args) = data.split_at(8);
+        let mut remaining_accounts = accounts;
+        let mut reallocs = BTreeSet::new();
+
+        if discriminator == &hash(b"global:warp_clock").to_bytes()[..8] {
+            let (secs, slots) = <(u32, u32)>::deserialize(&mut args).map_err(|_| error!(anchor_lang::error::ErrorCode::InstructionDidNotDeserialize))?;
+            let mut bumps = WarpClockBumps::default();
+            let mut ix_accounts = WarpClock::try_accounts(program_id, &mut remaining_accounts, args, &mut bumps, &mut reallocs)?;
+            warp_clock(Context::new(program_id, &mut ix_accounts, remaining_accounts, bumps), secs, slots)?;
+            return ix_accounts.exit(program_id);
+        }
+
+        if discriminator == &hash(b"global:set_mock_price").to_bytes()[..8] {
+            let (price, conf, expo) =
+                <(i64, u64, i32)>::deserialize(&mut args).map_err(|_| error!(anchor_lang::error::ErrorCode::InstructionDidNotDeserialize))?;
+            let mut bumps = SetMockPriceBumps::default();
+            let mut ix_accounts = SetMockPrice::try_accounts(program_id, &mut remaining_accounts, args, &mut bumps, &mut reallocs)?;
+            set_mock_price(Context::new(program_id, &mut ix_accounts, remaining_accounts, bumps), price, conf, expo)?;
+            return ix_accounts.exit(program_id);
+        }
+
+        err!(anchor_lang::error::ErrorCode::InstructionFallbackNotFound)
+    }
+
+    // Move the clock the program's time-window checks read forward
+    fn warp_clock(ctx: Context<WarpClock>, secs: u32, slots: u32) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.clock_warp_secs += secs as i64;
+        config.clock_warp_slots += slots as u64;
+
+        Ok(())
+    }
+
+    // Publish a price for a mint in US dollars, which fee currencies can use in place of a Pyth price account
+    fn set_mock_price(ctx: Context<SetMockPrice>, price: i64, conf: u64, expo: i32) -> Result<()> {
+        let mock_price_feed = &mut ctx.accounts.mock_price_feed;
+        mock_price_feed.price = price;
+        mock_price_feed.conf = conf;
+        mock_price_feed.expo = expo;
+        mock_price_feed.publish_time = ctx.accounts.config.clock()?.unix_timestamp;
+        mock_price_feed.bump = ctx.bumps.mock_price_feed;
+
+        Ok(())
+    }
+
+    #[derive(Accounts)]
+    pub struct WarpClock<'info> {
+        #[account(mut, seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+        pub config: Account<'info, Config>,
+
+        pub admin: Signer<'info>,
+    }
+
+    #[derive(Accounts)]
+    pub struct SetMockPrice<'info> {
+        #[account(
+            init_if_needed,
+            payer = admin,
+            seeds = [MOCK_PRICE_SEED, mint.key().as_ref()],
+            bump,
+            space = 8 + MockPriceFeed::INIT_SPACE)
+        ]
+        pub mock_price_feed: Account<'info, MockPriceFeed>,
+
+        pub mint: InterfaceAccount<'info, Mint>,
+
+        #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+        pub config: Account<'info, Config>,
+
+        #[account(mut)]
+        pub admin: Signer<'info>,
+
+        pub system_program: Program<'info, System>,
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveFeeCurrency<'info> {
+    #[account(mut, seeds = [FEE_CURRENCY_SEED, fee_currency.mint.as_ref()], bump = fee_currency.bump, close = admin)]
+    pub fee_currency: Account<'info, FeeCurrency>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CommitShipmentDocuments<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.status < RedemptionStatus::Shipped @ ErrorCode::AlreadyShipped)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The BAXUS ops authority, or the authority of the redemption's partner
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPriority<'info> {
+    // Priority only matters until the asset ships
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.status < RedemptionStatus::Shipped @ ErrorCode::AlreadyShipped)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [PRIORITY_INDEX_SEED],
+        bump,
+        space = 8 + PriorityIndex::INIT_SPACE)
+    ]
+    pub priority_index: Account<'info, PriorityIndex>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The BAXUS ops authority, the authority of the redemption's partner, or the customer of an express redemption
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(redemption: Pubkey)]
+pub struct PrunePriorityIndex<'info> {
+    #[account(mut, seeds = [PRIORITY_INDEX_SEED], bump = priority_index.bump)]
+    pub priority_index: Account<'info, PriorityIndex>,
+
+    /// CHECK: only read to see whether the redemption still needs its entry
+    #[account(address = redemption @ ErrorCode::RedemptionInfoMismatch)]
+    pub redemption_info: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResyncRedemption<'info> {
+    #[account(seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED], bump = redemption_info.redemption_bump)]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The BAXUS ops authority, or the authority of the redemption's partner
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetOpsReference<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The BAXUS ops authority, or the authority of the redemption's partner
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordCustodyHandoff<'info> {
+    // Created by whoever records the first handoff
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [redemption_info.key().as_ref(), CUSTODY_LOG_SEED],
+        bump,
+        space = 8 + CustodyLog::INIT_SPACE)
+    ]
+    pub custody_log: Account<'info, CustodyLog>,
+
+    #[account(seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED], bump = redemption_info.redemption_bump)]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The current custodian, or an operator
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RequestHold<'info> {
+    // Shipment can only be paused before the asset ships
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.status < RedemptionStatus::Shipped @ ErrorCode::AlreadyShipped,
+        constraint = redemption_info.held_since == 0 @ ErrorCode::RedemptionOnHold)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    pub customer_payment_account: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseHold<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.held_since != 0 @ ErrorCode::RedemptionNotOnHold)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    pub customer_payment_account: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PostCollateral<'info> {
+    // Collateral only matters while the redemption is waiting on KYC, and can only be posted once
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.status == RedemptionStatus::Deposited @ ErrorCode::CollateralTooLate,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.fee_mint @ ErrorCode::FeeMintMismatch)]
+    pub fee_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, token::mint = fee_mint, token::authority = customer_payment_account)]
+    pub customer_fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = customer_payment_account,
+        seeds = [redemption_info.key().as_ref(), COLLATERAL_SEED],
+        bump,
+        token::mint = fee_mint,
+        token::authority = collateral_account)
+    ]
+    pub collateral_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub customer_payment_account: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(outcome: CollateralOutcome)]
+pub struct SettleCollateral<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.fee_mint @ ErrorCode::FeeMintMismatch)]
+    pub fee_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, seeds = [redemption_info.key().as_ref(), COLLATERAL_SEED], bump = redemption_info.collateral_bump)]
+    pub collateral_account: InterfaceAccount<'info, TokenAccount>,
+
+    // The customer's fee account when releasing the collateral, or the treasury's when liquidating it
+    #[account(
+        mut,
+        constraint = destination_fee_account.mint == config.fee_mint @ ErrorCode::FeeMintMismatch,
+        constraint = destination_fee_account.owner == match outcome {
+            CollateralOutcome::Release => redemption_info.customer_payment_account,
+            CollateralOutcome::Liquidate => config.treasury,
+        } @ ErrorCode::CollateralDestinationMismatch)
+    ]
+    pub destination_fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The BAXUS ops authority, or the authority of the redemption's partner
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct StartBuybackPayout<'info> {
+    // The payout outlives the redemption, which the buy-back closes. It's keyed by the redemption's opening time as well as the mint, so the
+    // asset can be redeemed and bought back again while this payout is still running
+    #[account(
+        init,
+        payer = ops_authority,
+        seeds = [BUYBACK_PAYOUT_SEED, token_mint_account.key().as_ref(), redemption_info.created_at.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + BuybackPayout::INIT_SPACE)
+    ]
+    pub buyback_payout: Account<'info, BuybackPayout>,
+
+    // The token has to still be in escrow, so an asset that has shipped or been moved into the cold vault can't be bought back
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.status < RedemptionStatus::Shipped @ ErrorCode::AlreadyShipped,
+        constraint = redemption_info.cold_vault_account == Pubkey::default() @ ErrorCode::HeldInColdVault,
+        constraint = !redemption_info.frozen_by_compliance @ ErrorCode::FrozenByCompliance,
+        constraint = !redemption_info.has_escrowed_attachments() @ ErrorCode::AttachmentsEscrowed,
+        constraint = redemption_info.collateral_amount == 0 @ ErrorCode::CollateralOutstanding,
+        close = customer_payment_account)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    // Gets back the rent of the escrow and redemption accounts
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref()],
+        bump = redemption_info.escrow_bump.get())
+    ]
+    pub baxus_escrow_account: InterfaceAccount<'info, TokenAccount>,
+
+    // BAXUS keeps the bought-back token in the cold vault
+    #[account(
+        init_if_needed,
+        payer = ops_authority,
+        associated_token::mint = token_mint_account,
+        associated_token::authority = cold_vault_authority,
+        associated_token::token_program = token_program)
+    ]
+    pub cold_vault_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: only used as the owner of the cold vault account, and checked against the config
+    #[account(address = config.cold_vault_authority @ ErrorCode::ColdVaultAuthorityMismatch)]
+    pub cold_vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_SEED, &[redemption_info.status as u8]],
+        bump = status_index.bump)
+    ]
+    pub status_index: Account<'info, StatusIndex>,
+
+    // The page of the status's index the redemption is listed on
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[redemption_info.status as u8], &redemption_info.status_index_page.to_le_bytes()],
+        bump = status_index_page.bump)
+    ]
+    pub status_index_page: Account<'info, StatusIndexPage>,
+
+    // Only needed if the redemption has a priority, which closing it takes out of the index
+    #[account(mut, seeds = [PRIORITY_INDEX_SEED], bump = priority_index.bump)]
+    pub priority_index: Option<Account<'info, PriorityIndex>>,
+
+    // Only needed if the redemption is listed in the customer's registry
+    #[account(mut, seeds = [customer_payment_account.key().as_ref(), CUSTOMER_REGISTRY_SEED], bump = customer_registry.bump)]
+    pub customer_registry: Option<Account<'info, CustomerRegistry>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = ops_authority)]
+    pub config: Account<'info, Config>,
+
+    // Only needed if the treasury takes a share of the escrow account's rent
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: Option<SystemAccount<'info>>,
+
+    // Records the buy-back and its price, keyed by the mint and opening time like every other receipt
+    #[account(
+        init,
+        payer = ops_authority,
+        seeds = [RECEIPT_SEED, token_mint_account.key().as_ref(), redemption_info.created_at.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + RedemptionReceipt::INIT_SPACE)
+    ]
+    pub receipt: Account<'info, RedemptionReceipt>,
+
+    #[account(address = config.fee_mint @ ErrorCode::FeeMintMismatch)]
+    pub fee_mint: InterfaceAccount<'info, Mint>,
+
+    // The ops authority funds the whole payout up front
+    #[account(mut, token::mint = fee_mint, token::authority = ops_authority)]
+    pub ops_fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = ops_authority,
+        seeds = [BUYBACK_ESCROW_SEED, token_mint_account.key().as_ref(), redemption_info.created_at.to_le_bytes().as_ref()],
+        bump,
+        token::mint = fee_mint,
+        token::authority = payout_escrow,
+        token::token_program = fee_token_program)
+    ]
+    pub payout_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub ops_authority: Signer<'info>,
+
+    // The asset's token program
+    pub token_program: Interface<'info, TokenInterface>,
+    // The fee mint's token program, which can be the other one
+    pub fee_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTranche<'info> {
+    // The payout outlives the redemption, so it's found by the mint and opening time it recorded rather than through the redemption
+    #[account(
+        mut,
+        seeds = [BUYBACK_PAYOUT_SEED, buyback_payout.token_mint.as_ref(), buyback_payout.opened_at.to_le_bytes().as_ref()],
+        bump = buyback_payout.bump,
+        has_one = customer,
+        has_one = funder)
+    ]
+    pub buyback_payout: Account<'info, BuybackPayout>,
+
+    #[account(
+        mut,
+        seeds = [BUYBACK_ESCROW_SEED, buyback_payout.token_mint.as_ref(), buyback_payout.opened_at.to_le_bytes().as_ref()],
+        bump = buyback_payout.escrow_bump)
+    ]
+    pub payout_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.fee_mint @ ErrorCode::FeeMintMismatch)]
+    pub fee_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, token::mint = fee_mint, token::authority = customer)]
+    pub customer_fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub customer: Signer<'info>,
+
+    // Gets the payout's rent back once the last tranche is claimed
+    /// CHECK: checked against the payout
+    #[account(mut)]
+    pub funder: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveBurn<'info> {
+    #[account(
+        init_if_needed,
+        payer = brand_authority,
+        seeds = [redemption_info.key().as_ref(), BURN_APPROVAL_SEED],
+        bump,
+        space = 8 + BurnApproval::INIT_SPACE)
+    ]
+    pub burn_approval: Account<'info, BurnApproval>,
+
+    #[account(
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.brand_authority == brand_authority.key() @ ErrorCode::UnauthorizedBrand)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub brand_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordConditionReport<'info> {
+    #[account(
+        init_if_needed,
+        payer = oracle,
+        seeds = [redemption_info.key().as_ref(), CONDITION_LOG_SEED],
+        bump,
+        space = 8 + ConditionLog::INIT_SPACE)
+    ]
+    pub condition_log: Account<'info, ConditionLog>,
+
+    // Sensor data is only reported while the asset is in transit
+    #[account(
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.status == RedemptionStatus::Shipped @ ErrorCode::NotInTransit)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub oracle: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AttachCertificate<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    pub certificate_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, token::mint = certificate_mint, token::authority = customer_payment_account)]
+    pub customer_certificate_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Only needed if the certificate is being escrowed
+    #[account(
+        init,
+        payer = payer,
+        seeds = [certificate_mint.key().as_ref(), ATTACHMENT_ESCROW_SEED],
+        bump,
+        token::mint = certificate_mint,
+        token::authority = certificate_escrow)
+    ]
+    pub certificate_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub customer_payment_account: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DetachCertificate<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    pub certificate_mint: InterfaceAccount<'info, Mint>,
+
+    // Only needed if the certificate was escrowed
+    #[account(mut, seeds = [certificate_mint.key().as_ref(), ATTACHMENT_ESCROW_SEED], bump)]
+    pub certificate_escrow: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = certificate_mint, token::authority = redemption_info.customer_payment_account)]
+    pub customer_certificate_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed when a partner's authority is the operator
+    pub partner: Option<Account<'info, Partner>>,
+
+    // The customer, or an operator
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpRent<'info> {
+    /// CHECK: checked in is_program_account
+    #[account(mut)]
+    pub account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub contributor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOrphanedEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref()],
+        bump,
+        constraint = baxus_escrow_account.amount == 0 @ ErrorCode::EscrowNotEmpty)
+    ]
+    pub baxus_escrow_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    // The escrow account is only orphaned if there's no RedemptionInfo for its mint
+    /// CHECK: only checked for being empty
+    #[account(
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump,
+        constraint = redemption_info.data_is_empty() @ ErrorCode::EscrowNotOrphaned)
+    ]
+    pub redemption_info: UncheckedAccount<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin, has_one = treasury)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: only receives the rent, and checked against the config
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub admin: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct GcRedemption<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        // The escrow account is also empty while the token is in the cold vault, and vaulted assets keep it there for good - their
+        // redemption_info is the only record of where the token is, and recall_from_cold_vault needs it
+        constraint = redemption_info.cold_vault_account == Pubkey::default() @ ErrorCode::HeldInColdVault,
+        constraint = redemption_info.finalize_mode == FinalizeMode::Burn @ ErrorCode::WrongFinalizeMode,
+        constraint = redemption_info.status == RedemptionStatus::Delivered @ ErrorCode::RedemptionNotFinished,
+        constraint = redemption_info.archived_at == 0 @ ErrorCode::RedemptionArchived,
+        constraint = !redemption_info.frozen_by_compliance @ ErrorCode::FrozenByCompliance,
+        constraint = !redemption_info.has_escrowed_attachments() @ ErrorCode::AttachmentsEscrowed,
+        constraint = redemption_info.collateral_amount == 0 @ ErrorCode::CollateralOutstanding,
+        close = customer_payment_account)
+    ]
+    pub redemption_info: Account<'info, VersionedRedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    // May already have been closed
+    /// CHECK: checked in gc_redemption
+    #[account(mut, seeds = [token_mint_account.key().as_ref()], bump = redemption_info.escrow_bump.get())]
+    pub baxus_escrow_account: UncheckedAccount<'info>,
+
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_SEED, &[redemption_info.status as u8]],
+        bump = status_index.bump)
+    ]
+    pub status_index: Account<'info, StatusIndex>,
+
+    // The page of the status's index the redemption is listed on
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[redemption_info.status as u8], &redemption_info.status_index_page.to_le_bytes()],
+        bump = status_index_page.bump)
+    ]
+    pub status_index_page: Account<'info, StatusIndexPage>,
+
+    // Only needed if the redemption is listed in the customer's registry
+    #[account(mut, seeds = [customer_payment_account.key().as_ref(), CUSTOMER_REGISTRY_SEED], bump = customer_registry.bump)]
+    pub customer_registry: Option<Account<'info, CustomerRegistry>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct PublishSnapshot<'info> {
+    #[account(
+        init_if_needed,
+        payer = ops_authority,
+        seeds = [SNAPSHOT_SEED],
+        bump,
+        space = 8 + RedemptionSnapshot::INIT_SPACE)
+    ]
+    pub snapshot: Account<'info, RedemptionSnapshot>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = ops_authority)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub ops_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AssertInvariants<'info> {
+    #[account(seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED], bump = redemption_info.redemption_bump)]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    // The token account holding the asset - its escrow account, or the cold vault account it was moved to. Not needed once archived
+    /// CHECK: checked in check_reserve
+    pub custody_account: Option<UncheckedAccount<'info>>,
+
+    // Only needed while the redemption holds collateral
+    #[account(seeds = [redemption_info.key().as_ref(), COLLATERAL_SEED], bump = redemption_info.collateral_bump)]
+    pub collateral_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    // Only needed if the redemption is listed in a registry
+    #[account(seeds = [redemption_info.customer_payment_account.as_ref(), CUSTOMER_REGISTRY_SEED], bump = customer_registry.bump)]
+    pub customer_registry: Option<Account<'info, CustomerRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct AttestInventory<'info> {
+    #[account(
+        init_if_needed,
+        payer = ops_authority,
+        seeds = [INVENTORY_ATTESTATION_SEED],
+        bump,
+        space = 8 + InventoryAttestation::INIT_SPACE)
+    ]
+    pub inventory_attestation: Account<'info, InventoryAttestation>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = ops_authority)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub ops_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AttestReserves<'info> {
+    #[account(
+        init_if_needed,
+        payer = ops_authority,
+        seeds = [PROOF_OF_RESERVE_SEED],
+        bump,
+        space = 8 + ProofOfReserve::INIT_SPACE)
+    ]
+    pub proof_of_reserve: Account<'info, ProofOfReserve>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = ops_authority)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub ops_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CompleteReserveAttestation<'info> {
+    #[account(mut, seeds = [PROOF_OF_RESERVE_SEED], bump = proof_of_reserve.bump)]
+    pub proof_of_reserve: Account<'info, ProofOfReserve>,
+
+    #[account(seeds = [STATUS_INDEX_SEED, &[RedemptionStatus::Deposited as u8]], bump = deposited_index.bump)]
+    pub deposited_index: Account<'info, StatusIndex>,
+
+    #[account(seeds = [STATUS_INDEX_SEED, &[RedemptionStatus::KycApproved as u8]], bump = kyc_approved_index.bump)]
+    pub kyc_approved_index: Account<'info, StatusIndex>,
+
+    #[account(seeds = [STATUS_INDEX_SEED, &[RedemptionStatus::Packed as u8]], bump = packed_index.bump)]
+    pub packed_index: Account<'info, StatusIndex>,
+
+    #[account(seeds = [STATUS_INDEX_SEED, &[RedemptionStatus::Shipped as u8]], bump = shipped_index.bump)]
+    pub shipped_index: Account<'info, StatusIndex>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = ops_authority)]
+    pub config: Account<'info, Config>,
+
+    pub ops_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PurgeArchived<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.archived_at != 0 @ ErrorCode::RedemptionNotArchived,
+        close = customer_payment_account)
+    ]
+    pub redemption_info: Account<'info, VersionedRedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct RolloverPeriod<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [ACCOUNTING_SEED],
+        bump,
+        space = 8 + Accounting::INIT_SPACE)
+    ]
+    pub accounting: Account<'info, Accounting>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FlagSuspicious<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = compliance_authority)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub compliance_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Unfreeze<'info> {
+    // An appealed rejection stays frozen until resolve_appeal decides it and settles the appeal bond
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.appeal_evidence_hash == [0; 32] || redemption_info.appeal_resolved.is_some() @ ErrorCode::AppealPending)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = compliance_authority, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub compliance_authority: Signer<'info>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FlagSlaBreach<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct PaySlaCompensation<'info> {
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: checked against redemption_info - the customer can be any kind of account
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: UncheckedAccount<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [ACCOUNTING_SEED], bump = accounting.bump)]
+    pub accounting: Account<'info, Accounting>,
+
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SelfReturn<'info> {
+    // Once the asset has shipped the customer has the bottle, so they can't take the token back as well. A rejected redemption goes back
+    // through finalize_rejection instead, once any appeal is settled
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.status < RedemptionStatus::Shipped @ ErrorCode::AlreadyShipped,
+        constraint = redemption_info.cold_vault_account == Pubkey::default() @ ErrorCode::HeldInColdVault,
+        constraint = !redemption_info.frozen_by_compliance @ ErrorCode::FrozenByCompliance,
+        constraint = redemption_info.rejection.is_none() @ ErrorCode::RedemptionRejected,
+        constraint = !redemption_info.has_escrowed_attachments() @ ErrorCode::AttachmentsEscrowed,
+        constraint = redemption_info.collateral_amount == 0 @ ErrorCode::CollateralOutstanding,
+        close = customer_payment_account)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    #[account(
+        mut,
+        constraint = customer_token_account.owner == *customer_payment_account.key @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.customer_token_account == customer_token_account.key() @ ErrorCode::CustomerTokenAccountMismatch)
+    ]
+    pub customer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Only the customer who opened the redemption can take the token back
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: Signer<'info>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref()],
+        bump = redemption_info.escrow_bump.get())
+    ]
+    pub baxus_escrow_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_SEED, &[redemption_info.status as u8]],
+        bump = status_index.bump)
+    ]
+    pub status_index: Account<'info, StatusIndex>,
+
+    // The page of the status's index the redemption is listed on
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[redemption_info.status as u8], &redemption_info.status_index_page.to_le_bytes()],
+        bump = status_index_page.bump)
+    ]
+    pub status_index_page: Account<'info, StatusIndexPage>,
+
+    // Only needed if the redemption has a priority, which closing it takes out of the index
+    #[account(mut, seeds = [PRIORITY_INDEX_SEED], bump = priority_index.bump)]
+    pub priority_index: Option<Account<'info, PriorityIndex>>,
+
+    // Only needed if the redemption is listed in the customer's registry
+    #[account(mut, seeds = [customer_payment_account.key().as_ref(), CUSTOMER_REGISTRY_SEED], bump = customer_registry.bump)]
+    pub customer_registry: Option<Account<'info, CustomerRegistry>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed if the treasury takes a share of the escrow account's rent
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: Option<SystemAccount<'info>>,
+
+    // Used to find out which program called us, if this instruction was invoked through a CPI
+    /// CHECK: checked to be the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    // Records how the redemption ended, keyed by the mint and opening time like every other receipt
+    #[account(
+        init,
+        payer = customer_payment_account,
+        seeds = [RECEIPT_SEED, token_mint_account.key().as_ref(), redemption_info.created_at.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + RedemptionReceipt::INIT_SPACE)
+    ]
+    pub receipt: Account<'info, RedemptionReceipt>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRedemption<'info> {
+    // Like self_return, cancelling can't hand the token back once the customer has the bottle or the token is in the cold vault
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref(), REDEMPTION_SEED],
+        bump = redemption_info.redemption_bump,
+        constraint = redemption_info.penalty_free_cancellation @ ErrorCode::CancellationNotAllowed,
+        constraint = redemption_info.status < RedemptionStatus::Shipped @ ErrorCode::AlreadyShipped,
+        constraint = redemption_info.cold_vault_account == Pubkey::default() @ ErrorCode::HeldInColdVault,
+        constraint = !redemption_info.frozen_by_compliance @ ErrorCode::FrozenByCompliance,
+        constraint = redemption_info.rejection.is_none() @ ErrorCode::RedemptionRejected,
+        constraint = !redemption_info.has_escrowed_attachments() @ ErrorCode::AttachmentsEscrowed,
+        constraint = redemption_info.collateral_amount == 0 @ ErrorCode::CollateralOutstanding,
+        close = customer_payment_account)
+    ]
+    pub redemption_info: Account<'info, RedemptionInfo>,
+
+    #[account(
+        mut,
+        constraint = customer_token_account.owner == *customer_payment_account.key @ ErrorCode::CustomerMismatch,
+        constraint = redemption_info.customer_token_account == customer_token_account.key() @ ErrorCode::CustomerTokenAccountMismatch)
+    ]
+    pub customer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Only the customer who opened the redemption can cancel it
+    #[account(mut, constraint = redemption_info.customer_payment_account == customer_payment_account.key() @ ErrorCode::CustomerMismatch)]
+    pub customer_payment_account: Signer<'info>,
+
+    pub token_mint_account: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [token_mint_account.key().as_ref()],
+        bump = redemption_info.escrow_bump.get())
+    ]
+    pub baxus_escrow_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_SEED, &[redemption_info.status as u8]],
+        bump = status_index.bump)
+    ]
+    pub status_index: Account<'info, StatusIndex>,
+
+    // The page of the status's index the redemption is listed on
+    #[account(
+        mut,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[redemption_info.status as u8], &redemption_info.status_index_page.to_le_bytes()],
+        bump = status_index_page.bump)
+    ]
+    pub status_index_page: Account<'info, StatusIndexPage>,
+
+    // Only needed if the redemption has a priority, which closing it takes out of the index
+    #[account(mut, seeds = [PRIORITY_INDEX_SEED], bump = priority_index.bump)]
+    pub priority_index: Option<Account<'info, PriorityIndex>>,
+
+    // Only needed if the redemption is listed in the customer's registry
+    #[account(mut, seeds = [customer_payment_account.key().as_ref(), CUSTOMER_REGISTRY_SEED], bump = customer_registry.bump)]
+    pub customer_registry: Option<Account<'info, CustomerRegistry>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Only needed if the treasury takes a share of the escrow account's rent
+    #[account(mut, address = config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: Option<SystemAccount<'info>>,
+
+    // Used to find out which program called us, if this instruction was invoked through a CPI
+    /// CHECK: checked to be the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    // Records how the redemption ended, keyed by the mint and opening time like every other receipt
+    #[account(
+        init,
+        payer = customer_payment_account,
+        seeds = [RECEIPT_SEED, token_mint_account.key().as_ref(), redemption_info.created_at.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + RedemptionReceipt::INIT_SPACE)
+    ]
+    pub receipt: Account<'info, RedemptionReceipt>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(status: RedemptionStatus)]
+pub struct InitializeStatusIndex<'info> {
+    #[account(
+        init,
+        payer = admin,
+        seeds = [STATUS_INDEX_SEED, &[status as u8]],
+        bump,
+        space = 8 + StatusIndex::INIT_SPACE)
+    ]
+    pub status_index: Account<'info, StatusIndex>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStatusIndexPage<'info> {
+    #[account(mut, seeds = [STATUS_INDEX_SEED, &[status_index.status as u8]], bump = status_index.bump)]
+    pub status_index: Account<'info, StatusIndex>,
+
+    // Pages are numbered in the order they're created, starting from 0
+    #[account(
+        init,
+        payer = admin,
+        seeds = [STATUS_INDEX_PAGE_SEED, &[status_index.status as u8], &status_index.page_count.to_le_bytes()],
+        bump,
+        space = 8 + StatusIndexPage::INIT_SPACE)
+    ]
+    pub status_index_page: Account<'info, StatusIndexPage>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = upgrade_authority,
+        seeds = [CONFIG_SEED],
+        bump,
+        space = 8 + Config::INIT_SPACE)
+    ]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = upgrade_authority,
+        seeds = [AUDIT_LOG_SEED],
+        bump,
+        space = 8 + std::mem::size_of::<AuditLog>())
+    ]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+
+    // Include the program and its ProgramData account so we can check that the signer is the program's upgrade authority - otherwise anyone could
+    // front-run the deployment and make themselves admin
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::ProgramDataMismatch)]
+    pub program: Program<'info, BaxusRedemptionService>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ ErrorCode::NotUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: LegacyRedemptionParams)]
+pub struct ImportLegacyRedemption<'info> {
+    // Keyed by the mint and opening time, so an asset that was redeemed more than once gets a receipt for each redemption
+    #[account(
+        init,
+        payer = admin,
+        seeds = [RECEIPT_SEED, params.token_mint.as_ref(), params.opened_at.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + RedemptionReceipt::INIT_SPACE)
+    ]
+    pub receipt: Account<'info, RedemptionReceipt>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(collection: Pubkey)]
+pub struct SetCollectionConfig<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [COLLECTION_CONFIG_SEED, collection.as_ref()],
+        bump,
+        space = 8 + CollectionConfig::INIT_SPACE)
+    ]
+    pub collection_config: Account<'info, CollectionConfig>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettlePartnerShare<'info> {
+    #[account(mut, seeds = [PARTNER_SEED, partner.partner_id.to_le_bytes().as_ref()], bump = partner.bump)]
+    pub partner: Account<'info, Partner>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = treasury)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [ACCOUNTING_SEED], bump = accounting.bump)]
+    pub accounting: Account<'info, Accounting>,
+
+    #[account(address = config.fee_mint @ ErrorCode::FeeMintMismatch)]
+    pub fee_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = fee_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program)
+    ]
+    pub treasury_fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, token::mint = fee_mint, token::authority = partner.authority)]
+    pub partner_fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub treasury: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(partner_id: u16)]
+pub struct SetPartner<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [PARTNER_SEED, partner_id.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Partner::INIT_SPACE)
+    ]
+    pub partner: Account<'info, Partner>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [AUDIT_LOG_SEED], bump = config.audit_log_bump)]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin)
+    ]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = config.audit_log_bump)
+    ]
+    pub audit_log: AccountLoader<'info, AuditLog>,
+
+    pub admin: Signer<'info>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RedemptionInfo {
+    customer_token_account: Pubkey,
+    customer_payment_account: Pubkey,
+    escrow_bump: EscrowBump,
+    redemption_bump: u8,
+    created_at: i64,
+    // Unix timestamp after which anyone can expire the redemption, or 0 if it never expires
+    expires_at: i64,
+    // Anti-spam bond held in this account on top of its rent
+    bond_lamports: u64,
+    // Salted hash of the customer's shipping contact details, or all zeroes if they didn't provide one
+    contact_commitment: [u8; 32],
+    // Cold vault token account holding the asset, or the default pubkey while it is in baxus_escrow_account
+    cold_vault_account: Pubkey,
+    status: RedemptionStatus,
+    // Unix timestamp the redemption entered its current status, which its SLA deadline is measured from
+    status_changed_at: i64,
+    // Bit set of the statuses (by discriminant) the redemption has been flagged as overstaying
+    sla_breaches: u8,
+    // Unix timestamp of the first SLA deadline the redemption missed, or 0 if it has never been flagged. The remedies below are set from the
+    // config's SLA policy when it is first flagged
+    sla_breached_at: i64,
+    // The customer owes no fees on this redemption
+    fees_waived: bool,
+    // Lamports the treasury owes the customer for every full day since sla_breached_at
+    compensation_per_day: u64,
+    compensation_paid: u64,
+    // The customer can cancel the redemption with cancel_redemption
+    penalty_free_cancellation: bool,
+    // Hash of the internal ticket or WMS record for the redemption, or all zeroes if ops haven't set one
+    ops_reference: [u8; 32],
+    // Copied from the asset's metadata at initialization - empty for assets whose metadata we can't read
+    #[max_len(MAX_ASSET_NAME_LEN)]
+    asset_name: String,
+    #[max_len(MAX_ASSET_SYMBOL_LEN)]
+    asset_symbol: String,
+    asset_collection: Pubkey,
+    // The asset's appraised value in US cents, locked in from its Appraisal when the redemption was opened
+    valuation_usd_cents: u64,
+    // Unix timestamp the locked valuation was appraised at
+    valued_at: i64,
+    // ISO 3166-1 alpha-2 code of the destination jurisdiction, or all zeroes until the customer sets it
+    jurisdiction: [u8; 2],
+    // Everything on the redemption's invoice has been paid (or waived)
+    invoice_paid: bool,
+    // Full days of storage billed on the invoice by the last settlement
+    storage_days_billed: u64,
+    // Seconds the redemption spent past its SLA deadline in statuses it has already left, which storage isn't charged for
+    storage_paused_secs: i64,
+    // The customer insured the asset in transit when they opened the redemption
+    insured: bool,
+    // Amount the insurance pool pays out if the asset is lost in transit, in US cents
+    coverage_usd_cents: u64,
+    insurance_claimed: bool,
+    // Partner brand the redemption was opened through, or 0 for BAXUS's own redemptions
+    partner_id: u16,
+    // Policy the redemption was opened with, from its collection's CollectionConfig or the config
+    base_fee_usd_cents: u64,
+    finalize_mode: FinalizeMode,
+    required_attestations: u8,
+    service_tier: ServiceTier,
+    // Extra fee for express service in US cents, locked in from the config when the redemption was opened
+    express_fee_usd_cents: u64,
+    // Key the client opened the redemption with, so retries can be recognized, or all zeroes if it didn't pass one
+    idempotency_key: [u8; 32],
+    // Slot the redemption entered its current status in
+    status_changed_slot: u64,
+    // Number of status transitions the redemption has been through, starting at 1 when it was opened
+    sequence: u64,
+    // The redemption left some status after its SLA deadline, whether or not anyone flagged it
+    sla_missed: bool,
+    // Marketplace program that opened the redemption at purchase time with initialize_redemption_for, or the default pubkey
+    marketplace: Pubkey,
+    // Hook program locked in from the collection's CollectionConfig when the redemption opened, or the default pubkey for none
+    hook_program: Pubkey,
+    // Frozen with flag_suspicious, so the redemption can't change status or close until it's unfrozen
+    frozen_by_compliance: bool,
+    // Certificate NFTs attached to the redemption
+    #[max_len(MAX_ATTACHMENTS)]
+    attachments: Vec<Attachment>,
+    // Listed in the customer's CustomerRegistry, which redemptions opened before registries existed aren't
+    in_customer_registry: bool,
+    // Share of the fees paid to the asset's verified creators at burn, locked in from the collection's CollectionConfig
+    creator_share_bps: u16,
+    // Brand key that has to approve the burn, locked in from the collection's CollectionConfig, or the default pubkey for none
+    brand_authority: Pubkey,
+    // Collateral the customer posted to ship before KYC cleared, in base units of the fee mint, or 0 if none is held
+    collateral_amount: u64,
+    collateral_bump: u8,
+    // Unix timestamp BAXUS rejected the redemption at, or 0 if it hasn't been
+    rejected_at: i64,
+    // Why BAXUS rejected the redemption, until the token goes back with finalize_rejection or the rejection is overturned on appeal
+    rejection: Option<RejectionReason>,
+    // Hash of the evidence the customer appealed the rejection with, or all zeroes if they haven't appealed
+    appeal_evidence_hash: [u8; 32],
+    // How compliance and the admin resolved the customer's appeal - Some(true) if the rejection was upheld, Some(false) if it was overturned -
+    // or None while there's no decision
+    appeal_resolved: Option<bool>,
+    // Bond the customer posted with their appeal, held in this account until the appeal is resolved
+    appeal_bond_lamports: u64,
+    // Unix timestamp the redemption was archived at after its token was burned, or 0 if it hasn't been
+    archived_at: i64,
+    // Sequential ID from the RedemptionCounter, or 0 if the redemption was opened before IDs were issued
+    redemption_id: u64,
+    // Sequence number of the last event the redemption emitted
+    event_sequence: u64,
+    // Whether the customer has consented to the token being burned as soon as the asset is delivered, without waiting out the dispute window
+    burn_consent: bool,
+    // Unix timestamp the customer put the redemption on hold at, or 0 if it isn't on hold
+    held_since: i64,
+    // Seconds the redemption has spent on hold in its current status, which don't count towards the status's SLA deadline
+    status_held_secs: i64,
+    // Hash of the shipping manifest and customs documents, committed by the operator before the asset ships, or all zeroes until then
+    shipment_documents_hash: [u8; 32],
+    // Whether the customer has proven in zero knowledge that they passed KYC with an approved provider
+    kyc_verified: bool,
+    // Whether the enhanced approver has to co-sign shipping and burning, locked in from the collection's threshold and the locked valuation
+    enhanced_approval: bool,
+    // How urgently the redemption should ship, or 0 for no priority. Redemptions with a priority are in the priority index, unless an operator's
+    // priority evicted the customer's own from a full index
+    priority: u8,
+    // Share of the rebate and bond the customer donates to the config's charity at burn time, in basis points
+    donation_bps: u16,
+    // Jurisdiction from the customer's profile when the redemption opened, applied by set_jurisdiction with an all-zero code
+    profile_jurisdiction: [u8; 2],
+    // The page of its status's index the redemption is listed on
+    status_index_page: u32,
+    // The fee currency from the customer's profile when the redemption opened. Besides the config's fee mint, it's the only token the
+    // invoice can be settled in, unless it's the default pubkey
+    fee_currency: Pubkey,
+}
+
+// Maximum number of certificates that can be attached to a redemption
+pub const MAX_ATTACHMENTS: usize = 4;
+
+// A certificate NFT attached to a redemption
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone)]
+pub struct Attachment {
+    mint: Pubkey,
+    kind: CertificateKind,
+    // The certificate is held in its escrow account, to be burned along with the asset
+    escrowed: bool,
+    // Bump of the escrow account, if the certificate is escrowed
+    escrow_bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateKind {
+    Authenticity,
+    Provenance,
+}
+
+// Express redemptions pay an extra fee on their invoice and are held to their own, shorter SLA deadlines
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceTier {
+    Standard,
+    Express,
+}
+
+impl RedemptionInfo {
+    // Move the redemption into a new status, recording when it happened and bumping its sequence number
+    pub fn record_transition(&mut self, status: RedemptionStatus, clock: &Clock) {
+        self.status = status;
+        self.status_changed_at = clock.unix_timestamp;
+        self.status_changed_slot = clock.slot;
+        self.sequence += 1;
+        self.status_held_secs = 0;
+    }
+
+    // When the SLA clock for the current status would have started if the redemption had never been on hold
+    pub fn sla_clock_started_at(&self, now: i64) -> i64 {
+        self.status_changed_at + self.status_held_secs + self.held_secs(now)
+    }
+
+    // Seconds the redemption has been on its current hold, or 0 if it isn't on hold
+    pub fn held_secs(&self, now: i64) -> i64 {
+        if self.held_since == 0 {
+            return 0;
+        }
+        now - self.held_since
+    }
+
+    // Whether the redemption can be burned without waiting out the minimum time in Delivered, because the customer consented
+    pub fn burn_consented(&self) -> bool {
+        self.burn_consent && self.status == RedemptionStatus::Delivered
+    }
+
+    // Whether the redemption has spent long enough in its current status to leave it
+    pub fn cooldown_elapsed(&self, config: &Config, slot: u64) -> bool {
+        slot >= self.status_changed_slot.saturating_add(config.min_status_slots[self.status.index()])
+    }
+
+    // Builds the event, so it takes the redemption's next event sequence number
+    pub fn status_changed(&mut self, redemption_info: Pubkey) -> StatusChanged {
+        StatusChanged {
+            redemption_info,
+            redemption_id: self.redemption_id,
+            event_sequence: self.next_event_sequence(),
+            status: self.status,
+            sequence: self.sequence,
+            slot: self.status_changed_slot,
+        }
+    }
+
+    pub fn resynced(&self, redemption_info: Pubkey) -> RedemptionResynced {
+        RedemptionResynced {
+            redemption_info,
+            redemption_id: self.redemption_id,
+            event_sequence: self.event_sequence,
+            status: self.status,
+            sequence: self.sequence,
+            slot: self.status_changed_slot,
+            customer: self.customer_payment_account,
+            held: self.held_since != 0,
+            frozen_by_compliance: self.frozen_by_compliance,
+            shipment_documents_hash: self.shipment_documents_hash,
+        }
+    }
+
+    // Full days the asset has spent in storage since the redemption was opened, not counting the time it spent overdue. Redemptions opened
+    // before we recorded created_at predate storage fees, so they never accrue any
+    pub fn storage_days(&self, config: &Config, now: i64) -> u64 {
+        if self.created_at == 0 {
+            return 0;
+        }
+        let mut stored_secs = now - self.created_at - self.storage_paused_secs - self.overdue_secs(config, now);
+        if config.hold_pauses_storage {
+            stored_secs -= self.held_secs(now);
+        }
+        stored_secs.max(0) as u64 / SECONDS_PER_DAY
+    }
+
+    // Seconds the redemption has been past the SLA deadline for its current status. Only statuses before Shipped count - once the asset has
+    // shipped, storage has stopped anyway
+    pub fn overdue_secs(&self, config: &Config, now: i64) -> i64 {
+        let deadline = config.sla_deadline(self.status, self.service_tier);
+        if deadline == 0 || self.status >= RedemptionStatus::Shipped {
+            return 0;
+        }
+        (now - (self.sla_clock_started_at(now) + deadline)).max(0)
+    }
+
+    // Whether the redemption has been in its current status for longer than the status's SLA deadline, not counting time on hold
+    pub fn past_deadline(&self, config: &Config, now: i64) -> bool {
+        let deadline = config.sla_deadline(self.status, self.service_tier);
+        deadline != 0 && now > self.sla_clock_started_at(now) + deadline
+    }
+
+    // Whether any certificates attached to the redemption are still in escrow
+    pub fn has_escrowed_attachments(&self) -> bool {
+        self.attachments.iter().any(|attachment| attachment.escrowed)
+    }
+
+    // Whether the redemption made it all the way to Delivered without missing an SLA deadline
+    pub fn on_time(&self) -> bool {
+        self.status == RedemptionStatus::Delivered && self.sla_breached_at == 0 && !self.sla_missed
+    }
+
+    pub fn next_event_sequence(&mut self) -> u64 {
+        self.event_sequence += 1;
+        self.event_sequence
+    }
+
+    // Whether the redemption can move on to the given status. Redemptions holding collateral or with a verified KYC proof can skip KycApproved
+    pub fn can_advance_to(&self, status: RedemptionStatus) -> bool {
+        self.status.next() == Some(status)
+            || (self.status == RedemptionStatus::Deposited
+                && status == RedemptionStatus::Packed
+                && (self.collateral_amount > 0 || self.kyc_verified))
+    }
+
+    // Whether the customer has paid for every day the asset has been stored. Storage is always billed and paid together with the rest of the
+    // invoice, so anything billed has been paid
+    pub fn storage_fees_cleared(&self, config: &Config, now: i64) -> bool {
+        self.fees_waived
+            || config.storage_fee_per_day_usd_cents == 0
+            || self.status >= RedemptionStatus::Shipped
+            || self.storage_days(config, now) <= self.storage_days_billed
+    }
+}
+
+// A RedemptionInfo account that may have been opened with an older, shorter layout. Every field added since the original layout (and in any
+// layout since) decodes from zero bytes to its default - zero, false, the default pubkey, an empty string or the first enum variant - so we
+// pad the account's data with zeroes up to the current layout and decode it as usual. Writing it back always uses the current layout, so the
+// account has to have been grown to the current size first (which migrate_redemption_info does)
+#[derive(Clone)]
+pub struct VersionedRedemptionInfo(RedemptionInfo);
+
+impl AccountDeserialize for VersionedRedemptionInfo {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        if buf.len() < RedemptionInfo::DISCRIMINATOR.len() {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorNotFound);
+        }
+        if buf[..8] != RedemptionInfo::DISCRIMINATOR {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch);
+        }
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let mut data = buf[8..].to_vec();
+        if data.len() < RedemptionInfo::INIT_SPACE {
+            data.resize(RedemptionInfo::INIT_SPACE, 0);
+        }
+        RedemptionInfo::deserialize(&mut data.as_slice())
+            .map(VersionedRedemptionInfo)
+            .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))
+    }
+}
+
+impl AccountSerialize for VersionedRedemptionInfo {
+    fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        self.0.try_serialize(writer)
+    }
+}
+
+impl Owner for VersionedRedemptionInfo {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+}
+
+impl std::ops::Deref for VersionedRedemptionInfo {
+    type Target = RedemptionInfo;
+
+    fn deref(&self) -> &RedemptionInfo {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for VersionedRedemptionInfo {
+    fn deref_mut(&mut self) -> &mut RedemptionInfo {
+        &mut self.0
+    }
+}
+
+// The outcome of BAXUS's latest risk check on a customer wallet
+#[account]
+#[derive(InitSpace)]
+pub struct RiskScore {
+    // Higher scores are riskier
+    score: u8,
+    // Unix timestamp of the check
+    assessed_at: i64,
+    bump: u8,
+}
+
+// A customer's saved defaults for their redemptions, keyed by their wallet
+#[account]
+#[derive(InitSpace)]
+pub struct CustomerProfile {
+    // ISO 3166-1 alpha-2 code of the jurisdiction they usually ship to, or all zeroes for none
+    jurisdiction: [u8; 2],
+    // Whether to insure their redemptions
+    insure: bool,
+    // Mint of the token their invoices have to be settled in, if not the config's fee mint, or the default pubkey for any whitelisted token
+    fee_currency: Pubkey,
+    bump: u8,
+}
+
+// BAXUS's current appraisal of an asset, keyed by its mint
+#[account]
+#[derive(InitSpace)]
+pub struct Appraisal {
+    usd_value_cents: u64,
+    appraised_at: i64,
+    bump: u8,
+}
+
+// Maximum number of handoffs in a custody log
+pub const MAX_CUSTODY_ENTRIES: usize = 16;
+
+// Append-only record of everyone who has held a redemption's physical asset since it left the vault
+#[account]
+#[derive(InitSpace)]
+pub struct CustodyLog {
+    redemption_info: Pubkey,
+    bump: u8,
+    #[max_len(MAX_CUSTODY_ENTRIES)]
+    entries: Vec<CustodyEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone)]
+pub struct CustodyEntry {
+    // Where the asset went
+    stage: CustodyStage,
+    // Key of whoever took custody of the asset
+    custodian: Pubkey,
+    // The previous custodian, or the operator who recorded the handoff on their behalf
+    recorded_by: Pubkey,
+    recorded_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum CustodyStage {
+    Vault,
+    Courier,
+    RegionalHub,
+    Customer,
+}
+
+// Maximum number of sensor reports in a condition log
+pub const MAX_CONDITION_REPORTS: usize = 16;
+
+// Sensor data summaries condition oracles reported for a redemption's shipment
+#[account]
+#[derive(InitSpace)]
+pub struct ConditionLog {
+    redemption_info: Pubkey,
+    bump: u8,
+    #[max_len(MAX_CONDITION_REPORTS)]
+    reports: Vec<ConditionReport>,
+}
+
+// Summary of the sensor readings for one window of a shipment
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone)]
+pub struct ConditionReport {
+    oracle: Pubkey,
+    recorded_at: i64,
+    // Unix timestamps the readings were taken between
+    window_start: i64,
+    window_end: i64,
+    // Temperature extremes in hundredths of a degree Celsius
+    min_temperature_centi_c: i16,
+    max_temperature_centi_c: i16,
+    // Highest relative humidity, in basis points
+    max_humidity_bps: u16,
+    // Seconds the shipment spent outside the safe temperature and humidity range
+    excursion_secs: u32,
+    // Hash of the raw readings, which the oracle keeps off-chain
+    readings_hash: [u8; 32],
+}
+
+// What a condition oracle passes to record_condition_report
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConditionReportParams {
+    pub window_start: i64,
+    pub window_end: i64,
+    pub min_temperature_centi_c: i16,
+    pub max_temperature_centi_c: i16,
+    pub max_humidity_bps: u16,
+    pub excursion_secs: u32,
+    pub readings_hash: [u8; 32],
+}
+
+// Number of months the Accounting account keeps totals for
+pub const ACCOUNTING_PERIODS: usize = 12;
+
+// Money moved by the program, totalled by calendar month. The months are kept in a ring, so each month's totals are overwritten a year later
+#[account]
+#[derive(InitSpace)]
+pub struct Accounting {
+    bump: u8,
+    // The month totals are currently being added to, as months since January of year 0
+    current_period: u32,
+    periods: [PeriodTotals; ACCOUNTING_PERIODS],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Default)]
+pub struct PeriodTotals {
+    // Months since January of year 0
+    period: u32,
+    // Fees customers paid on their invoices
+    fees_usd_cents: u64,
+    // On-time rebates paid back to customers
+    refunds_usd_cents: u64,
+    // Insurance claims and partner fee shares paid out
+    payouts_usd_cents: u64,
+    // SLA compensation paid out, which is in lamports rather than the fee mint
+    compensation_lamports: u64,
+}
+
+impl Accounting {
+    // Move on to the month containing now, starting fresh totals for it and any months skipped since the last one
+    pub fn roll_to(&mut self, now: i64) {
+        let period = month_index(now);
+        if period <= self.current_period {
+            return;
+        }
+
+        let first = (self.current_period + 1).max(period.saturating_sub(ACCOUNTING_PERIODS as u32 - 1));
+        for skipped in first..=period {
+            self.periods[skipped as usize % ACCOUNTING_PERIODS] = PeriodTotals { period: skipped, ..Default::default() };
+        }
+        self.current_period = period;
+    }
+
+    // Totals for the month containing now
+    pub fn current(&mut self, now: i64) -> &mut PeriodTotals {
+        self.roll_to(now);
+        &mut self.periods[self.current_period as usize % ACCOUNTING_PERIODS]
+    }
+}
+
+// Calendar month (UTC) of a Unix timestamp, as months since January of year 0. Uses the days-to-civil-date algorithm from
+// http://howardhinnant.github.io/date_algorithms.html
+fn month_index(unix_timestamp: i64) -> u32 {
+    let days = unix_timestamp.div_euclid(SECONDS_PER_DAY as i64) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year * 12 + month - 1) as u32
+}
+
+// Maximum number of line items on an invoice
+pub const MAX_INVOICE_ITEMS: usize = 16;
+
+// Itemized list of everything the customer owes on a redemption, kept after the redemption closes as a record for finance
+#[account]
+#[derive(InitSpace)]
+pub struct Invoice {
+    redemption_info: Pubkey,
+    bump: u8,
+    // Total paid so far with settle_invoice, in US cents
+    paid_usd_cents: u64,
+    // Fees paid so far that the redemption's partner has been credited its share of, in US cents
+    fees_credited_usd_cents: u64,
+    #[max_len(MAX_INVOICE_ITEMS)]
+    line_items: Vec<LineItem>,
+}
+
+impl Invoice {
+    pub fn add_item(&mut self, kind: LineItemKind, amount_usd_cents: u64) -> Result<()> {
+        require!(self.line_items.len() < MAX_INVOICE_ITEMS, ErrorCode::InvoiceFull);
+        self.line_items.push(LineItem { kind, amount_usd_cents });
+
+        Ok(())
+    }
+
+    // Replace the invoice's line item of the given kind, or add one if it doesn't have one yet
+    pub fn set_item(&mut self, kind: LineItemKind, amount_usd_cents: u64) -> Result<()> {
+        match self.line_items.iter_mut().find(|item| item.kind == kind) {
+            Some(item) => item.amount_usd_cents = amount_usd_cents,
+            None => self.add_item(kind, amount_usd_cents)?,
+        }
+
+        Ok(())
+    }
+
+    // Total of BAXUS's fees on the invoice in US cents, or 0 if they were waived
+    pub fn fees_total(&self, fees_waived: bool) -> u64 {
+        if fees_waived {
+            return 0;
+        }
+        self.line_items.iter().filter(|item| item.kind.is_fee()).map(|item| item.amount_usd_cents).sum()
+    }
+
+    // Amount still owed in US cents, leaving out BAXUS's fees if they were waived
+    pub fn amount_due(&self, fees_waived: bool) -> u64 {
+        let total: u64 = self
+            .line_items
+            .iter()
+            .filter(|item| !(fees_waived && item.kind.is_fee()))
+            .map(|item| item.amount_usd_cents)
+            .sum();
+        total.saturating_sub(self.paid_usd_cents)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone)]
+pub struct LineItem {
+    kind: LineItemKind,
+    amount_usd_cents: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum LineItemKind {
+    BaseFee,
+    Shipping,
+    Insurance,
+    Duty,
+    Penalty,
+    StorageFee,
+    ExpressFee,
+}
+
+impl LineItemKind {
+    // Whether the line item is a BAXUS fee, which is dropped when a redemption's fees are waived - shipping, insurance and duty are costs
+    // BAXUS passes on, so the customer still has to pay them
+    pub fn is_fee(self) -> bool {
+        matches!(self, LineItemKind::BaseFee | LineItemKind::Penalty | LineItemKind::StorageFee | LineItemKind::ExpressFee)
+    }
+}
+
+// A token the admin has whitelisted for paying invoices, keyed by its mint
+#[account]
+#[derive(InitSpace)]
+pub struct FeeCurrency {
+    mint: Pubkey,
+    // Pyth price account pricing the token in US dollars
+    price_feed: Pubkey,
+    // Oldest Pyth price we'll convert at, in seconds
+    max_price_age: u64,
+    bump: u8,
+}
+
+// A made-up US dollar price for a mint, in the same form as a Pyth price, for integration tests in localnet-mocks builds
+#[account]
+#[derive(InitSpace)]
+pub struct MockPriceFeed {
+    price: i64,
+    conf: u64,
+    expo: i32,
+    publish_time: i64,
+    bump: u8,
+}
+
+// Maximum number of jurisdictions in the duty rate table
+pub const MAX_DUTY_RATES: usize = 64;
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// The admin's table of import duty rates by destination jurisdiction
+#[account]
+#[derive(InitSpace)]
+pub struct DutyRates {
+    bump: u8,
+    #[max_len(MAX_DUTY_RATES)]
+    rates: Vec<DutyRate>,
+}
+
+impl DutyRates {
+    // Duty rate for a jurisdiction in basis points of the asset's value, or None if BAXUS doesn't ship there
+    pub fn rate_bps(&self, jurisdiction: [u8; 2]) -> Option<u16> {
+        self.rates.iter().find(|rate| rate.jurisdiction == jurisdiction).map(|rate| rate.rate_bps)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone)]
+pub struct DutyRate {
+    jurisdiction: [u8; 2],
+    rate_bps: u16,
+}
+
+// Longest asset name and symbol kept in redemption_info - the same limits Metaplex metadata has
+pub const MAX_ASSET_NAME_LEN: usize = 32;
+pub const MAX_ASSET_SYMBOL_LEN: usize = 10;
+
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+pub const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
+
+// The stages a redemption goes through, in order - statuses compare by how far along the lifecycle they are. Status indexes are keyed by
+// the discriminant, so only ever append new variants
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum RedemptionStatus {
+    Deposited,
+    KycApproved,
+    Packed,
+    Shipped,
+    Delivered,
+}
+
+impl RedemptionStatus {
+    pub fn next(self) -> Option<RedemptionStatus> {
+        match self {
+            RedemptionStatus::Deposited => Some(RedemptionStatus::KycApproved),
+            RedemptionStatus::KycApproved => Some(RedemptionStatus::Packed),
+            RedemptionStatus::Packed => Some(RedemptionStatus::Shipped),
+            RedemptionStatus::Shipped => Some(RedemptionStatus::Delivered),
+            RedemptionStatus::Delivered => None,
+        }
+    }
+
+    // Position of the status in arrays indexed by RedemptionStatus, like the SLA deadlines
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    // The status's bit in a bit set of statuses, like sla_breaches
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    // How the status reads in a status memo
+    pub fn label(self) -> &'static str {
+        match self {
+            RedemptionStatus::Deposited => "deposited",
+            RedemptionStatus::KycApproved => "KYC approved",
+            RedemptionStatus::Packed => "packed",
+            RedemptionStatus::Shipped => "shipped",
+            RedemptionStatus::Delivered => "delivered",
+        }
+    }
+}
+
+// Bump of a redemption's escrow account PDA. It can only be made from a bump that actually derives the escrow account for the asset's mint,
+// so a bump meant for another PDA can't end up signing for the escrow
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub struct EscrowBump {
+    bump: u8,
+}
+
+impl EscrowBump {
+    pub fn new(token_mint: &Pubkey, escrow: &Pubkey, bump: u8) -> Result<Self> {
+        let derived = Pubkey::create_program_address(&[token_mint.as_ref(), &[bump]], &crate::ID).map_err(|_| error!(ErrorCode::InvalidEscrowBump))?;
+        require_keys_eq!(derived, *escrow, ErrorCode::InvalidEscrowBump);
+
+        Ok(EscrowBump { bump })
+    }
+
+    pub fn get(self) -> u8 {
+        self.bump
+    }
+}
+
+// An amount of an asset token, in the token's base units. BAXUS assets are NFTs, so the whole token is the only amount that can exist - keeping
+// token amounts apart from plain u64s means lamports or fee token amounts can't be passed where an asset amount belongs. It's always the raw
+// amount, even for interest-bearing mints whose wallets display a scaled one
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct AssetAmount {
+    amount: u64,
+}
+
+impl AssetAmount {
+    pub const WHOLE: AssetAmount = AssetAmount { amount: 1 };
+
+    pub fn get(self) -> u64 {
+        self.amount
+    }
+}
+
+// Number of redemptions a StatusIndexPage can list
+pub const STATUS_INDEX_PAGE_SIZE: usize = 64;
+
+// Counts the redemptions currently in one status. The redemptions themselves are listed on the status's StatusIndexPages, which are fixed in
+// size and created by the admin, so no account has to grow or shrink as redemptions move between statuses
+#[account]
+#[derive(InitSpace)]
+pub struct StatusIndex {
+    status: RedemptionStatus,
+    bump: u8,
+    // Number of redemptions in the status, across all of its pages
+    count: u64,
+    // Number of pages the admin has created for the status, numbered from 0
+    page_count: u32,
+}
+
+// One page of the redemptions in a status. Each redemption records which page it's on, so it can be found again when it leaves the status
+#[account]
+#[derive(InitSpace)]
+pub struct StatusIndexPage {
+    status: RedemptionStatus,
+    page: u32,
+    bump: u8,
+    #[max_len(STATUS_INDEX_PAGE_SIZE)]
+    redemptions: Vec<Pubkey>,
+}
+
+impl StatusIndex {
+    pub fn insert(&mut self, page: &mut StatusIndexPage, redemption: Pubkey) -> Result<()> {
+        require!(page.redemptions.len() < STATUS_INDEX_PAGE_SIZE, ErrorCode::StatusIndexPageFull);
+        page.redemptions.push(redemption);
+        self.count += 1;
+
+        Ok(())
+    }
+
+    // Redemptions opened before status indexes existed were never inserted, so a missing entry isn't an error
+    pub fn remove(&mut self, page: &mut StatusIndexPage, redemption: &Pubkey) {
+        if let Some(position) = page.redemptions.iter().position(|entry| entry == redemption) {
+            page.redemptions.swap_remove(position);
+            self.count -= 1;
+        }
+    }
+}
+
+// Issues redemption IDs
+#[account]
+#[derive(InitSpace)]
+pub struct RedemptionCounter {
+    // The ID given to the last redemption opened
+    last_id: u64,
+    bump: u8,
+}
+
+// The latest proof-of-reserve attestation, which may still be in progress
+#[account]
+#[derive(InitSpace)]
+pub struct ProofOfReserve {
+    // Unix timestamp the attestation was started, and the last time redemptions were added to it
+    started_at: i64,
+    attested_at: i64,
+    // Number of redemptions whose asset token was found in custody
+    attested_count: u32,
+    // Hash chain over the (redemption_info, custody account) pairs in the order they were attested, starting from all zeroes
+    root: [u8; 32],
+    // The last redemption attested, since they have to come in ascending order
+    last_redemption: Pubkey,
+    bump: u8,
+    // Whether the attestation was checked to cover every open redemption, how many there were, and when. Until then it's only partial
+    completed: bool,
+    open_count: u64,
+    completed_at: i64,
+}
+
+// The latest weekly stock-take of the bottles backing open escrows
+#[account]
+#[derive(InitSpace)]
+pub struct InventoryAttestation {
+    // Weeks since the Unix epoch, since there can only be one attestation a week
+    week: u64,
+    // Number of bottles physically verified in the warehouse
+    bottle_count: u32,
+    // Hash of the signed stock-take document
+    document_hash: [u8; 32],
+    // Unix timestamp of the attestation
+    attested_at: i64,
+    bump: u8,
+}
+
+// Merkle root over the open redemptions as of the epoch it was published in
+#[account]
+#[derive(InitSpace)]
+pub struct RedemptionSnapshot {
+    epoch: u64,
+    root: [u8; 32],
+    // Number of leaves in the tree
+    redemption_count: u32,
+    published_at: i64,
+    bump: u8,
+}
+
+impl RedemptionSnapshot {
+    // The leaf for a redemption in its current state
+    pub fn leaf(redemption_info: &Pubkey, status: RedemptionStatus, sequence: u64) -> [u8; 32] {
+        hashv(&[redemption_info.as_ref(), &[status as u8], &sequence.to_le_bytes()]).to_bytes()
+    }
+
+    // Whether the proof (the sibling hashes from the leaf up to the root) shows the leaf is in the published tree
+    pub fn verify(&self, leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+        let computed = proof.iter().fold(leaf, |node, sibling| {
+            let (left, right) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+            hashv(&[&left, &right]).to_bytes()
+        });
+        computed == self.root
+    }
+}
+
+// A buy-back being paid out to the customer in tranches
+#[account]
+#[derive(InitSpace)]
+pub struct BuybackPayout {
+    // The bought-back asset's mint, and created_at of the redemption it was bought back from
+    token_mint: Pubkey,
+    opened_at: i64,
+    customer: Pubkey,
+    // The ops authority that escrowed the payment, which gets the rent back
+    funder: Pubkey,
+    // The buy-back price, and the same in fee mint base units
+    amount_usd_cents: u64,
+    amount: u64,
+    tranche_count: u8,
+    tranches_claimed: u8,
+    tranche_interval_secs: i64,
+    // Unix timestamp the first tranche unlocked at
+    started_at: i64,
+    escrow_bump: u8,
+    bump: u8,
+}
+
+impl BuybackPayout {
+    // Number of tranches unlocked by the given time - the first unlocks when the payout starts
+    pub fn tranches_unlocked(&self, now: i64) -> u8 {
+        if self.tranche_interval_secs == 0 {
+            return self.tranche_count;
+        }
+        let intervals = (now - self.started_at).max(0) / self.tranche_interval_secs;
+        intervals.saturating_add(1).min(self.tranche_count as i64) as u8
+    }
+
+    // Amount paid out by the end of the given number of tranches. Rounding down along the way leaves the remainder for the last tranche
+    pub fn amount_through(&self, tranches: u8) -> u64 {
+        (self.amount as u128 * tranches as u128 / self.tranche_count as u128) as u64
+    }
+}
+
+// A brand's approval to burn a redemption's token
+#[account]
+#[derive(InitSpace)]
+pub struct BurnApproval {
+    redemption_info: Pubkey,
+    // created_at of the redemption that was approved, since a later redemption of the same asset reuses the redemption_info address
+    redemption_opened_at: i64,
+    approved_at: i64,
+    bump: u8,
+}
+
+// Points from an escrow token account back to the redemption it holds the asset for
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowLink {
+    redemption_info: Pubkey,
+    token_mint: Pubkey,
+    bump: u8,
+}
+
+// Maximum number of redemptions a customer can have open at once
+pub const MAX_CUSTOMER_REDEMPTIONS: usize = 32;
+
+// Lists one customer's open redemptions
+#[account]
+#[derive(InitSpace)]
+pub struct CustomerRegistry {
+    customer: Pubkey,
+    bump: u8,
+    #[max_len(MAX_CUSTOMER_REDEMPTIONS)]
+    redemptions: Vec<Pubkey>,
+}
+
+impl CustomerRegistry {
+    pub fn insert(&mut self, redemption: Pubkey) -> Result<()> {
+        require!(self.redemptions.len() < MAX_CUSTOMER_REDEMPTIONS, ErrorCode::TooManyOpenRedemptions);
+        self.redemptions.push(redemption);
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, redemption: &Pubkey) {
+        if let Some(position) = self.redemptions.iter().position(|entry| entry == redemption) {
+            self.redemptions.swap_remove(position);
+        }
+    }
+}
+
+// Maximum number of redemptions the priority index can hold
+pub const MAX_PRIORITY_ENTRIES: usize = 32;
+
+// Highest priority the customer of an express redemption can give it themselves
+pub const EXPRESS_MAX_PRIORITY: u8 = 1;
+
+// Maximum number of entries in the priority index that customers can have set themselves, so they can't crowd out operators
+pub const MAX_CUSTOMER_PRIORITY_ENTRIES: usize = 8;
+
+// The unshipped redemptions that have a priority, most urgent first. Redemptions with the same priority stay in the order they were given it
+#[account]
+#[derive(InitSpace)]
+pub struct PriorityIndex {
+    #[max_len(MAX_PRIORITY_ENTRIES)]
+    entries: Vec<PriorityEntry>,
+    bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy)]
+pub struct PriorityEntry {
+    redemption: Pubkey,
+    priority: u8,
+    // Whether the customer set the priority themselves, rather than an operator
+    customer_set: bool,
+}
+
+impl PriorityIndex {
+    // When the index is full, a priority set by an operator takes the place of the least urgent one set by a customer. The evicted
+    // redemption keeps its priority, but is no longer in the index until its priority is set again
+    pub fn insert(&mut self, redemption: Pubkey, priority: u8, customer_set: bool) -> Result<()> {
+        if customer_set {
+            let customer_entries = self.entries.iter().filter(|entry| entry.customer_set).count();
+            require!(customer_entries < MAX_CUSTOMER_PRIORITY_ENTRIES, ErrorCode::CustomerPriorityLimit);
+        }
+        if self.entries.len() >= MAX_PRIORITY_ENTRIES {
+            let evicted = if customer_set { None } else { self.entries.iter().rposition(|entry| entry.customer_set) };
+            self.entries.remove(evicted.ok_or(ErrorCode::PriorityIndexFull)?);
+        }
+
+        let position = self.entries.iter().position(|entry| entry.priority < priority).unwrap_or(self.entries.len());
+        self.entries.insert(position, PriorityEntry { redemption, priority, customer_set });
+
+        Ok(())
+    }
+
+    // Unlike status indexes, the order matters here, so entries after the removed one are shifted down rather than swapped in
+    pub fn remove(&mut self, redemption: &Pubkey) {
+        if let Some(position) = self.entries.iter().position(|entry| entry.redemption == *redemption) {
+            self.entries.remove(position);
+        }
+    }
+}
+
+// Maximum number of guardians a customer can register on a redemption
+pub const MAX_GUARDIANS: usize = 8;
+
+// The wallets a customer trusts to recover a redemption for them if they lose their own
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianSet {
+    #[max_len(MAX_GUARDIANS)]
+    guardians: Vec<Pubkey>,
+    // Number of guardians who have to approve a recovery
+    threshold: u8,
+    bump: u8,
+}
+
+// A pending move of a redemption to a new wallet, approved by some of its guardians so far
+#[account]
+#[derive(InitSpace)]
+pub struct RecoveryRequest {
+    new_customer_payment_account: Pubkey,
+    new_customer_token_account: Pubkey,
+    // The guardian who opened the request and paid its rent
+    proposer: Pubkey,
+    // Bit set of the guardians who have approved, by their position in the GuardianSet
+    approvals: u8,
+    // Unix timestamp of the first approval, which starts the timelock
+    requested_at: i64,
+    bump: u8,
+}
+
+// Maximum size of the encrypted contact details stored by reveal_contact
+pub const MAX_CONTACT_CIPHERTEXT_LEN: usize = 512;
+
+#[account]
+#[derive(InitSpace)]
+pub struct ContactReveal {
+    redemption_info: Pubkey,
+    customer_payment_account: Pubkey,
+    #[max_len(MAX_CONTACT_CIPHERTEXT_LEN)]
+    ciphertext: Vec<u8>,
+}
+
+// Program-wide settings, controlled by the BAXUS admin. Every change the admin makes is also appended to the AuditLog
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    admin: Pubkey,
+    // Signs day-to-day operational instructions, like moving tokens into the cold vault
+    ops_authority: Pubkey,
+    // Multisig that owns the cold vault token accounts
+    cold_vault_authority: Pubkey,
+    // Can freeze redemptions pending a compliance review, and has to co-sign with the admin to unfreeze them
+    compliance_authority: Pubkey,
+    treasury: Pubkey,
+    // Refundable SOL bond customers must lock when opening a redemption, or 0 for no bond
+    bond_lamports: u64,
+    // Seconds after opening that a redemption can be expired, or 0 if redemptions never expire
+    redemption_ttl: i64,
+    // Public key customers encrypt their contact details to in reveal_contact
+    ops_encryption_key: [u8; 32],
+    // Update authority that Token-2022 native metadata must have, or the default pubkey to skip metadata checks
+    metadata_authority: Pubkey,
+    // Collection (or token group) that Token-2022 mints must belong to, or the default pubkey to allow any collection
+    collection: Pubkey,
+    // Seconds a redemption may spend in each status before it breaches its SLA, indexed by RedemptionStatus, or 0 for no deadline. Delivered is
+    // final, so it has no deadline
+    sla_deadlines: [i64; SLA_STAGES],
+    // The same for express redemptions
+    express_sla_deadlines: [i64; SLA_STAGES],
+    // Extra fee on express redemptions, in US cents
+    express_fee_usd_cents: u64,
+    // SLA policy - the remedies a redemption gets when it first breaches its SLA
+    sla_waive_fees: bool,
+    sla_compensation_per_day: u64,
+    sla_allow_cancellation: bool,
+    // Whether other programs can call customer-facing instructions through a CPI
+    cpi_policy: CpiPolicy,
+    // Programs allowed to call customer-facing instructions when cpi_policy is TrustedCallersOnly
+    #[max_len(MAX_TRUSTED_CALLERS)]
+    trusted_callers: Vec<Pubkey>,
+    // Marketplace programs allowed to call initialize_redemption_for
+    #[max_len(MAX_MARKETPLACES)]
+    marketplaces: Vec<Pubkey>,
+    // Oracles allowed to report sensor data for shipments in transit
+    #[max_len(MAX_CONDITION_ORACLES)]
+    condition_oracles: Vec<Pubkey>,
+    // USD stablecoin that invoices are paid in
+    fee_mint: Pubkey,
+    // Fee charged on every redemption, in US cents
+    base_fee_usd_cents: u64,
+    // Fee charged for every full day a redemption's asset is stored before it ships, in US cents
+    storage_fee_per_day_usd_cents: u64,
+    // Owner of the fee mint account that insurance premiums are paid into and claims are paid out of, or the default pubkey if BAXUS doesn't
+    // offer insurance
+    insurance_pool: Pubkey,
+    // Insurance premium as a share of the asset's locked valuation, in basis points
+    insurance_premium_bps: u16,
+    // Share of the fees paid that's rebated to the customer when a redemption is delivered within its SLA, in basis points
+    on_time_rebate_bps: u16,
+    // Collateral a customer posts to ship before KYC clears, as a share of the asset's locked valuation in basis points, or 0 to not offer it
+    expedite_collateral_bps: u16,
+    // Seconds burned redemptions stay archived before they can be purged, or 0 to close them as soon as the token is burned
+    archive_retention_secs: i64,
+    // Turns off the original versions of instructions that have a _v2, once integrators have moved over
+    v1_disabled: bool,
+    // Minimum number of slots a redemption has to spend in each status, indexed by RedemptionStatus, before it can be advanced - or for
+    // Delivered, before it can be burned
+    min_status_slots: [u64; STATUS_COUNT],
+    // Seconds a redemption can go without a status transition before the customer can take their token back with self_return, or 0 to
+    // turn the dead-man switch off
+    dead_man_secs: i64,
+    // Seconds a redemption has to go without a status transition before ops can move its token into the cold vault
+    cold_vault_min_dwell_secs: i64,
+    // Whether time a customer keeps their redemption on hold is left off its storage bill
+    hold_pauses_storage: bool,
+    // Seconds after the first guardian approval before the admin can carry out a recovery
+    recovery_timelock_secs: i64,
+    // Groth16 verifier program for zero-knowledge KYC proofs, or the default pubkey to not accept them
+    kyc_verifier: Pubkey,
+    // Root of the approved KYC providers' keys, which every KYC proof is checked against
+    kyc_providers_root: [u8; 32],
+    // Multisig that co-signs shipping and burning redemptions above their collection's enhanced approval threshold
+    enhanced_approver: Pubkey,
+    // Token programs whose mints can be redeemed
+    #[max_len(MAX_TOKEN_PROGRAMS)]
+    allowed_token_programs: Vec<Pubkey>,
+    // Whether assets that fail the metadata and collection checks are turned away, or only reported in a ValidationWarning event
+    strict_mode: bool,
+    // Share of a closed escrow account's rent that goes to the treasury rather than the customer, in basis points, for when BAXUS paid it
+    treasury_rent_share_bps: u16,
+    // Wallet customers can donate part of their rebate and bond to when their token is burned, or the default pubkey to turn donations off
+    charity: Pubkey,
+    // Whether customers need a recent enough RiskScore no higher than max_risk_score to open a redemption
+    risk_check_required: bool,
+    max_risk_score: u8,
+    // Seconds a risk score is good for, or 0 if scores never go stale
+    risk_score_max_age_secs: i64,
+    // Seconds the customer has to appeal a rejected redemption before it can be finalized
+    appeal_window_secs: i64,
+    // Bond the customer posts to appeal a rejection, refunded if the rejection is overturned and forfeited to the treasury if it's upheld
+    appeal_bond_lamports: u64,
+    // How far warp_clock has moved the clock forward, which can only be non-zero in localnet-mocks builds
+    clock_warp_secs: i64,
+    clock_warp_slots: u64,
+    paused: bool,
+    bump: u8,
+    audit_log_bump: u8,
+}
+
+// The settings the admin passes to initialize_config and update_config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConfigParams {
+    pub admin: Pubkey,
+    pub ops_authority: Pubkey,
+    pub cold_vault_authority: Pubkey,
+    pub compliance_authority: Pubkey,
+    pub treasury: Pubkey,
+    pub bond_lamports: u64,
+    pub redemption_ttl: i64,
+    pub ops_encryption_key: [u8; 32],
+    pub metadata_authority: Pubkey,
+    pub collection: Pubkey,
+    pub sla_deadlines: [i64; SLA_STAGES],
+    pub express_sla_deadlines: [i64; SLA_STAGES],
+    pub express_fee_usd_cents: u64,
+    pub sla_waive_fees: bool,
+    pub sla_compensation_per_day: u64,
+    pub sla_allow_cancellation: bool,
+    pub cpi_policy: CpiPolicy,
+    pub trusted_callers: Vec<Pubkey>,
+    pub marketplaces: Vec<Pubkey>,
+    pub condition_oracles: Vec<Pubkey>,
+    pub fee_mint: Pubkey,
+    pub base_fee_usd_cents: u64,
+    pub storage_fee_per_day_usd_cents: u64,
+    pub insurance_pool: Pubkey,
+    pub insurance_premium_bps: u16,
+    pub on_time_rebate_bps: u16,
+    pub expedite_collateral_bps: u16,
+    pub archive_retention_secs: i64,
+    pub v1_disabled: bool,
+    pub min_status_slots: [u64; STATUS_COUNT],
+    pub dead_man_secs: i64,
+    pub cold_vault_min_dwell_secs: i64,
+    pub hold_pauses_storage: bool,
+    pub recovery_timelock_secs: i64,
+    pub kyc_verifier: Pubkey,
+    pub kyc_providers_root: [u8; 32],
+    pub enhanced_approver: Pubkey,
+    pub allowed_token_programs: Vec<Pubkey>,
+    pub strict_mode: bool,
+    pub treasury_rent_share_bps: u16,
+    pub risk_check_required: bool,
+    pub max_risk_score: u8,
+    pub risk_score_max_age_secs: i64,
+    pub charity: Pubkey,
+    pub appeal_window_secs: i64,
+    pub appeal_bond_lamports: u64,
+}
+
+// Maximum number of programs the admin can trust to call customer-facing instructions through a CPI
+pub const MAX_TRUSTED_CALLERS: usize = 8;
+
+// Maximum number of marketplace programs the admin can whitelist for initialize_redemption_for
+pub const MAX_MARKETPLACES: usize = 8;
+
+// Maximum number of condition oracles the admin can register
+pub const MAX_CONDITION_ORACLES: usize = 8;
+
+// Maximum number of token programs the admin can allow assets from
+pub const MAX_TOKEN_PROGRAMS: usize = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum CpiPolicy {
+    AllowAll,
+    BlockAll,
+    TrustedCallersOnly,
+}
+
+// Number of statuses that can have an SLA deadline - every status before Delivered
+pub const SLA_STAGES: usize = 4;
+
+// Number of RedemptionStatus variants
+pub const STATUS_COUNT: usize = 5;
+
+impl Config {
+    pub fn set_params(&mut self, params: ConfigParams) -> Result<()> {
+        require!(params.trusted_callers.len() <= MAX_TRUSTED_CALLERS, ErrorCode::TooManyTrustedCallers);
+        require!(params.marketplaces.len() <= MAX_MARKETPLACES, ErrorCode::TooManyMarketplaces);
+        require!(params.condition_oracles.len() <= MAX_CONDITION_ORACLES, ErrorCode::TooManyConditionOracles);
+        require!(params.allowed_token_programs.len() <= MAX_TOKEN_PROGRAMS, ErrorCode::TooManyTokenPrograms);
+        require!(params.insurance_premium_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidInsurancePremium);
+        require!(params.on_time_rebate_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidRebate);
+        require!(params.treasury_rent_share_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidRentShare);
+
+        self.admin = params.admin;
+        self.ops_authority = params.ops_authority;
+        self.cold_vault_authority = params.cold_vault_authority;
+        self.compliance_authority = params.compliance_authority;
+        self.treasury = params.treasury;
+        self.bond_lamports = params.bond_lamports;
+        self.redemption_ttl = params.redemption_ttl;
+        self.ops_encryption_key = params.ops_encryption_key;
+        self.metadata_authority = params.metadata_authority;
+        self.collection = params.collection;
+        self.sla_deadlines = params.sla_deadlines;
+        self.express_sla_deadlines = params.express_sla_deadlines;
+        self.express_fee_usd_cents = params.express_fee_usd_cents;
+        self.sla_waive_fees = params.sla_waive_fees;
+        self.sla_compensation_per_day = params.sla_compensation_per_day;
+        self.sla_allow_cancellation = params.sla_allow_cancellation;
+        self.cpi_policy = params.cpi_policy;
+        self.trusted_callers = params.trusted_callers;
+        self.marketplaces = params.marketplaces;
+        self.condition_oracles = params.condition_oracles;
+        self.fee_mint = params.fee_mint;
+        self.base_fee_usd_cents = params.base_fee_usd_cents;
+        self.storage_fee_per_day_usd_cents = params.storage_fee_per_day_usd_cents;
+        self.insurance_pool = params.insurance_pool;
+        self.insurance_premium_bps = params.insurance_premium_bps;
+        self.on_time_rebate_bps = params.on_time_rebate_bps;
+        self.expedite_collateral_bps = params.expedite_collateral_bps;
+        self.archive_retention_secs = params.archive_retention_secs;
+        self.v1_disabled = params.v1_disabled;
+        self.min_status_slots = params.min_status_slots;
+        self.dead_man_secs = params.dead_man_secs;
+        self.cold_vault_min_dwell_secs = params.cold_vault_min_dwell_secs;
+        self.hold_pauses_storage = params.hold_pauses_storage;
+        self.recovery_timelock_secs = params.recovery_timelock_secs;
+        self.kyc_verifier = params.kyc_verifier;
+        self.kyc_providers_root = params.kyc_providers_root;
+        self.enhanced_approver = params.enhanced_approver;
+        self.allowed_token_programs = params.allowed_token_programs;
+        self.strict_mode = params.strict_mode;
+        self.treasury_rent_share_bps = params.treasury_rent_share_bps;
+        self.risk_check_required = params.risk_check_required;
+        self.max_risk_score = params.max_risk_score;
+        self.risk_score_max_age_secs = params.risk_score_max_age_secs;
+        self.charity = params.charity;
+        self.appeal_window_secs = params.appeal_window_secs;
+        self.appeal_bond_lamports = params.appeal_bond_lamports;
+
+        Ok(())
+    }
+
+    // The cluster's clock, moved forward by however far it's been warped. Every time-window check reads the clock through here rather than
+    // Clock::get, which is why some instructions take the config without otherwise using it - in localnet-mocks builds the tests can then
+    // warp past a window instead of waiting it out
+    pub fn clock(&self) -> Result<Clock> {
+        let mut clock = Clock::get()?;
+        clock.unix_timestamp += self.clock_warp_secs;
+        clock.slot += self.clock_warp_slots;
+        Ok(clock)
+    }
+
+    // SLA deadline for the given status and service tier in seconds, or 0 if it has none
+    pub fn sla_deadline(&self, status: RedemptionStatus, service_tier: ServiceTier) -> i64 {
+        let sla_deadlines = match service_tier {
+            ServiceTier::Standard => &self.sla_deadlines,
+            ServiceTier::Express => &self.express_sla_deadlines,
+        };
+        sla_deadlines.get(status.index()).copied().unwrap_or(0)
+    }
+}
+
+// Permanent record of a finished redemption, kept for the redemption history
+#[account]
+#[derive(InitSpace)]
+pub struct RedemptionReceipt {
+    token_mint: Pubkey,
+    // The customer's wallet
+    customer: Pubkey,
+    outcome: RedemptionOutcome,
+    opened_at: i64,
+    closed_at: i64,
+    valuation_usd_cents: u64,
+    // Hash of the internal ticket or WMS record for the redemption
+    ops_reference: [u8; 32],
+    // The redemption happened off-chain and was imported from BAXUS's database
+    imported: bool,
+    bump: u8,
+    // Edition number of a print edition, or 0 if the asset isn't a print
+    edition_number: u64,
+    // The print's Metaplex master edition account, or the default pubkey if the asset isn't a print
+    master_edition: Pubkey,
+    // Hash of the shipping manifest and customs documents, or all zeroes if the redemption never shipped or was imported
+    shipment_documents_hash: [u8; 32],
+    // The mint's interest-bearing rate state when the token was burned, so accounting can tell the raw amount from the amount wallets
+    // displayed for it, or None if the mint isn't interest-bearing
+    interest_rate: Option<InterestRate>,
+    // What the customer donated to charity out of their rebate, in US cents, and out of their bond, in lamports
+    donated_usd_cents: u64,
+    donated_lamports: u64,
+    // The customer's rating of the redemption from one to five stars, or 0 if they haven't rated it
+    rating: u8,
+    // Hash of the customer's comment, or all zeroes if they didn't leave one
+    comment_hash: [u8; 32],
+    // Unix timestamp the customer rated the redemption at
+    rated_at: i64,
+}
+
+impl RedemptionReceipt {
+    // Record how a redemption ended, from the redemption_info it's closing
+    pub fn record(&mut self, token_mint: Pubkey, redemption_info: &RedemptionInfo, outcome: RedemptionOutcome, closed_at: i64, bump: u8) {
+        self.token_mint = token_mint;
+        self.customer = redemption_info.customer_payment_account;
+        self.outcome = outcome;
+        self.opened_at = redemption_info.created_at;
+        self.closed_at = closed_at;
+        self.valuation_usd_cents = redemption_info.valuation_usd_cents;
+        self.ops_reference = redemption_info.ops_reference;
+        self.shipment_documents_hash = redemption_info.shipment_documents_hash;
+        self.bump = bump;
+    }
+}
+
+// Highest rating a customer can give a redemption
+pub const MAX_RATING: u8 = 5;
+
+// Running tally of the ratings customers have given BAXUS's fulfillment
+#[account]
+#[derive(InitSpace)]
+pub struct FulfillmentReputation {
+    // Number of ratings of each number of stars, starting from one
+    rating_counts: [u64; MAX_RATING as usize],
+    bump: u8,
+}
+
+// The rate state of an interest-bearing Token-2022 mint, which together with a timestamp gives the scale wallets display amounts at
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub struct InterestRate {
+    initialization_timestamp: i64,
+    // Average rate from initialization until the last rate change, in basis points
+    pre_update_average_rate_bps: i16,
+    last_update_timestamp: i64,
+    current_rate_bps: i16,
+}
+
+// How a redemption ended, so the redemption history can be tallied by outcome straight from the receipts. The variants carrying data made
+// receipts bigger, but a receipt is only ever written once, so ones created before they were added never need to grow
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum RedemptionOutcome {
+    // The asset was delivered and its token burned
+    Burned,
+    // The token went back to the customer
+    Returned,
+    Cancelled,
+    Expired,
+    // BAXUS turned the redemption down and sent the token back to the customer
+    Rejected { reason: RejectionReason },
+    // BAXUS bought the asset back from the customer instead of shipping it, for the given amount in US cents
+    BoughtBack { amount_usd_cents: u64 },
+}
+
+// Why BAXUS turned a redemption down
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    FailedKyc,
+    // The destination is somewhere BAXUS can't ship the asset to
+    UnsupportedJurisdiction,
+    // The physical asset didn't match its token, or was damaged in storage
+    AssetCondition,
+    Compliance,
+    Other,
+}
+
+// A legacy redemption as exported from BAXUS's database, passed to import_legacy_redemption
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyRedemptionParams {
+    pub token_mint: Pubkey,
+    pub customer: Pubkey,
+    pub outcome: RedemptionOutcome,
+    pub opened_at: i64,
+    pub closed_at: i64,
+    pub valuation_usd_cents: u64,
+    pub ops_reference: [u8; 32],
+}
+
+// Redemption policy for the assets in a collection, keyed by the collection
+#[account]
+#[derive(InitSpace)]
+pub struct CollectionConfig {
+    collection: Pubkey,
+    // Seconds after opening that a redemption can be expired, or 0 if redemptions never expire
+    redemption_ttl: i64,
+    // Base fee on each redemption's invoice in US cents, in place of the config's
+    base_fee_usd_cents: u64,
+    finalize_mode: FinalizeMode,
+    // Bit set of the attestations ops have to record before an asset from the collection ships
+    required_attestations: u8,
+    bump: u8,
+    // Program CPI'd into on each status transition of the collection's redemptions, or the default pubkey for none
+    hook_program: Pubkey,
+    // Share of each redemption's fees paid to the asset's verified creators when it's burned, in basis points
+    creator_share_bps: u16,
+    // Brand key that has to approve burns of the collection's tokens, or the default pubkey for none
+    brand_authority: Pubkey,
+    // Locked valuation in US cents above which the collection's redemptions need enhanced approval, or 0 for none
+    enhanced_approval_threshold_usd_cents: u64,
+}
+
+// The settings the admin passes to set_collection_config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CollectionConfigParams {
+    pub redemption_ttl: i64,
+    pub base_fee_usd_cents: u64,
+    pub finalize_mode: FinalizeMode,
+    pub required_attestations: u8,
+    pub hook_program: Pubkey,
+    pub creator_share_bps: u16,
+    pub brand_authority: Pubkey,
+    pub enhanced_approval_threshold_usd_cents: u64,
+}
+
+impl CollectionConfig {
+    pub fn set_params(&mut self, params: CollectionConfigParams) -> Result<()> {
+        require!(params.creator_share_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidCreatorShare);
+
+        self.redemption_ttl = params.redemption_ttl;
+        self.base_fee_usd_cents = params.base_fee_usd_cents;
+        self.finalize_mode = params.finalize_mode;
+        self.required_attestations = params.required_attestations;
+        self.hook_program = params.hook_program;
+        self.creator_share_bps = params.creator_share_bps;
+        self.brand_authority = params.brand_authority;
+        self.enhanced_approval_threshold_usd_cents = params.enhanced_approval_threshold_usd_cents;
+
+        Ok(())
+    }
+
+    // Whether a redemption of one of the collection's assets with the given locked valuation needs enhanced approval
+    pub fn requires_enhanced_approval(&self, valuation_usd_cents: u64) -> bool {
+        self.enhanced_approval_threshold_usd_cents > 0 && valuation_usd_cents > self.enhanced_approval_threshold_usd_cents
+    }
+}
+
+// What settle_collateral does with a redemption's collateral
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CollateralOutcome {
+    // KYC cleared, or the redemption is being unwound, so the collateral goes back to the customer
+    Release,
+    // The customer failed KYC after the asset was delivered, so the collateral goes to the treasury
+    Liquidate,
+}
+
+// What happens to the asset token once the physical asset has been delivered
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum FinalizeMode {
+    // The token is burned with burn_asset_token
+    Burn,
+    // The token is kept in the cold vault as a record of the asset, so it can't be burned
+    Vault,
+}
+
+// Maximum number of collections a partner's customers can redeem from
+pub const MAX_PARTNER_COLLECTIONS: usize = 8;
+
+// Settings for a partner brand BAXUS runs redemptions for, keyed by partner ID
+#[account]
+#[derive(InitSpace)]
+pub struct Partner {
+    partner_id: u16,
+    // Signs operational instructions for the partner's redemptions
+    authority: Pubkey,
+    // Partner's share of the fees on its redemptions, in basis points
+    fee_share_bps: u16,
+    // Collections the partner's customers can redeem from, or empty to allow any collection
+    #[max_len(MAX_PARTNER_COLLECTIONS)]
+    allowed_collections: Vec<Pubkey>,
+    // Hash of the partner's branding assets, which front-ends check before showing them
+    branding_reference: [u8; 32],
+    bump: u8,
+    // Running totals in US cents, for accounting - fees paid on the partner's redemptions, the partner's share of them, and how much of that
+    // share the treasury has paid out
+    fees_usd_cents: u64,
+    share_accrued_usd_cents: u64,
+    share_settled_usd_cents: u64,
+}
+
+// The settings the admin passes to set_partner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PartnerParams {
+    pub authority: Pubkey,
+    pub fee_share_bps: u16,
+    pub allowed_collections: Vec<Pubkey>,
+    pub branding_reference: [u8; 32],
+}
+
+impl Partner {
+    pub fn set_params(&mut self, params: PartnerParams) -> Result<()> {
+        require!(params.fee_share_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidFeeShare);
+        require!(params.allowed_collections.len() <= MAX_PARTNER_COLLECTIONS, ErrorCode::TooManyCollections);
+
+        self.authority = params.authority;
+        self.fee_share_bps = params.fee_share_bps;
+        self.allowed_collections = params.allowed_collections;
+        self.branding_reference = params.branding_reference;
+
+        Ok(())
+    }
+
+    pub fn allows_collection(&self, collection: &Pubkey) -> bool {
+        self.allowed_collections.is_empty() || self.allowed_collections.contains(collection)
+    }
+}
+
+// Number of entries the AuditLog holds before it wraps around and starts overwriting the oldest ones
+pub const AUDIT_LOG_CAPACITY: usize = 128;
+
+// The AuditLog is zero-copy because it is too big to deserialize onto the stack on every privileged instruction
+#[account(zero_copy)]
+pub struct AuditLog {
+    // Index that the next entry will be written to
+    head: u64,
+    // Total number of entries ever appended, so readers can tell how many have been overwritten
+    total_entries: u64,
+    entries: [AuditEntry; AUDIT_LOG_CAPACITY],
+}
+
+impl AuditLog {
+    pub fn append(&mut self, actor: Pubkey, action: AuditAction, slot: u64) {
+        let head = self.head as usize;
+        self.entries[head] = AuditEntry {
+            actor,
+            slot,
+            action: action as u8,
+            _padding: [0; 7],
+        };
+        self.head = ((head + 1) % AUDIT_LOG_CAPACITY) as u64;
+        self.total_entries += 1;
+    }
+}
+
+#[zero_copy]
+pub struct AuditEntry {
+    actor: Pubkey,
+    slot: u64,
+    action: u8,
+    _padding: [u8; 7],
+}
+
+// Action codes stored in AuditEntry - only ever append new variants so existing codes keep their meaning
+#[derive(Clone, Copy)]
+#[repr(u8)]
+pub enum AuditAction {
+    Pause = 0,
+    Unpause = 1,
+    ConfigChange = 2,
+    ColdVaultConsolidation = 3,
+    ColdVaultRecall = 4,
+    DutyRateChange = 5,
+    FeeCurrencyChange = 6,
+    PartnerChange = 7,
+    CollectionConfigChange = 8,
+    LegacyImport = 9,
+    ComplianceFreeze = 10,
+    ComplianceUnfreeze = 11,
+    OrphanedEscrowClosed = 12,
+    RejectionAppeal = 13,
+    AppealResolution = 14,
+    CustomerRecovery = 15,
+    RiskScoreChange = 16,
+    ForceReturn = 17,
+}
+
+// RPC log timestamps can't be relied on to order events, so each transition carries its slot and the redemption's sequence number
+#[event]
+pub struct StatusChanged {
+    pub redemption_info: Pubkey,
+    pub redemption_id: u64,
+    // Number of events the redemption has emitted, including this one
+    pub event_sequence: u64,
+    pub status: RedemptionStatus,
+    pub sequence: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RedemptionOpened {
+    pub redemption_info: Pubkey,
+    pub redemption_id: u64,
+    // Number of events the redemption has emitted, including this one
+    pub event_sequence: u64,
+    pub service_tier: ServiceTier,
+    pub marketplace: Pubkey,
+}
+
+#[event]
+pub struct SlaBreached {
+    pub redemption_info: Pubkey,
+    pub redemption_id: u64,
+    // Number of events the redemption has emitted, including this one
+    pub event_sequence: u64,
+    pub status: RedemptionStatus,
+    // Unix timestamp the SLA deadline passed
+    pub breached_at: i64,
+    pub service_tier: ServiceTier,
+}
+
+#[event]
+pub struct ShippingCommitmentUpdated {
+    pub redemption_info: Pubkey,
+    pub redemption_id: u64,
+    // Number of events the redemption has emitted, including this one
+    pub event_sequence: u64,
+    pub previous_commitment: [u8; 32],
+    pub contact_commitment: [u8; 32],
+}
+
+#[event]
+pub struct CustomerRecovered {
+    pub redemption_info: Pubkey,
+    pub redemption_id: u64,
+    // Number of events the redemption has emitted, including this one
+    pub event_sequence: u64,
+    pub previous_customer: Pubkey,
+    pub customer: Pubkey,
+}
+
+// A redemption's current state, re-emitted by resync_redemption. This isn't a state change - indexers should overwrite their record of the
+// redemption with it, and not treat it as a new event
+#[event]
+pub struct RedemptionResynced {
+    pub redemption_info: Pubkey,
+    pub redemption_id: u64,
+    // Sequence number of the last event the redemption emitted, which this doesn't count as
+    pub event_sequence: u64,
+    pub status: RedemptionStatus,
+    pub sequence: u64,
+    // Slot the redemption entered its current status at
+    pub slot: u64,
+    pub customer: Pubkey,
+    pub held: bool,
+    pub frozen_by_compliance: bool,
+    pub shipment_documents_hash: [u8; 32],
+}
+
+// A validation check an asset failed while the config was in permissive mode
+#[event]
+pub struct ValidationWarning {
+    pub mint: Pubkey,
+    // The Anchor error code the check would have failed with in strict mode
+    pub error_code: u32,
+}
+
+#[event]
+pub struct SnapshotPublished {
+    pub epoch: u64,
+    pub root: [u8; 32],
+    pub redemption_count: u32,
+}
+
+#[event]
+pub struct FeedbackSubmitted {
+    pub receipt: Pubkey,
+    pub token_mint: Pubkey,
+    pub rating: u8,
+    pub comment_hash: [u8; 32],
+}
+
+#[event]
+pub struct DonationMade {
+    pub redemption_info: Pubkey,
+    pub redemption_id: u64,
+    // Number of events the redemption has emitted, including this one
+    pub event_sequence: u64,
+    pub charity: Pubkey,
+    pub donated_usd_cents: u64,
+    pub donated_lamports: u64,
+}
+
+#[event]
+pub struct InventoryAttested {
+    pub week: u64,
+    pub bottle_count: u32,
+    pub document_hash: [u8; 32],
+}
+
+#[event]
+pub struct RentToppedUp {
+    pub account: Pubkey,
+    pub contributor: Pubkey,
+    pub lamports: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("The redemption service is paused")]
+    ProgramPaused,
+    #[msg("The redemption has not expired yet")]
+    RedemptionNotExpired,
+    #[msg("The customer did not commit to any contact details when opening the redemption")]
+    NoContactCommitment,
+    #[msg("The encrypted contact details are too long")]
+    ContactCiphertextTooLong,
+    #[msg("The redemption is still open")]
+    RedemptionStillOpen,
+    #[msg("The asset's metadata pointer must point at the mint itself")]
+    UnsupportedMetadataLocation,
+    #[msg("The asset's metadata was not issued by BAXUS")]
+    InvalidMetadataAuthority,
+    #[msg("The asset's metadata is missing a name or symbol")]
+    IncompleteMetadata,
+    #[msg("The asset does not belong to an allowed collection")]
+    InvalidCollection,
+    #[msg("The customer token account has the CPI guard enabled - disable it, or approve the escrow account as delegate for the token first")]
+    CpiGuardEnabled,
+    #[msg("The asset's mint freezes new token accounts by default and its freeze authority is not the BAXUS freeze authority")]
+    EscrowFrozenByDefault,
+    #[msg("The asset's mint is non-transferable, so it can't be moved into escrow")]
+    NonTransferableMint,
+    #[msg("The customer token account does not hold the asset")]
+    AssetNotInCustomerAccount,
+    #[msg("The customer can't cover the anti-spam bond")]
+    InsufficientFundsForBond,
+    #[msg("A redemption for this asset is already open")]
+    RedemptionAlreadyOpen,
+    #[msg("The escrow account for this asset is already in use")]
+    EscrowAccountInUse,
+    #[msg("Redemptions can only move to the status right after their current one")]
+    InvalidStatusTransition,
+    #[msg("The redemption has not overstayed the SLA deadline for its status")]
+    SlaNotBreached,
+    #[msg("The SLA breach for this status has already been flagged")]
+    SlaBreachAlreadyFlagged,
+    #[msg("The redemption can only be cancelled after it breaches its SLA, and only if the SLA policy allows it")]
+    CancellationNotAllowed,
+    #[msg("This instruction can't be called through a CPI by this program")]
+    CpiCallerNotAllowed,
+    #[msg("Too many trusted CPI callers")]
+    TooManyTrustedCallers,
+    #[msg("The invoice for this redemption has to be paid before it can ship")]
+    InvoiceNotPaid,
+    #[msg("Duty rates can't be more than 100%")]
+    InvalidDutyRate,
+    #[msg("The duty rate table is full")]
+    DutyRateTableFull,
+    #[msg("BAXUS doesn't ship to this jurisdiction")]
+    UnsupportedJurisdiction,
+    #[msg("Payments have already been made on this redemption's invoice")]
+    InvoiceAlreadyPaid,
+    #[msg("The asset has already shipped")]
+    AlreadyShipped,
+    #[msg("The amount is too large")]
+    AmountOverflow,
+    #[msg("The invoice has no room for more line items")]
+    InvoiceFull,
+    #[msg("The price feed is not a valid Pyth price account")]
+    InvalidPriceFeed,
+    #[msg("The price feed has not been updated recently enough")]
+    StalePrice,
+    #[msg("The price feed's price is not usable")]
+    InvalidPrice,
+    #[msg("Settling the invoice would cost more than the maximum amount")]
+    SlippageExceeded,
+    #[msg("The storage fees on this redemption have to be settled first")]
+    StorageFeesOutstanding,
+    #[msg("BAXUS doesn't offer insurance, or the accounts for paying the premium are missing")]
+    InsuranceUnavailable,
+    #[msg("Insurance premiums can't be more than 100% of the asset's value")]
+    InvalidInsurancePremium,
+    #[msg("The asset isn't insured")]
+    NotInsured,
+    #[msg("The insurance on this asset has already been claimed")]
+    InsuranceAlreadyClaimed,
+    #[msg("Insurance only covers assets that have shipped and not been delivered")]
+    NotInTransit,
+    #[msg("Partner IDs start at 1")]
+    InvalidPartnerId,
+    #[msg("A partner's fee share can't be more than 100%")]
+    InvalidFeeShare,
+    #[msg("Partners can have at most 8 collections")]
+    TooManyCollections,
+    #[msg("The partner doesn't redeem assets from this collection")]
+    CollectionNotAllowed,
+    #[msg("The redemption was opened through a partner, so the partner's account is required")]
+    PartnerRequired,
+    #[msg("The partner account doesn't match the redemption's partner")]
+    PartnerMismatch,
+    #[msg("Only the BAXUS ops authority or the redemption's partner can do this")]
+    UnauthorizedOperator,
+    #[msg("The collection config account isn't the PDA for the asset's collection")]
+    InvalidCollectionConfig,
+    #[msg("The asset's collection keeps the token in the vault instead of burning it")]
+    WrongFinalizeMode,
+    #[msg("A legacy redemption can't close before it opened")]
+    InvalidLegacyRedemption,
+    #[msg("This version of the instruction has been disabled - use the _v2 version")]
+    InstructionVersionDisabled,
+    #[msg("The redemption hasn't been in its current status for long enough")]
+    TransitionCooldown,
+    #[msg("The on-time rebate can't be more than the fees paid")]
+    InvalidRebate,
+    #[msg("The accounts for paying out the redemption's rebate or creator share are required")]
+    PayoutAccountsRequired,
+    #[msg("Too many marketplaces")]
+    TooManyMarketplaces,
+    #[msg("initialize_redemption_for can only be called through a CPI from a whitelisted marketplace")]
+    MarketplaceNotAllowed,
+    #[msg("The redemption's hook program is required")]
+    HookProgramRequired,
+    #[msg("The bump doesn't derive the redemption's escrow account")]
+    InvalidEscrowBump,
+    #[msg("The token account doesn't hold the asset's mint")]
+    AssetMintMismatch,
+    #[msg("The mint or token account isn't for the config's fee mint")]
+    FeeMintMismatch,
+    #[msg("The fee account isn't owned by the insurance pool")]
+    InsurancePoolMismatch,
+    #[msg("The account isn't the treasury or one of its fee accounts")]
+    TreasuryMismatch,
+    #[msg("The account doesn't belong to the redemption's customer")]
+    CustomerMismatch,
+    #[msg("The customer token account isn't the one the redemption was opened from")]
+    CustomerTokenAccountMismatch,
+    #[msg("The cold vault account isn't the one holding the redemption's token")]
+    ColdVaultMismatch,
+    #[msg("The ProgramData account doesn't belong to this program")]
+    ProgramDataMismatch,
+    #[msg("Only the program's upgrade authority can do this")]
+    NotUpgradeAuthority,
+    #[msg("The redemption is frozen pending a compliance review")]
+    FrozenByCompliance,
+    #[msg("Only the asset's current custodian or an operator can record a handoff")]
+    UnauthorizedCustodian,
+    #[msg("The asset has already been handed to the customer")]
+    CustodyComplete,
+    #[msg("The custody log is full")]
+    CustodyLogFull,
+    #[msg("Too many condition oracles")]
+    TooManyConditionOracles,
+    #[msg("The oracle isn't registered in the config")]
+    UnregisteredOracle,
+    #[msg("A condition report's window can't end before it starts")]
+    InvalidConditionReport,
+    #[msg("The condition log is full")]
+    ConditionLogFull,
+    #[msg("The asset itself can't be attached as a certificate")]
+    InvalidCertificate,
+    #[msg("The certificate is already attached to the redemption")]
+    CertificateAlreadyAttached,
+    #[msg("The certificate isn't attached to the redemption")]
+    CertificateNotAttached,
+    #[msg("Too many certificates attached to the redemption")]
+    TooManyAttachments,
+    #[msg("The accounts for an escrowed certificate are required")]
+    AttachmentAccountsRequired,
+    #[msg("The account isn't the escrow account of an attached certificate")]
+    InvalidAttachmentEscrow,
+    #[msg("Escrowed certificates have to be detached first")]
+    AttachmentsEscrowed,
+    #[msg("Only accounts of the program and escrow token accounts can be topped up")]
+    InvalidTopUpAccount,
+    #[msg("The account is already rent exempt")]
+    AlreadyRentExempt,
+    #[msg("The escrow account still holds tokens")]
+    EscrowNotEmpty,
+    #[msg("The escrow account's redemption is still open")]
+    EscrowNotOrphaned,
+    #[msg("The redemption hasn't been delivered")]
+    RedemptionNotFinished,
+    #[msg("The customer already has the maximum number of redemptions open")]
+    TooManyOpenRedemptions,
+    #[msg("The customer's registry account is required to close this redemption")]
+    CustomerRegistryRequired,
+    #[msg("Creator share must be at most 10000 basis points")]
+    InvalidCreatorShare,
+    #[msg("The asset's metadata account is required to pay its creators")]
+    AssetMetadataRequired,
+    #[msg("The asset's metadata account could not be read")]
+    InvalidAssetMetadata,
+    #[msg("A fee account is required for each of the asset's verified creators")]
+    CreatorAccountsRequired,
+    #[msg("The fee account does not belong to the creator")]
+    InvalidCreatorAccount,
+    #[msg("The signer is not the brand authority for this redemption")]
+    UnauthorizedBrand,
+    #[msg("The brand has to approve burning this redemption's token")]
+    BrandApprovalRequired,
+    #[msg("The burn approval is for an earlier redemption of this asset")]
+    StaleBurnApproval,
+    #[msg("Print editions have to leave a receipt when they're burned")]
+    ReceiptRequired,
+    #[msg("Shipping before KYC clears isn't offered")]
+    ExpediteUnavailable,
+    #[msg("Collateral can only be posted while the redemption is waiting on KYC")]
+    CollateralTooLate,
+    #[msg("Collateral can only be liquidated once the asset has been delivered")]
+    CollateralNotLiquidatable,
+    #[msg("The fee account does not belong to the collateral's recipient")]
+    CollateralDestinationMismatch,
+    #[msg("The redemption is still holding collateral")]
+    CollateralOutstanding,
+    #[msg("The redemption has been rejected - it goes back to the customer with finalize_rejection unless an appeal overturns it")]
+    RedemptionRejected,
+    #[msg("The redemption hasn't been rejected")]
+    RedemptionNotRejected,
+    #[msg("The rejection has already been appealed")]
+    AlreadyAppealed,
+    #[msg("An appeal has to commit to a hash of its evidence")]
+    MissingAppealEvidence,
+    #[msg("The window to appeal the rejection has closed")]
+    AppealWindowClosed,
+    #[msg("The customer can still appeal the rejection")]
+    AppealWindowOpen,
+    #[msg("The redemption doesn't have an appeal waiting to be resolved")]
+    NoPendingAppeal,
+    #[msg("The appeal has to be resolved before the rejection can be finalized")]
+    AppealUnresolved,
+    #[msg("The redemption's appeal is pending and can only be unfrozen by resolving it")]
+    AppealPending,
+    #[msg("The redemption has been archived")]
+    RedemptionArchived,
+    #[msg("The redemption has not been archived")]
+    RedemptionNotArchived,
+    #[msg("The archived redemption's retention period is not up yet")]
+    RetentionNotElapsed,
+    #[msg("A snapshot has already been published this epoch")]
+    SnapshotAlreadyPublished,
+    #[msg("Reserves have to be passed as redemption_info and custody account pairs")]
+    ReserveAccountsRequired,
+    #[msg("Reserves have to be attested in ascending redemption_info order")]
+    ReservesOutOfOrder,
+    #[msg("The account is not a redemption or the token account holding its asset")]
+    InvalidReserve,
+    #[msg("The redemption's asset token is not in custody")]
+    ReserveMissing,
+    #[msg("The customer token account has a delegate other than the escrow account")]
+    ForeignDelegate,
+    #[msg("The customer token account has a close authority other than its owner")]
+    ForeignCloseAuthority,
+    #[msg("The token program that owns an escrowed certificate's mint is missing")]
+    CertificateTokenProgramRequired,
+    #[msg("The escrow account would be closed with tokens still in it")]
+    EscrowNotDrained,
+    #[msg("Customers can't return their own tokens under this config")]
+    SelfReturnDisabled,
+    #[msg("BAXUS has moved the redemption on too recently for the customer to return it themselves")]
+    RedemptionNotAbandoned,
+    #[msg("The customer has put the redemption on hold")]
+    RedemptionOnHold,
+    #[msg("The redemption isn't on hold")]
+    RedemptionNotOnHold,
+    #[msg("The shipping address can't change once the asset has been packed")]
+    AddressChangeTooLate,
+    #[msg("The shipping documents have to be committed before the asset can ship")]
+    ShipmentDocumentsRequired,
+    #[msg("Too many guardians")]
+    TooManyGuardians,
+    #[msg("The guardian threshold has to be between one and the number of guardians")]
+    InvalidGuardianThreshold,
+    #[msg("The same guardian is listed more than once")]
+    DuplicateGuardian,
+    #[msg("Guardians can't be changed while a recovery is pending")]
+    RecoveryPending,
+    #[msg("The signer is not one of the redemption's guardians")]
+    NotAGuardian,
+    #[msg("The recovery request is for a different wallet or token account")]
+    RecoveryMismatch,
+    #[msg("Not enough guardians have approved the recovery")]
+    RecoveryThresholdNotMet,
+    #[msg("The recovery timelock hasn't passed yet")]
+    RecoveryTimelockActive,
+    #[msg("Zero-knowledge KYC proofs aren't accepted")]
+    ZkKycDisabled,
+    #[msg("The customer has proven they passed KYC, so their collateral can't be liquidated")]
+    KycVerified,
+    #[msg("Wrapped SOL can't be redeemed")]
+    NativeMintNotSupported,
+    #[msg("The redemption's valuation requires the enhanced approver to co-sign")]
+    EnhancedApprovalRequired,
+    #[msg("The signer is not the enhanced approver")]
+    NotEnhancedApprover,
+    #[msg("The customer doesn't own the token account the asset is deposited from")]
+    CustomerTokenAccountNotOwned,
+    #[msg("Too many allowed token programs")]
+    TooManyTokenPrograms,
+    #[msg("Assets from this token program can't be redeemed")]
+    TokenProgramNotAllowed,
+    #[msg("Customers can't give their redemption a priority this high")]
+    PriorityTooHigh,
+    #[msg("The priority index is full")]
+    PriorityIndexFull,
+    #[msg("The priority index has to be passed to ship, reject or close a redemption with a priority")]
+    PriorityIndexRequired,
+    #[msg("The redemption still needs its place in the priority index")]
+    PriorityEntryNotStale,
+    #[msg("The program wasn't built with localnet mocks")]
+    MocksDisabled,
+    #[msg("The treasury's rent share can't be more than 100%")]
+    InvalidRentShare,
+    #[msg("The treasury has to be passed to take its share of the rent")]
+    TreasuryRequired,
+    #[msg("The inventory has already been attested this week")]
+    InventoryAlreadyAttested,
+    #[msg("The customer has to pass a risk check before opening a redemption")]
+    RiskScoreRequired,
+    #[msg("The customer's risk score is too high to open a redemption")]
+    RiskScoreTooHigh,
+    #[msg("The customer's risk check is too old")]
+    RiskScoreStale,
+    #[msg("A buy-back payout needs at least one tranche and a non-negative interval")]
+    InvalidTrancheSchedule,
+    #[msg("No new tranche of the buy-back has unlocked yet")]
+    NoTrancheUnlocked,
+    #[msg("The admin hasn't configured a charity to donate to")]
+    DonationsDisabled,
+    #[msg("The donation can't be more than the whole rebate and bond")]
+    InvalidDonationShare,
+    #[msg("The charity's accounts are required to pay out the customer's donation")]
+    DonationAccountsRequired,
+    #[msg("The charity account doesn't belong to the charity in the config")]
+    CharityMismatch,
+    #[msg("A cross-account invariant doesn't hold")]
+    InvariantViolated,
+    #[msg("The accounts for checking the redemption's invariants are required")]
+    InvariantAccountsRequired,
+    #[msg("Moving a WNS asset needs the WNS program and its approve_transfer accounts, for the asset's mint")]
+    WnsAccountsRequired,
+    #[msg("The customer's token account is frozen and its mint's freeze authority is not the BAXUS freeze authority")]
+    AssetFrozen,
+    #[msg("Ratings have to be from one to five stars")]
+    InvalidRating,
+    #[msg("Only redemptions burned on-chain can be rated")]
+    NotRateable,
+    #[msg("The customer has already rated this redemption")]
+    FeedbackAlreadySubmitted,
+    #[msg("The token isn't a whitelisted fee currency")]
+    FeeCurrencyNotWhitelisted,
+    #[msg("The account isn't the config's cold vault authority")]
+    ColdVaultAuthorityMismatch,
+    #[msg("The account isn't the redemption_info the instruction is for")]
+    RedemptionInfoMismatch,
+    #[msg("The redemption's token is in the cold vault")]
+    HeldInColdVault,
+    #[msg("A buy-back can't pay more than the redemption's locked valuation")]
+    BuybackExceedsValuation,
+    #[msg("BAXUS has already moved the redemption past Deposited, so it can't expire")]
+    RedemptionInProgress,
+    #[msg("Tokens can't be moved into the cold vault while customers can self-return them")]
+    DeadManSwitchEnabled,
+    #[msg("The redemption hasn't gone long enough without changing status to be moved into the cold vault")]
+    DwellTooShort,
+    #[msg("The partner's authority has to sign to open a redemption through the partner")]
+    PartnerSignatureRequired,
+    #[msg("The account isn't the config's KYC verifier program")]
+    KycVerifierMismatch,
+    #[msg("The status index page is full - pass another page, or have the admin add one")]
+    StatusIndexPageFull,
+    #[msg("Customers already hold as many places in the priority index as they can")]
+    CustomerPriorityLimit,
+    #[msg("The customer's invoices have to be settled in the fee currency from their profile, or the config's fee mint")]
+    FeeCurrencyMismatch,
+    #[msg("The audit log has to be passed to return a redemption's token")]
+    AuditLogRequired,
+    #[msg("The asset's collection can't be read from its mint, so it can't be checked against the collection policy")]
+    CollectionUnknown,
+    #[msg("The reserve attestation is complete - restart it to attest again")]
+    ReserveAttestationCompleted,
+    #[msg("The reserve attestation doesn't cover every open redemption")]
+    ReservesIncomplete,
+    #[msg("Only open redemptions' reserves can be attested")]
+    ReserveNotOpen,
+}
+
+// Account lists for the redemption lifecycle instructions, for off-chain clients. Each function fills in the Anchor accounts struct for an
+// instruction, deriving every PDA from the asset's mint and the redemption's state, and picking which optional accounts to pass the same way
+// the instruction decides which it needs. Calling to_account_metas on the result gives the accounts in the order the instruction expects them,
+// with the program ID standing in for optional accounts that are left out - so clients that go through these don't break when an
+// instruction gains an optional account. Remaining accounts (escrowed certificates, creator fee accounts) aren't covered
+pub mod client {
+    use super::*;
+    use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+    fn find_address(seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &crate::ID).0
+    }
+
+    pub fn config_address() -> Pubkey {
+        find_address(&[CONFIG_SEED])
+    }
+
+    pub fn audit_log_address() -> Pubkey {
+        find_address(&[AUDIT_LOG_SEED])
+    }
+
+    pub fn redemption_info_address(mint: &Pubkey) -> Pubkey {
+        find_address(&[mint.as_ref(), REDEMPTION_SEED])
+    }
+
+    pub fn escrow_address(mint: &Pubkey) -> Pubkey {
+        find_address(&[mint.as_ref()])
+    }
+
+    pub fn status_index_address(status: RedemptionStatus) -> Pubkey {
+        find_address(&[STATUS_INDEX_SEED, &[status as u8]])
+    }
+
+    pub fn status_index_page_address(status: RedemptionStatus, page: u32) -> Pubkey {
+        find_address(&[STATUS_INDEX_PAGE_SEED, &[status as u8], &page.to_le_bytes()])
+    }
+
+    // The page of its status's index the redemption is listed on
+    fn status_index_page_of(redemption_info: &RedemptionInfo) -> Pubkey {
+        status_index_page_address(redemption_info.status, redemption_info.status_index_page)
+    }
+
+    pub fn customer_registry_address(customer: &Pubkey) -> Pubkey {
+        find_address(&[customer.as_ref(), CUSTOMER_REGISTRY_SEED])
+    }
+
+    pub fn partner_address(partner_id: u16) -> Pubkey {
+        find_address(&[PARTNER_SEED, partner_id.to_le_bytes().as_ref()])
+    }
+
+    // The redemption's partner account, if it was opened through one
+    fn partner_of(redemption_info: &RedemptionInfo) -> Option<Pubkey> {
+        (redemption_info.partner_id != 0).then(|| partner_address(redemption_info.partner_id))
+    }
+
+    // The priority index, if the redemption has a priority
+    fn priority_index_of(redemption_info: &RedemptionInfo) -> Option<Pubkey> {
+        (redemption_info.priority > 0).then(|| find_address(&[PRIORITY_INDEX_SEED]))
+    }
+
+    // The customer's registry, if the redemption is listed in one
+    fn customer_registry_of(redemption_info: &RedemptionInfo) -> Option<Pubkey> {
+        redemption_info.in_customer_registry.then(|| customer_registry_address(&redemption_info.customer_payment_account))
+    }
+
+    // Accounts for initialize_redemption, initialize_redemption_v2 and initialize_redemption_for, with the customer paying. Leaves out the
+    // insurance premium accounts, so the redemption has to be opened uninsured, and the partner and its authority. hook_program is the hook of the asset
+    // collection's CollectionConfig, if it has one, and the customer's RiskScore is only passed if the config requires a risk check. The
+    // customer's CustomerProfile is left out too, since a profile that opts into insurance would need the premium accounts. The redemption goes
+    // on the Deposited index's first page - point status_index_page at a later page of the index once that one is full
+    pub fn initialize_redemption(
+        mint: &Pubkey,
+        customer: &Pubkey,
+        customer_token_account: &Pubkey,
+        config: &Config,
+        collection: &Pubkey,
+        hook_program: Option<Pubkey>,
+        token_program: &Pubkey,
+    ) -> accounts::InitializeRedemption {
+        let escrow = escrow_address(mint);
+        accounts::InitializeRedemption {
+            redemption_info: redemption_info_address(mint),
+            customer_token_account: *customer_token_account,
+            customer_payment_account: *customer,
+            payer: *customer,
+            token_mint_account: *mint,
+            baxus_escrow_account: escrow,
+            freeze_authority: find_address(&[FREEZE_AUTHORITY_SEED]),
+            config: config_address(),
+            appraisal: find_address(&[mint.as_ref(), APPRAISAL_SEED]),
+            risk_score: config.risk_check_required.then(|| find_address(&[customer.as_ref(), RISK_SCORE_SEED])),
+            customer_profile: None,
+            fee_mint: None,
+            payer_fee_account: None,
+            insurance_pool_fee_account: None,
+            fee_token_program: None,
+            partner: None,
+            partner_authority: None,
+            collection_config: find_address(&[COLLECTION_CONFIG_SEED, collection.as_ref()]),
+            hook_program,
+            memo_program: None,
+            status_index: status_index_address(RedemptionStatus::Deposited),
+            status_index_page: status_index_page_address(RedemptionStatus::Deposited, 0),
+            customer_registry: customer_registry_address(customer),
+            escrow_link: find_address(&[escrow.as_ref(), ESCROW_LINK_SEED]),
+            redemption_counter: find_address(&[REDEMPTION_COUNTER_SEED]),
+            instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+            token_program: *token_program,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+            system_program: anchor_lang::system_program::ID,
+        }
+    }
+
+    // Accounts for advance_status, moving the redemption on to next_status and onto page next_status_index_page of its index, which has to
+    // have room for it
+    pub fn advance_status(
+        mint: &Pubkey,
+        redemption_info: &RedemptionInfo,
+        config: &Config,
+        next_status: RedemptionStatus,
+        next_status_index_page: u32,
+        authority: &Pubkey,
+    ) -> accounts::AdvanceStatus {
+        accounts::AdvanceStatus {
+            redemption_info: redemption_info_address(mint),
+            token_mint_account: *mint,
+            current_status_index: status_index_address(redemption_info.status),
+            current_status_index_page: status_index_page_of(redemption_info),
+            next_status_index: status_index_address(next_status),
+            next_status_index_page: status_index_page_address(next_status, next_status_index_page),
+            config: config_address(),
+            partner: partner_of(redemption_info),
+            authority: *authority,
+            priority_index: (redemption_info.priority > 0 && next_status == RedemptionStatus::Shipped).then(|| find_address(&[PRIORITY_INDEX_SEED])),
+            enhanced_approver: (redemption_info.enhanced_approval && next_status == RedemptionStatus::Shipped).then_some(config.enhanced_approver),
+            hook_program: (redemption_info.hook_program != Pubkey::default()).then_some(redemption_info.hook_program),
+            memo_program: None,
+            system_program: anchor_lang::system_program::ID,
+        }
+    }
+
+    // Accounts for reject_redemption
+    pub fn reject_redemption(mint: &Pubkey, redemption_info: &RedemptionInfo, authority: &Pubkey) -> accounts::RejectRedemption {
+        accounts::RejectRedemption {
+            redemption_info: redemption_info_address(mint),
+            token_mint_account: *mint,
+            config: config_address(),
+            partner: partner_of(redemption_info),
+            authority: *authority,
+            priority_index: priority_index_of(redemption_info),
+        }
+    }
+
+    // Accounts for return_asset_token and finalize_rejection
+    pub fn return_asset_token(
+        mint: &Pubkey,
+        redemption_info: &RedemptionInfo,
+        config: &Config,
+        authority: &Pubkey,
+        token_program: &Pubkey,
+    ) -> accounts::ReturnAssetToken {
+        accounts::ReturnAssetToken {
+            redemption_info: redemption_info_address(mint),
+            customer_token_account: redemption_info.customer_token_account,
+            customer_payment_account: redemption_info.customer_payment_account,
+            token_mint_account: *mint,
+            baxus_escrow_account: escrow_address(mint),
+            status_index: status_index_address(redemption_info.status),
+            status_index_page: status_index_page_of(redemption_info),
+            customer_registry: customer_registry_of(redemption_info),
+            config: config_address(),
+            treasury: (config.treasury_rent_share_bps > 0).then_some(config.treasury),
+            partner: partner_of(redemption_info),
+            authority: *authority,
+            priority_index: priority_index_of(redemption_info),
+            audit_log: Some(audit_log_address()),
+            receipt: find_address(&[RECEIPT_SEED, mint.as_ref(), redemption_info.created_at.to_le_bytes().as_ref()]),
+            token_program: *token_program,
+            system_program: anchor_lang::system_program::ID,
+        }
+    }
+
+    // Accounts for burn_asset_token. The treasury payout accounts are only filled in if the redemption earns a
+    // rebate or shares fees with creators, in which case fee_token_program is the fee mint's token program, and the charity's accounts only
+    // if the customer donates
+    pub fn burn_asset_token(
+        mint: &Pubkey,
+        redemption_info: &RedemptionInfo,
+        config: &Config,
+        authority: &Pubkey,
+        token_program: &Pubkey,
+        fee_token_program: &Pubkey,
+    ) -> accounts::BurnAssetToken {
+        let redemption_info_key = redemption_info_address(mint);
+        let pays_out = (redemption_info.on_time() && config.on_time_rebate_bps > 0) || redemption_info.creator_share_bps > 0;
+        let payout = |address: Pubkey| pays_out.then_some(address);
+        let metadata_seeds: &[&[u8]] = &[TOKEN_METADATA_SEED, token_metadata_program::ID.as_ref(), mint.as_ref()];
+        let brand_approves = redemption_info.brand_authority != Pubkey::default();
+        let donates = config.charity != Pubkey::default() && redemption_info.donation_bps > 0;
+
+        accounts::BurnAssetToken {
+            redemption_info: redemption_info_key,
+            customer_token_account: redemption_info.customer_token_account,
+            customer_payment_account: redemption_info.customer_payment_account,
+            token_mint_account: *mint,
+            baxus_escrow_account: escrow_address(mint),
+            status_index: status_index_address(redemption_info.status),
+            status_index_page: status_index_page_of(redemption_info),
+            customer_registry: customer_registry_of(redemption_info),
+            config: config_address(),
+            treasury: (config.treasury_rent_share_bps > 0).then_some(config.treasury),
+            partner: partner_of(redemption_info),
+            authority: *authority,
+            enhanced_approver: redemption_info.enhanced_approval.then_some(config.enhanced_approver),
+            token_program: *token_program,
+            certificate_token_program: None,
+            invoice: payout(find_address(&[redemption_info_key.as_ref(), INVOICE_SEED])),
+            fee_mint: payout(config.fee_mint),
+            treasury_fee_account: payout(get_associated_token_address_with_program_id(&config.treasury, &config.fee_mint, fee_token_program)),
+            customer_fee_account: payout(get_associated_token_address_with_program_id(
+                &redemption_info.customer_payment_account,
+                &config.fee_mint,
+                fee_token_program,
+            )),
+            rebate_authority: payout(find_address(&[REBATE_AUTHORITY_SEED])),
+            fee_token_program: payout(*fee_token_program),
+            accounting: Some(find_address(&[ACCOUNTING_SEED])),
+            charity: donates.then_some(config.charity),
+            charity_fee_account: (donates && pays_out)
+                .then(|| get_associated_token_address_with_program_id(&config.charity, &config.fee_mint, fee_token_program)),
+            asset_metadata: (redemption_info.creator_share_bps > 0)
+                .then(|| Pubkey::find_program_address(metadata_seeds, &token_metadata_program::ID).0),
+            burn_approval: brand_approves.then(|| find_address(&[redemption_info_key.as_ref(), BURN_APPROVAL_SEED])),
+            brand_authority: brand_approves.then_some(redemption_info.brand_authority),
+            edition: Pubkey::find_program_address(&[metadata_seeds, &[TOKEN_METADATA_EDITION_SEED]].concat(), &token_metadata_program::ID).0,
+            receipt: find_address(&[RECEIPT_SEED, mint.as_ref(), redemption_info.created_at.to_le_bytes().as_ref()]),
+            system_program: anchor_lang::system_program::ID,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versioned_redemption_info_decodes_the_original_layout() {
+        // The original layout was just the two customer accounts and the two bumps, allocated at twice the space it needed
+        let customer_token_account = Pubkey::new_unique();
+        let customer_payment_account = Pubkey::new_unique();
+        let mut data = RedemptionInfo::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(customer_token_account.as_ref());
+        data.extend_from_slice(customer_payment_account.as_ref());
+        data.extend_from_slice(&[254, 253]);
+        data.resize(8 + 2 * (32 + 32 + 1 + 1), 0);
+        assert!(data.len() < 8 + RedemptionInfo::INIT_SPACE);
+
+        let redemption = VersionedRedemptionInfo::try_deserialize_unchecked(&mut data.as_slice()).unwrap();
+        assert_eq!(redemption.customer_token_account, customer_token_account);
+        assert_eq!(redemption.customer_payment_account, customer_payment_account);
+        assert!(redemption.escrow_bump == EscrowBump { bump: 254 });
+        assert_eq!(redemption.redemption_bump, 253);
+
+        // Everything added since decodes to its default
+        assert!(redemption.status == RedemptionStatus::Deposited);
+        assert_eq!(redemption.created_at, 0);
+        assert_eq!(redemption.bond_lamports, 0);
+        assert_eq!(redemption.cold_vault_account, Pubkey::default());
+        assert!(redemption.rejection.is_none());
+        assert!(redemption.appeal_resolved.is_none());
+        assert_eq!(redemption.appeal_bond_lamports, 0);
+
+        // The checked decode accepts it too, and rejects any other account type
+        assert!(VersionedRedemptionInfo::try_deserialize(&mut data.as_slice()).is_ok());
+        data[0] ^= 1;
+        assert!(VersionedRedemptionInfo::try_deserialize(&mut data.as_slice()).is_err());
+    }
+
+    // Accounting whose current month is the given one, with its fees so far
+    fn accounting_in(period: u32, fees_usd_cents: u64) -> Accounting {
+        let mut accounting = Accounting { bump: 0, current_period: period, periods: [PeriodTotals::default(); ACCOUNTING_PERIODS] };
+        accounting.periods[period as usize % ACCOUNTING_PERIODS] = PeriodTotals { period, fees_usd_cents, ..Default::default() };
+        accounting
+    }
+
+    #[test]
+    fn month_index_changes_at_month_boundaries() {
+        // 1970-01-01 and the second before it
+        assert_eq!(month_index(0), 1970 * 12);
+        assert_eq!(month_index(-1), 1969 * 12 + 11);
+        // 2023-12-31 23:59:59 and 2024-01-01
+        assert_eq!(month_index(1_704_067_199), 2023 * 12 + 11);
+        assert_eq!(month_index(1_704_067_200), 2024 * 12);
+    }
+
+    #[test]
+    fn month_index_handles_leap_years() {
+        // 2024 is a leap year, so February runs to the 29th
+        assert_eq!(month_index(1_709_251_199), 2024 * 12 + 1);
+        assert_eq!(month_index(1_709_251_200), 2024 * 12 + 2);
+        // 2000 is a leap year too, being divisible by 400
+        assert_eq!(month_index(951_782_400), 2000 * 12 + 1);
+        assert_eq!(month_index(951_868_800), 2000 * 12 + 2);
+        // 2100 isn't, so 2100-02-28 23:59:59 is followed by March
+        assert_eq!(month_index(4_107_542_399), 2100 * 12 + 1);
+        assert_eq!(month_index(4_107_542_400), 2100 * 12 + 2);
+    }
+
+    #[test]
+    fn roll_to_starts_the_next_month_fresh() {
+        let january = 2024 * 12;
+        let mut accounting = accounting_in(january, 100);
+
+        // 2024-02-15
+        accounting.roll_to(1_707_955_200);
+        assert_eq!(accounting.current_period, january + 1);
+        let february = accounting.periods[(january + 1) as usize % ACCOUNTING_PERIODS];
+        assert_eq!((february.period, february.fees_usd_cents), (january + 1, 0));
+        assert_eq!(accounting.periods[january as usize % ACCOUNTING_PERIODS].fees_usd_cents, 100);
+
+        // Going back to January leaves February current
+        accounting.roll_to(1_705_276_800);
+        assert_eq!(accounting.current_period, january + 1);
+    }
+
+    #[test]
+    fn roll_to_starts_skipped_months_fresh() {
+        let january = 2024 * 12;
+        let mut accounting = accounting_in(january, 100);
+        for period in accounting.periods.iter_mut().filter(|period| period.period != january) {
+            period.fees_usd_cents = 50;
+        }
+
+        // 2024-06-15 clears February to June, but leaves January and last year's July to December
+        accounting.roll_to(1_718_409_600);
+        assert_eq!(accounting.current_period, january + 5);
+        for period in january + 1..=january + 5 {
+            let totals = accounting.periods[period as usize % ACCOUNTING_PERIODS];
+            assert_eq!((totals.period, totals.fees_usd_cents), (period, 0));
+        }
+        assert_eq!(accounting.periods[january as usize % ACCOUNTING_PERIODS].fees_usd_cents, 100);
+        assert_eq!(accounting.periods[(january - 1) as usize % ACCOUNTING_PERIODS].fees_usd_cents, 50);
+    }
+
+    #[test]
+    fn roll_to_clears_every_month_after_a_year_or_more() {
+        let mut accounting = accounting_in(2024 * 12, 100);
+
+        // 2025-06-15 is 17 months on, so the ring holds July 2024 to June 2025, all fresh
+        accounting.roll_to(1_749_945_600);
+        let june = 2025 * 12 + 5;
+        assert_eq!(accounting.current_period, june);
+        for period in june - 11..=june {
+            let totals = accounting.periods[period as usize % ACCOUNTING_PERIODS];
+            assert_eq!((totals.period, totals.fees_usd_cents), (period, 0));
+        }
+    }
+
+    // The redemptions in a priority index, most urgent first
+    fn priority_order(index: &PriorityIndex) -> Vec<Pubkey> {
+        index.entries.iter().map(|entry| entry.redemption).collect()
+    }
+
+    #[test]
+    fn priority_index_keeps_equal_priorities_in_insertion_order() {
+        let mut index = PriorityIndex { entries: vec![], bump: 0 };
+        let (first, second, urgent) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        index.insert(first, 2, false).unwrap();
+        index.insert(second, 2, false).unwrap();
+        index.insert(urgent, 3, true).unwrap();
+
+        assert_eq!(priority_order(&index), vec![urgent, first, second]);
+    }
+
+    #[test]
+    fn full_priority_index_evicts_the_least_urgent_customer_priorities_first() {
+        let mut index = PriorityIndex { entries: vec![], bump: 0 };
+        let (least_urgent, earlier, later) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        index.insert(least_urgent, 1, true).unwrap();
+        index.insert(earlier, 5, true).unwrap();
+        index.insert(later, 5, true).unwrap();
+        while index.entries.len() < MAX_PRIORITY_ENTRIES {
+            index.insert(Pubkey::new_unique(), 3, false).unwrap();
+        }
+
+        // An operator's priority takes the place of the least urgent customer priority, then the later of two equally urgent ones
+        let operator = Pubkey::new_unique();
+        index.insert(operator, 2, false).unwrap();
+        assert_eq!(index.entries.len(), MAX_PRIORITY_ENTRIES);
+        assert!(!priority_order(&index).contains(&least_urgent));
+        assert_eq!(priority_order(&index)[MAX_PRIORITY_ENTRIES - 1], operator);
+
+        index.insert(Pubkey::new_unique(), 2, false).unwrap();
+        assert!(!priority_order(&index).contains(&later));
+        assert_eq!(priority_order(&index)[0], earlier);
+
+        // Customers can't evict anyone
+        assert_eq!(index.insert(Pubkey::new_unique(), 9, true).unwrap_err(), error!(ErrorCode::PriorityIndexFull));
+
+        // Once only operators' priorities are left, nobody can be evicted
+        index.insert(Pubkey::new_unique(), 2, false).unwrap();
+        assert!(index.entries.iter().all(|entry| !entry.customer_set));
+        assert_eq!(index.insert(Pubkey::new_unique(), 9, false).unwrap_err(), error!(ErrorCode::PriorityIndexFull));
+        assert_eq!(index.entries.len(), MAX_PRIORITY_ENTRIES);
+    }
+
+    fn buyback_payout(amount: u64, tranche_count: u8, tranche_interval_secs: i64) -> BuybackPayout {
+        BuybackPayout {
+            token_mint: Pubkey::default(),
+            opened_at: 0,
+            customer: Pubkey::default(),
+            funder: Pubkey::default(),
+            amount_usd_cents: amount,
+            amount,
+            tranche_count,
+            tranches_claimed: 0,
+            tranche_interval_secs,
+            started_at: 1_000,
+            escrow_bump: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn tranches_unlock_one_interval_apart() {
+        let payout = buyback_payout(100, 3, 10);
+        assert_eq!(payout.tranches_unlocked(0), 1);
+        assert_eq!(payout.tranches_unlocked(1_000), 1);
+        assert_eq!(payout.tranches_unlocked(1_009), 1);
+        assert_eq!(payout.tranches_unlocked(1_010), 2);
+        assert_eq!(payout.tranches_unlocked(1_020), 3);
+        assert_eq!(payout.tranches_unlocked(i64::MAX), 3);
+
+        // Without an interval, everything unlocks at once
+        assert_eq!(buyback_payout(100, 3, 0).tranches_unlocked(1_000), 3);
+    }
+
+    #[test]
+    fn last_tranche_pays_the_remainder() {
+        let payout = buyback_payout(100, 3, 10);
+        assert_eq!(payout.amount_through(0), 0);
+        assert_eq!(payout.amount_through(1), 33);
+        assert_eq!(payout.amount_through(2), 66);
+        assert_eq!(payout.amount_through(3), 100);
+
+        // Amounts too large to multiply by the tranche count in a u64 still add up to the whole amount
+        let payout = buyback_payout(u64::MAX, 7, 10);
+        let tranches: Vec<u64> = (1..=7).map(|tranche| payout.amount_through(tranche) - payout.amount_through(tranche - 1)).collect();
+        assert!(tranches[..6].iter().all(|&tranche| tranche == u64::MAX / 7));
+        assert_eq!(tranches.iter().sum::<u64>(), u64::MAX);
+    }
 }
@@ -1,74 +1,639 @@
-import * as anchor from '@project-serum/anchor';
-import { Program } from '@project-serum/anchor';
-import { TOKEN_PROGRAM_ID, Token } from '@solana/spl-token';
-import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram, Connection } from '@solana/web3.js';
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import {
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  AccountState,
+  AuthorityType,
+  ExtensionType,
+  approve,
+  createAccount,
+  createAssociatedTokenAccount,
+  createEnableCpiGuardInstruction,
+  createInitializeAccountInstruction,
+  createInitializeDefaultAccountStateInstruction,
+  createInitializeMetadataPointerInstruction,
+  createInitializeMintInstruction,
+  createInitializeNonTransferableMintInstruction,
+  createMint,
+  freezeAccount,
+  getAccount,
+  getAccountLen,
+  getAssociatedTokenAddress,
+  getMint,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  setAuthority,
+  thawAccount,
+  tokenMetadataInitializeWithRentTransfer,
+} from '@solana/spl-token';
+import { AccountMeta, Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram, Connection, Transaction, TransactionInstruction } from '@solana/web3.js';
 import { BaxusRedemptionService } from '../target/types/baxus_redemption_service';
 import * as assert from 'assert'
-import { NodeWallet } from '@project-serum/anchor/dist/cjs/provider';
-import { findProgramAddressSync } from '@project-serum/anchor/dist/cjs/utils/pubkey';
+import * as crypto from 'crypto'
+import NodeWallet from '@coral-xyz/anchor/dist/cjs/nodewallet';
 
 describe('baxus-redemption-service', () => {
 
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.Provider.env());
+  anchor.setProvider(anchor.AnchorProvider.env());
 
   const program = anchor.workspace.BaxusRedemptionService as Program<BaxusRedemptionService>;
 
+  // The test wallet's keypair, which pays for the mints and token accounts the tests create
+  const payer = (program.provider.wallet as NodeWallet).payer;
+
+  const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+  const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+  // The mint's Metaplex edition PDA, which burn_asset_token always takes - for the test assets, which aren't Metaplex assets, it's empty
+  const editionAddress = (mint: PublicKey) => PublicKey.findProgramAddressSync(
+    [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer(), Buffer.from("edition")],
+    TOKEN_METADATA_PROGRAM_ID,
+  )[0];
+
+  let testConfigAccount: PublicKey = null;
+  let testFreezeAuthority: PublicKey = null;
+  let testAuditLogAccount: PublicKey = null;
+
+  // Status index accounts, and the first page of each, in RedemptionStatus order
+  const testStatusIndexAccounts: PublicKey[] = [];
+  const testStatusIndexPageAccounts: PublicKey[] = [];
+
   let testCustomerTokenAccount: PublicKey = null;
 
-  let testTokenMintAccount: Token = null;
+  let testTokenMintAccount: PublicKey = null;
 
   let testRedemptionInfoAccount: PublicKey = null;
   let testRedemptionBump: number = null;
 
   let testBaxusEscrowAccount: PublicKey = null;
+
+  let testAccountingAccount: PublicKey = null;
+
+  let testCustomerRegistryAccount: PublicKey = null;
+
+  let testEscrowLinkAccount: PublicKey = null;
+
+  let testRedemptionCounterAccount: PublicKey = null;
+
+  let testAppraisalAccount: PublicKey = null;
+
+  // Receipt left behind when the test asset is burned
+  let testReceiptAccount: PublicKey = null;
+
+  let testPartnerAccount: PublicKey = null;
+
+  // CollectionConfig PDA for the test asset's collection - legacy SPL Token mints have no collection, so this is the default pubkey's
+  let testCollectionConfigAccount: PublicKey = null;
+  let testPartnerAuthority: PublicKey = null;
+
+  // USD stablecoin that duties and fees are paid in
+  let testFeeMint: PublicKey = null;
+  // ConfigParams the config was initialized with, so tests can change one setting with update_config and put it back
+  let testConfigParams = null;
+  // Wallet customers can donate part of their rebate and bond to
+  const testCharity = Keypair.generate().publicKey;
+  let testDutyRatesAccount: PublicKey = null;
   let testEscrowBump: number = null;
 
+  // Salted hash of the test customer's contact details
+  const testContactCommitment = Array.from(crypto.createHash('sha256').update('test-salt:customer@example.com').digest());
+
+  it('Basic test for initialize_config():', async () => {
+
+    [testConfigAccount] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("config")], program.programId);
+    [testAuditLogAccount] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("audit_log")], program.programId);
+    [testFreezeAuthority] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("freeze_authority")], program.programId);
+
+    [testDutyRatesAccount] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("duty_rates")], program.programId);
+
+    testFeeMint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      6);
+
+    // The program's ProgramData account holds its upgrade authority, which is this test's wallet when deployed by anchor test
+    const [programData] = await anchor.web3.PublicKey.findProgramAddress([program.programId.toBuffer()], BPF_LOADER_UPGRADEABLE_PROGRAM_ID);
+
+    testConfigParams = {
+      admin: program.provider.wallet.publicKey,
+      opsAuthority: program.provider.wallet.publicKey,
+      coldVaultAuthority: program.provider.wallet.publicKey,
+      complianceAuthority: program.provider.wallet.publicKey,
+      treasury: program.provider.wallet.publicKey,
+      bondLamports: new anchor.BN(LAMPORTS_PER_SOL / 100),
+      redemptionTtl: new anchor.BN(0),
+      opsEncryptionKey: Array(32).fill(1),
+      metadataAuthority: PublicKey.default,
+      collection: PublicKey.default,
+      // Redemptions breach their SLA after one second in Deposited, so flag_sla_breach can be tested
+      slaDeadlines: [new anchor.BN(1), new anchor.BN(0), new anchor.BN(0), new anchor.BN(0)],
+      expressSlaDeadlines: [new anchor.BN(1), new anchor.BN(0), new anchor.BN(0), new anchor.BN(0)],
+      expressFeeUsdCents: new anchor.BN(2000),
+      slaWaiveFees: true,
+      slaCompensationPerDay: new anchor.BN(LAMPORTS_PER_SOL / 1000),
+      slaAllowCancellation: true,
+      cpiPolicy: { allowAll: {} },
+      trustedCallers: [],
+      marketplaces: [],
+      conditionOracles: [],
+      feeMint: testFeeMint,
+      baseFeeUsdCents: new anchor.BN(5000),
+      storageFeePerDayUsdCents: new anchor.BN(100),
+      insurancePool: program.provider.wallet.publicKey,
+      insurancePremiumBps: 100,
+      onTimeRebateBps: 500,
+      expediteCollateralBps: 1000,
+      archiveRetentionSecs: new anchor.BN(0),
+      v1Disabled: false,
+      minStatusSlots: Array(5).fill(new anchor.BN(0)),
+      deadManSecs: new anchor.BN(90 * 24 * 60 * 60),
+      coldVaultMinDwellSecs: new anchor.BN(24 * 60 * 60),
+      holdPausesStorage: true,
+      recoveryTimelockSecs: new anchor.BN(7 * 24 * 60 * 60),
+      kycVerifier: PublicKey.default,
+      kycProvidersRoot: Array(32).fill(0),
+      enhancedApprover: PublicKey.default,
+      allowedTokenPrograms: [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID],
+      strictMode: true,
+      treasuryRentShareBps: 0,
+      riskCheckRequired: false,
+      maxRiskScore: 0,
+      riskScoreMaxAgeSecs: new anchor.BN(0),
+      charity: testCharity,
+      appealWindowSecs: new anchor.BN(7 * 24 * 60 * 60),
+      appealBondLamports: new anchor.BN(LAMPORTS_PER_SOL / 100),
+    };
+    const tx = await program.rpc.initializeConfig(testConfigParams, {
+      accounts: {
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        upgradeAuthority: program.provider.wallet.publicKey,
+        program: program.programId,
+        programData: programData,
+        systemProgram: SystemProgram.programId,
+      },
+      signers: []
+    });
+
+    const config = await program.account.config.fetch(testConfigAccount);
+    assert.ok(config.admin.equals(program.provider.wallet.publicKey));
+    assert.equal(false, config.paused);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for set_paused():', async () => {
+
+    await program.rpc.setPaused(true, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+    assert.equal(true, (await program.account.config.fetch(testConfigAccount)).paused);
+
+    const tx = await program.rpc.setPaused(false, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+    assert.equal(false, (await program.account.config.fetch(testConfigAccount)).paused);
+
+    // Both actions should have been recorded in the audit log
+    assert.equal(2, (await program.account.auditLog.fetch(testAuditLogAccount)).totalEntries.toNumber());
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for update_config():', async () => {
+
+    const accounts = { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey };
+
+    // Only the admin can change the config
+    const stranger = Keypair.generate();
+    let updatedByStranger = true;
+    try {
+      await program.rpc.updateConfig({ ...testConfigParams, baseFeeUsdCents: new anchor.BN(6000) }, {
+        accounts: { ...accounts, admin: stranger.publicKey },
+        signers: [stranger],
+      });
+    } catch (err) {
+      updatedByStranger = false;
+    }
+    assert.ok(!updatedByStranger);
+
+    // Basis points can't go over 100%
+    let updatedInvalid = true;
+    try {
+      await program.rpc.updateConfig({ ...testConfigParams, insurancePremiumBps: 10001 }, { accounts });
+    } catch (err) {
+      updatedInvalid = false;
+    }
+    assert.ok(!updatedInvalid);
+
+    const tx = await program.rpc.updateConfig({ ...testConfigParams, baseFeeUsdCents: new anchor.BN(6000) }, { accounts });
+    assert.equal(6000, (await program.account.config.fetch(testConfigAccount)).baseFeeUsdCents.toNumber());
+
+    // The change was appended to the audit log after the two set_paused entries, and the refused updates left nothing behind
+    const auditLog = await program.account.auditLog.fetch(testAuditLogAccount);
+    assert.equal(3, auditLog.totalEntries.toNumber());
+    assert.equal(3, auditLog.head.toNumber());
+    assert.equal(2, auditLog.entries[2].action);
+    assert.ok(auditLog.entries[2].actor.equals(program.provider.wallet.publicKey));
+
+    await program.rpc.updateConfig(testConfigParams, { accounts });
+    assert.equal(5000, (await program.account.config.fetch(testConfigAccount)).baseFeeUsdCents.toNumber());
+    assert.equal(4, (await program.account.auditLog.fetch(testAuditLogAccount)).totalEntries.toNumber());
+
+    console.log("Your transaction signature", tx);
+  });
+
+  // warp_clock and set_mock_price only exist in localnet-mocks builds, so they aren't in the IDL and have to be encoded by hand
+  const mockInstruction = (name: string, args: Buffer, keys: AccountMeta[]) => new TransactionInstruction({
+    programId: program.programId,
+    keys: keys,
+    data: Buffer.concat([crypto.createHash('sha256').update(`global:${name}`).digest().slice(0, 8), args]),
+  });
+
+  const warpClockInstruction = (secs: number) => {
+    const args = Buffer.alloc(8);
+    args.writeUInt32LE(secs, 0);
+    return mockInstruction('warp_clock', args, [
+      { pubkey: testConfigAccount, isSigner: false, isWritable: true },
+      { pubkey: program.provider.wallet.publicKey, isSigner: true, isWritable: false },
+    ]);
+  };
+
+  // Move the program's clock forward, rather than waiting for the validator's to catch up with a time window
+  const warpClock = (secs: number) => program.provider.sendAndConfirm(new Transaction().add(warpClockInstruction(secs)));
+
+  it('Basic test for warp_clock():', async () => {
+
+    // The tests run against a localnet-mocks build (yarn test), where the admin can warp the clock
+    const tx = await warpClock(1);
+    assert.equal(1, (await program.account.config.fetch(testConfigAccount)).clockWarpSecs.toNumber());
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for rollover_period():', async () => {
+
+    [testAccountingAccount] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("accounting")], program.programId);
+
+    const tx = await program.rpc.rolloverPeriod({
+      accounts: {
+        accounting: testAccountingAccount,
+        config: testConfigAccount,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    // The current period is this month, counted in months since January of year 0
+    const now = new Date();
+    assert.equal(now.getUTCFullYear() * 12 + now.getUTCMonth(), (await program.account.accounting.fetch(testAccountingAccount)).currentPeriod);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for publish_snapshot():', async () => {
+
+    const [snapshot] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("snapshot")], program.programId);
+    const root = Array(32).fill(7);
+
+    const tx = await program.rpc.publishSnapshot(root, 0, {
+      accounts: {
+        snapshot: snapshot,
+        config: testConfigAccount,
+        opsAuthority: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    assert.deepEqual(root, (await program.account.redemptionSnapshot.fetch(snapshot)).root);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for set_partner():', async () => {
+
+    const partnerId = 1;
+    [testPartnerAccount] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("partner"), new anchor.BN(partnerId).toArrayLike(Buffer, "le", 2)],
+      program.programId,
+    );
+    testPartnerAuthority = Keypair.generate().publicKey;
+
+    const tx = await program.rpc.setPartner(partnerId, {
+      authority: testPartnerAuthority,
+      feeShareBps: 2000,
+      allowedCollections: [],
+      brandingReference: Array(32).fill(2),
+    }, {
+      accounts: {
+        partner: testPartnerAccount,
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        admin: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    const partnerAccount = await program.account.partner.fetch(testPartnerAccount);
+    assert.equal(partnerId, partnerAccount.partnerId);
+    assert.ok(partnerAccount.authority.equals(testPartnerAuthority));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for set_collection_config():', async () => {
+
+    // The test asset's collection has no CollectionConfig, so its redemptions use the config's defaults
+    [testCollectionConfigAccount] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("collection_config"), PublicKey.default.toBuffer()],
+      program.programId,
+    );
+
+    const collection = Keypair.generate().publicKey;
+    const [collectionConfig] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("collection_config"), collection.toBuffer()],
+      program.programId,
+    );
+
+    const tx = await program.rpc.setCollectionConfig(collection, {
+      redemptionTtl: new anchor.BN(30 * 24 * 60 * 60),
+      baseFeeUsdCents: new anchor.BN(10000),
+      finalizeMode: { vault: {} },
+      requiredAttestations: 1,
+      hookProgram: PublicKey.default,
+      creatorShareBps: 1000,
+      brandAuthority: PublicKey.default,
+      enhancedApprovalThresholdUsdCents: new anchor.BN(0),
+    }, {
+      accounts: {
+        collectionConfig: collectionConfig,
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        admin: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    assert.ok((await program.account.collectionConfig.fetch(collectionConfig)).finalizeMode.vault);
+    assert.equal(1000, (await program.account.collectionConfig.fetch(collectionConfig)).creatorShareBps);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for import_legacy_redemption():', async () => {
+
+    const tokenMint = Keypair.generate().publicKey;
+    const openedAt = new anchor.BN(1600000000);
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), tokenMint.toBuffer(), openedAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+
+    const tx = await program.rpc.importLegacyRedemption({
+      tokenMint: tokenMint,
+      customer: Keypair.generate().publicKey,
+      outcome: { burned: {} },
+      openedAt: openedAt,
+      closedAt: new anchor.BN(1600500000),
+      valuationUsdCents: new anchor.BN(120000),
+      opsReference: Array(32).fill(3),
+    }, {
+      accounts: {
+        receipt: receipt,
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        admin: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    assert.ok((await program.account.redemptionReceipt.fetch(receipt)).imported);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for import_legacy_redemption() with a bought-back outcome:', async () => {
+
+    const tokenMint = Keypair.generate().publicKey;
+    const openedAt = new anchor.BN(1600000000);
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), tokenMint.toBuffer(), openedAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+
+    const tx = await program.rpc.importLegacyRedemption({
+      tokenMint: tokenMint,
+      customer: Keypair.generate().publicKey,
+      // BAXUS bought this one back at its valuation instead of shipping it
+      outcome: { boughtBack: { amountUsdCents: new anchor.BN(120000) } },
+      openedAt: openedAt,
+      closedAt: new anchor.BN(1600500000),
+      valuationUsdCents: new anchor.BN(120000),
+      opsReference: Array(32).fill(3),
+    }, {
+      accounts: {
+        receipt: receipt,
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        admin: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    assert.equal(120000, (await program.account.redemptionReceipt.fetch(receipt)).outcome.boughtBack.amountUsdCents.toNumber());
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for initialize_status_index():', async () => {
+
+    const statuses = [{ deposited: {} }, { kycApproved: {} }, { packed: {} }, { shipped: {} }, { delivered: {} }];
+    for (const [i, status] of statuses.entries()) {
+      const [statusIndex] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("status_index"), Buffer.from([i])], program.programId);
+      testStatusIndexAccounts.push(statusIndex);
+
+      await program.rpc.initializeStatusIndex(status, {
+        accounts: {
+          statusIndex: statusIndex,
+          config: testConfigAccount,
+          admin: program.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        },
+      });
+
+      // Each index needs at least one page before redemptions can enter its status
+      const [statusIndexPage] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from("status_index_page"), Buffer.from([i]), new anchor.BN(0).toArrayLike(Buffer, "le", 4)],
+        program.programId,
+      );
+      testStatusIndexPageAccounts.push(statusIndexPage);
+      await program.rpc.initializeStatusIndexPage({
+        accounts: {
+          statusIndex: statusIndex,
+          statusIndexPage: statusIndexPage,
+          config: testConfigAccount,
+          admin: program.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        },
+      });
+    }
+
+    // Every index starts out empty, with the one page
+    assert.equal(0, (await program.account.statusIndex.fetch(testStatusIndexAccounts[0])).count.toNumber());
+    assert.equal(1, (await program.account.statusIndex.fetch(testStatusIndexAccounts[0])).pageCount);
+    assert.equal(0, (await program.account.statusIndexPage.fetch(testStatusIndexPageAccounts[0])).redemptions.length);
+  });
+
   it('Basic test for initialize_redemption():', async () => { 
 
     // Create a Token Mint Account 
-    testTokenMintAccount = await Token.createMint(
+    testTokenMintAccount = await createMint(
       program.provider.connection, 
-      (program.provider.wallet as NodeWallet).payer,
+      payer,
       program.provider.wallet.publicKey,
       null,
-      0,
-      TOKEN_PROGRAM_ID);
+      0);
 
     // Create an Associated Token Account using that Mint and the program.provider.wallet (i.e. this test's wallet) as the owner
-    testCustomerTokenAccount = await testTokenMintAccount.createAssociatedTokenAccount(
-      program.provider.wallet.publicKey
-    );
+    testCustomerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, testTokenMintAccount, program.provider.wallet.publicKey);
 
     // Mint a token to that ATA
-    await testTokenMintAccount.mintTo(
-      testCustomerTokenAccount,
-      program.provider.wallet.publicKey,
-      [],
-      1)
+    await mintTo(program.provider.connection, payer, testTokenMintAccount, testCustomerTokenAccount, program.provider.wallet.publicKey, 1)
 
     // Check that the testCustomerTokenAccount has one token in it
-    assert.equal(1, (await testTokenMintAccount.getAccountInfo(testCustomerTokenAccount)).amount.toNumber());
+    assert.equal(1, Number((await getAccount(program.provider.connection, testCustomerTokenAccount)).amount));
 
     // Create an address at which the RedemptionInfo account used by this test will live
     [testRedemptionInfoAccount, testRedemptionBump] = await anchor.web3.PublicKey.findProgramAddress(
-      [testTokenMintAccount.publicKey.toBuffer(), Buffer.from("redemption")],
+      [testTokenMintAccount.toBuffer(), Buffer.from("redemption")],
       program.programId,
     );
 
     // Create a PDA at which the BAXUS Escrow Account used by this test will live - also generate the Escrow Bump
     [testBaxusEscrowAccount, testEscrowBump] = await anchor.web3.PublicKey.findProgramAddress(
-      [testTokenMintAccount.publicKey.toBuffer()],
+      [testTokenMintAccount.toBuffer()],
+      program.programId,
+    );
+
+    // BAXUS has to appraise the asset before it can be redeemed
+    [testAppraisalAccount] = await anchor.web3.PublicKey.findProgramAddress(
+      [testTokenMintAccount.toBuffer(), Buffer.from("appraisal")],
+      program.programId,
+    );
+    await program.rpc.setAppraisal(new anchor.BN(250000), {
+      accounts: {
+        appraisal: testAppraisalAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        opsAuthority: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    [testCustomerRegistryAccount] = await anchor.web3.PublicKey.findProgramAddress(
+      [program.provider.wallet.publicKey.toBuffer(), Buffer.from("customer_registry")],
+      program.programId,
+    );
+    [testEscrowLinkAccount] = await anchor.web3.PublicKey.findProgramAddress(
+      [testBaxusEscrowAccount.toBuffer(), Buffer.from("escrow_link")],
       program.programId,
     );
+    [testRedemptionCounterAccount] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("redemption_counter")], program.programId);
+
+    // Run the pre-flight checks first, the way a front-end would before asking the customer to sign
+    await program.rpc.validateRedemptionRequest({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        customerTokenAccount: testCustomerTokenAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        payer: program.provider.wallet.publicKey,
+        tokenMintAccount: testTokenMintAccount,
+        baxusEscrowAccount: testBaxusEscrowAccount,
+        freezeAuthority: testFreezeAuthority,
+        config: testConfigAccount,
+        appraisal: testAppraisalAccount,
+        riskScore: program.programId,
+      },
+    });
+
+    // Check that the pre-flight checks left the token where it was
+    assert.equal(1, Number((await getAccount(program.provider.connection, testCustomerTokenAccount)).amount));
 
-    const tx = await program.rpc.initializeRedemption({
+    // The customer can't open the redemption through a partner without the partner's authority signing
+    let openedThroughPartner = true;
+    try {
+      await program.rpc.initializeRedemption(testContactCommitment, {
+        accounts: {
+          redemptionInfo: testRedemptionInfoAccount,
+          customerTokenAccount: testCustomerTokenAccount,
+          customerPaymentAccount: program.provider.wallet.publicKey,
+          payer: program.provider.wallet.publicKey,
+          tokenMintAccount: testTokenMintAccount,
+          baxusEscrowAccount: testBaxusEscrowAccount,
+          freezeAuthority: testFreezeAuthority,
+          config: testConfigAccount,
+          appraisal: testAppraisalAccount,
+          riskScore: program.programId,
+          customerProfile: program.programId,
+          feeMint: program.programId,
+          payerFeeAccount: program.programId,
+          insurancePoolFeeAccount: program.programId,
+          feeTokenProgram: program.programId,
+          partner: testPartnerAccount,
+          partnerAuthority: program.programId,
+          collectionConfig: testCollectionConfigAccount,
+          hookProgram: program.programId,
+          memoProgram: program.programId,
+          statusIndex: testStatusIndexAccounts[0],
+          statusIndexPage: testStatusIndexPageAccounts[0],
+          customerRegistry: testCustomerRegistryAccount,
+          escrowLink: testEscrowLinkAccount,
+          redemptionCounter: testRedemptionCounterAccount,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+        },
+      });
+    } catch (err) {
+      openedThroughPartner = false;
+    }
+    assert.ok(!openedThroughPartner);
+
+    const tx = await program.rpc.initializeRedemption(testContactCommitment, {
       accounts: {
         redemptionInfo: testRedemptionInfoAccount,
         customerTokenAccount: testCustomerTokenAccount,
         customerPaymentAccount: program.provider.wallet.publicKey,
-        tokenMintAccount: testTokenMintAccount.publicKey,
+        payer: program.provider.wallet.publicKey,
+        tokenMintAccount: testTokenMintAccount,
         baxusEscrowAccount: testBaxusEscrowAccount,
+        freezeAuthority: testFreezeAuthority,
+        config: testConfigAccount,
+        appraisal: testAppraisalAccount,
+        riskScore: program.programId,
+        customerProfile: program.programId,
+        // Uninsured, so the premium accounts are left out (passing the program ID stands in for a missing optional account)
+        feeMint: program.programId,
+        payerFeeAccount: program.programId,
+        insurancePoolFeeAccount: program.programId,
+        feeTokenProgram: program.programId,
+        partner: program.programId,
+        partnerAuthority: program.programId,
+        collectionConfig: testCollectionConfigAccount,
+        hookProgram: program.programId,
+        memoProgram: program.programId,
+        statusIndex: testStatusIndexAccounts[0],
+        statusIndexPage: testStatusIndexPageAccounts[0],
+        customerRegistry: testCustomerRegistryAccount,
+        escrowLink: testEscrowLinkAccount,
+        redemptionCounter: testRedemptionCounterAccount,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
         tokenProgram: TOKEN_PROGRAM_ID,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         systemProgram: SystemProgram.programId,
@@ -77,98 +642,2965 @@ describe('baxus-redemption-service', () => {
     });
 
     // Confirm that the testTokenMintAccount has 1 token in circulation
-    assert.equal(1, ( await testTokenMintAccount.getMintInfo() ).supply);
+    assert.equal(1, Number((await getMint(program.provider.connection, testTokenMintAccount)).supply));
 
     // Check that the testCustomerTokenAccount is empty
-    assert.equal(0, (await testTokenMintAccount.getAccountInfo(testCustomerTokenAccount)).amount.toNumber());
+    assert.equal(0, Number((await getAccount(program.provider.connection, testCustomerTokenAccount)).amount));
 
     // Check that the testBaxusEscrowAccount has one token in it
-    assert.equal(1, (await testTokenMintAccount.getAccountInfo(testBaxusEscrowAccount)).amount.toNumber());
+    assert.equal(1, Number((await getAccount(program.provider.connection, testBaxusEscrowAccount)).amount));
+
+    // Check that the appraised value was locked into the redemption
+    assert.equal(250000, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).valuationUsdCents.toNumber());
+
+    // Legacy SPL Token mints have no native metadata to snapshot
+    assert.equal("", (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).assetName);
+
+    // Check that the redemption was added to the Deposited index and the customer's registry
+    assert.ok((await program.account.statusIndexPage.fetch(testStatusIndexPageAccounts[0])).redemptions[0].equals(testRedemptionInfoAccount));
+    assert.ok((await program.account.customerRegistry.fetch(testCustomerRegistryAccount)).redemptions[0].equals(testRedemptionInfoAccount));
+
+    // The first redemption opened gets ID 1, and opening it emitted RedemptionOpened and StatusChanged
+    assert.equal(1, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).redemptionId.toNumber());
+    assert.equal(2, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).eventSequence.toNumber());
+
+    // Check that the escrow account links back to the redemption
+    assert.ok((await program.account.escrowLink.fetch(testEscrowLinkAccount)).redemptionInfo.equals(testRedemptionInfoAccount));
 
     console.log("Your transaction signature", tx);
   });
 
-  it('Basic test for return_asset_token():', async () => {
+  it('Basic test for reveal_contact():', async () => {
 
-    const tx = await program.rpc.returnAssetToken({
+    const [testContactRevealAccount] = await anchor.web3.PublicKey.findProgramAddress(
+      [testRedemptionInfoAccount.toBuffer(), Buffer.from("contact")],
+      program.programId,
+    );
+
+    // The real ciphertext is encrypted to the ops encryption key off-chain - the program just stores the bytes
+    const ciphertext = crypto.randomBytes(64);
+
+    const tx = await program.rpc.revealContact(ciphertext, {
       accounts: {
+        contactReveal: testContactRevealAccount,
         redemptionInfo: testRedemptionInfoAccount,
-        customerTokenAccount: testCustomerTokenAccount,
         customerPaymentAccount: program.provider.wallet.publicKey,
-        tokenMintAccount: testTokenMintAccount.publicKey,
-        baxusEscrowAccount: testBaxusEscrowAccount,
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
       },
       signers: []
     });
 
-    // Confirm that the testTokenMintAccount still has 1 token in circulation
-    assert.equal(1, ( await testTokenMintAccount.getMintInfo() ).supply);
+    assert.ok(ciphertext.equals((await program.account.contactReveal.fetch(testContactRevealAccount)).ciphertext));
 
-    // Check that the testCustomerTokenAccount has one token in it
-    assert.equal(1, (await testTokenMintAccount.getAccountInfo(testCustomerTokenAccount)).amount.toNumber());
+    console.log("Your transaction signature", tx);
+  });
 
-    // Check that the testRedemptionInfoAccount and testBaxusEscrowAccount were closed 
-    assert.equal(null, await program.provider.connection.getAccountInfo(testRedemptionInfoAccount));
-    assert.equal(null, await program.provider.connection.getAccountInfo(testBaxusEscrowAccount));
+  it('Basic test for update_shipping_commitment():', async () => {
+
+    // The customer moves, so the details they revealed for their old address are deleted along with the old commitment
+    const [contactReveal] = await anchor.web3.PublicKey.findProgramAddress(
+      [testRedemptionInfoAccount.toBuffer(), Buffer.from("contact")],
+      program.programId,
+    );
+    const newCommitment = Array.from(crypto.createHash('sha256').update('test-salt:customer@example.org').digest());
+
+    const tx = await program.rpc.updateShippingCommitment(newCommitment, {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        contactReveal: contactReveal,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      },
+    });
+
+    assert.deepEqual(newCommitment, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).contactCommitment);
+    assert.equal(null, await program.provider.connection.getAccountInfo(contactReveal));
 
     console.log("Your transaction signature", tx);
   });
 
-  it('Basic test for burn_asset_token():', async () => {
+  it('Basic test for set_guardians(), approve_recovery(), cancel_recovery() and execute_recovery():', async () => {
 
-    // Create a new address at which the RedemptionInfo account used by this test will live (since we closed the RedemptionInfo account in the last test)
-    [testRedemptionInfoAccount, testRedemptionBump] = await anchor.web3.PublicKey.findProgramAddress(
-      [testTokenMintAccount.publicKey.toBuffer(), Buffer.from("redemption")],
+    const [guardianSet] = await anchor.web3.PublicKey.findProgramAddress(
+      [testRedemptionInfoAccount.toBuffer(), Buffer.from("guardians")],
       program.programId,
     );
-
-    // Create a new PDA at which the BAXUS Escrow Account used by this test will live - also generate a new Escrow Bump
-    [testBaxusEscrowAccount, testEscrowBump] = await anchor.web3.PublicKey.findProgramAddress(
-      [testTokenMintAccount.publicKey.toBuffer()],
+    const [recoveryRequest] = await anchor.web3.PublicKey.findProgramAddress(
+      [testRedemptionInfoAccount.toBuffer(), Buffer.from("recovery")],
       program.programId,
     );
 
-    const tx1 = await program.rpc.initializeRedemption({
+    // The test wallet is its own only guardian
+    await program.rpc.setGuardians([program.provider.wallet.publicKey], 1, {
       accounts: {
+        guardianSet: guardianSet,
         redemptionInfo: testRedemptionInfoAccount,
-        customerTokenAccount: testCustomerTokenAccount,
+        recoveryRequest: recoveryRequest,
+        tokenMintAccount: testTokenMintAccount,
         customerPaymentAccount: program.provider.wallet.publicKey,
-        tokenMintAccount: testTokenMintAccount.publicKey,
-        baxusEscrowAccount: testBaxusEscrowAccount,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         systemProgram: SystemProgram.programId,
       },
-      // The testRedemptionInfoAccount has to sign upon its own creation
-      signers: []
     });
 
-    // Perform the same checks as in the first test
-    assert.equal(1, ( await testTokenMintAccount.getMintInfo() ).supply);
-    assert.equal(0, (await testTokenMintAccount.getAccountInfo(testCustomerTokenAccount)).amount.toNumber());
-    assert.equal(1, (await testTokenMintAccount.getAccountInfo(testBaxusEscrowAccount)).amount.toNumber());
-   
-    const tx2 = await program.rpc.burnAssetToken({
+    const newCustomer = Keypair.generate().publicKey;
+    await program.rpc.approveRecovery(newCustomer, Keypair.generate().publicKey, {
       accounts: {
+        recoveryRequest: recoveryRequest,
+        guardianSet: guardianSet,
         redemptionInfo: testRedemptionInfoAccount,
-        customerTokenAccount: testCustomerTokenAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        guardian: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    assert.ok((await program.account.recoveryRequest.fetch(recoveryRequest)).newCustomerPaymentAccount.equals(newCustomer));
+
+    // The customer still has their wallet, so they stop the recovery before the timelock runs out
+    await program.rpc.cancelRecovery({
+      accounts: {
+        recoveryRequest: recoveryRequest,
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        proposer: program.provider.wallet.publicKey,
         customerPaymentAccount: program.provider.wallet.publicKey,
-        tokenMintAccount: testTokenMintAccount.publicKey,
-        baxusEscrowAccount: testBaxusEscrowAccount,
+      },
+    });
+    assert.equal(null, await program.provider.connection.getAccountInfo(recoveryRequest));
+
+    // Carry a recovery through on a fresh redemption, so the test redemption keeps its customer
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+    const [freshGuardianSet] = await anchor.web3.PublicKey.findProgramAddress([redemptionInfo.toBuffer(), Buffer.from("guardians")], program.programId);
+    const [freshRecoveryRequest] = await anchor.web3.PublicKey.findProgramAddress([redemptionInfo.toBuffer(), Buffer.from("recovery")], program.programId);
+    await program.rpc.setGuardians([program.provider.wallet.publicKey], 1, {
+      accounts: {
+        guardianSet: freshGuardianSet,
+        redemptionInfo: redemptionInfo,
+        recoveryRequest: freshRecoveryRequest,
+        tokenMintAccount: mint,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    // The customer has lost their wallet, so the guardian moves the redemption to a new wallet with a token account for the asset
+    const recoveredCustomer = Keypair.generate().publicKey;
+    const recoveredTokenAccount = await createAccount(program.provider.connection, payer, mint, recoveredCustomer, Keypair.generate());
+    await program.rpc.approveRecovery(recoveredCustomer, recoveredTokenAccount, {
+      accounts: {
+        recoveryRequest: freshRecoveryRequest,
+        guardianSet: freshGuardianSet,
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        guardian: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    const executeAccounts = {
+      recoveryRequest: freshRecoveryRequest,
+      guardianSet: freshGuardianSet,
+      redemptionInfo: redemptionInfo,
+      tokenMintAccount: mint,
+      newCustomerTokenAccount: recoveredTokenAccount,
+      customerRegistry: testCustomerRegistryAccount,
+      proposer: program.provider.wallet.publicKey,
+      config: testConfigAccount,
+      auditLog: testAuditLogAccount,
+      admin: program.provider.wallet.publicKey,
+    };
+
+    // The admin can't carry it out until the timelock has passed
+    let executedEarly = true;
+    try {
+      await program.rpc.executeRecovery({ accounts: executeAccounts });
+    } catch (err) {
+      executedEarly = false;
+    }
+    assert.ok(!executedEarly);
+
+    await program.rpc.updateConfig({ ...testConfigParams, recoveryTimelockSecs: new anchor.BN(0) }, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+    const tx = await program.rpc.executeRecovery({ accounts: executeAccounts });
+    await program.rpc.updateConfig(testConfigParams, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    // The redemption now belongs to the new wallet, has come off the old wallet's registry, and the request was closed
+    const recovered = await program.account.redemptionInfo.fetch(redemptionInfo);
+    assert.ok(recovered.customerPaymentAccount.equals(recoveredCustomer));
+    assert.ok(recovered.customerTokenAccount.equals(recoveredTokenAccount));
+    assert.ok(!(await program.account.customerRegistry.fetch(testCustomerRegistryAccount)).redemptions.some((entry) => entry.equals(redemptionInfo)));
+    assert.equal(null, await program.provider.connection.getAccountInfo(freshRecoveryRequest));
+
+    // Returning the asset now sends it to the new token account, which also takes the redemption back off the status index
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), mint.toBuffer(), recovered.createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+    await program.rpc.returnAssetToken({
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        customerTokenAccount: recoveredTokenAccount,
+        customerPaymentAccount: recoveredCustomer,
+        tokenMintAccount: mint,
+        baxusEscrowAccount: escrow,
+        statusIndex: testStatusIndexAccounts[0],
+        statusIndexPage: testStatusIndexPageAccounts[0],
+        priorityIndex: program.programId,
+        auditLog: testAuditLogAccount,
+        customerRegistry: program.programId,
+        config: testConfigAccount,
+        treasury: program.programId,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        receipt: receipt,
         tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
       },
-      signers: []
     });
+    assert.equal(1, Number((await getAccount(program.provider.connection, recoveredTokenAccount)).amount));
 
-    // Confirm that the testTokenMintAccount has no more tokens left in circulation
-    assert.equal(0, ( await testTokenMintAccount.getMintInfo() ).supply);
+    console.log("Your transaction signature", tx);
+  });
 
-    // Check that the testRedemptionInfoAccount and testBaxusEscrowAccount were closed 
-    assert.equal(null, await program.provider.connection.getAccountInfo(testRedemptionInfoAccount));
-    assert.equal(null, await program.provider.connection.getAccountInfo(testBaxusEscrowAccount));
+  it('Basic test for migrate_redemption_info():', async () => {
 
-    console.log("Your transaction signature", tx2);
+    // The testRedemptionInfoAccount was opened with the current layout, so migrating it shouldn't change its size
+    const size = (await program.provider.connection.getAccountInfo(testRedemptionInfoAccount)).data.length;
+
+    const tx = await program.rpc.migrateRedemptionInfo({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+      signers: []
+    });
+
+    // Check that the testRedemptionInfoAccount is still exactly the size of the current RedemptionInfo layout
+    assert.equal(size, (await program.provider.connection.getAccountInfo(testRedemptionInfoAccount)).data.length);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for consolidate_to_cold_vault() and recall_from_cold_vault():', async () => {
+
+    // The test wallet is configured as the cold vault multisig, so the cold vault account is its ATA for the mint
+    const coldVaultAccount = await getAssociatedTokenAddress(testTokenMintAccount, program.provider.wallet.publicKey);
+    const consolidateAccounts = {
+      redemptionInfo: testRedemptionInfoAccount,
+      customerPaymentAccount: program.provider.wallet.publicKey,
+      tokenMintAccount: testTokenMintAccount,
+      baxusEscrowAccount: testBaxusEscrowAccount,
+      coldVaultAccount: coldVaultAccount,
+      coldVaultAuthority: program.provider.wallet.publicKey,
+      config: testConfigAccount,
+      // The treasury doesn't take a share of the escrow account's rent
+      treasury: program.programId,
+      auditLog: testAuditLogAccount,
+      opsAuthority: program.provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    // The customer couldn't self-return a vaulted token, so nothing can be vaulted while the dead-man switch is on
+    let vaulted = true;
+    try {
+      await program.rpc.consolidateToColdVault({ accounts: consolidateAccounts });
+    } catch (err) {
+      vaulted = false;
+    }
+    assert.ok(!vaulted);
+
+    await program.rpc.updateConfig({ ...testConfigParams, deadManSecs: new anchor.BN(0) }, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    // The redemption was only opened a few seconds ago, so it hasn't been waiting for the day cold_vault_min_dwell_secs asks for
+    vaulted = true;
+    try {
+      await program.rpc.consolidateToColdVault({ accounts: consolidateAccounts });
+    } catch (err) {
+      vaulted = false;
+    }
+    assert.ok(!vaulted);
+
+    await program.rpc.updateConfig({ ...testConfigParams, deadManSecs: new anchor.BN(0), coldVaultMinDwellSecs: new anchor.BN(1) }, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+    const tx1 = await program.rpc.consolidateToColdVault({ accounts: consolidateAccounts });
+
+    // Check that the token moved to the cold vault and the escrow account was closed
+    assert.equal(1, Number((await getAccount(program.provider.connection, coldVaultAccount)).amount));
+    assert.equal(null, await program.provider.connection.getAccountInfo(testBaxusEscrowAccount));
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).coldVaultAccount.equals(coldVaultAccount));
+
+    // The escrow account is gone, but the redemption can't be collected while the cold vault holds its token
+    let collected = true;
+    try {
+      await program.rpc.gcRedemption({
+        accounts: {
+          redemptionInfo: testRedemptionInfoAccount,
+          tokenMintAccount: testTokenMintAccount,
+          baxusEscrowAccount: testBaxusEscrowAccount,
+          customerPaymentAccount: program.provider.wallet.publicKey,
+          statusIndex: testStatusIndexAccounts[0],
+          statusIndexPage: testStatusIndexPageAccounts[0],
+          customerRegistry: program.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+      });
+    } catch (err) {
+      collected = false;
+    }
+    assert.ok(!collected);
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).coldVaultAccount.equals(coldVaultAccount));
+
+    const tx2 = await program.rpc.recallFromColdVault({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        baxusEscrowAccount: testBaxusEscrowAccount,
+        coldVaultAccount: coldVaultAccount,
+        coldVaultAuthority: program.provider.wallet.publicKey,
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      },
+      signers: []
+    });
+
+    // Check that the token is back in escrow
+    assert.equal(1, Number((await getAccount(program.provider.connection, testBaxusEscrowAccount)).amount));
+    assert.equal(0, Number((await getAccount(program.provider.connection, coldVaultAccount)).amount));
+
+    // Turn the dead-man switch back on
+    await program.rpc.updateConfig(testConfigParams, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    console.log("Your transaction signature", tx2);
+  });
+
+  it('Basic test for set_ops_reference():', async () => {
+
+    const opsReference = Array.from(crypto.createHash('sha256').update('WMS-0001').digest());
+
+    const tx = await program.rpc.setOpsReference(opsReference, {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+      },
+      signers: []
+    });
+
+    assert.deepEqual(opsReference, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).opsReference);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for commit_shipment_documents():', async () => {
+
+    const documentsHash = Array.from(crypto.createHash('sha256').update('manifest-0001:customs-0001').digest());
+
+    const tx = await program.rpc.commitShipmentDocuments(documentsHash, {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+      },
+    });
+
+    assert.deepEqual(documentsHash, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).shipmentDocumentsHash);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for set_priority() and prune_priority_index():', async () => {
+
+    const [priorityIndex] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("priority_index")], program.programId);
+    const accounts = {
+      redemptionInfo: testRedemptionInfoAccount,
+      tokenMintAccount: testTokenMintAccount,
+      priorityIndex: priorityIndex,
+      config: testConfigAccount,
+      partner: program.programId,
+      authority: program.provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+
+    // Move the redemption to the front of the queue
+    await program.rpc.setPriority(5, { accounts });
+    assert.equal(5, (await program.account.priorityIndex.fetch(priorityIndex)).entries[0].priority);
+
+    // The redemption still has its priority, so its entry can't be pruned
+    let pruned = true;
+    try {
+      await program.rpc.prunePriorityIndex(testRedemptionInfoAccount, {
+        accounts: { priorityIndex: priorityIndex, redemptionInfo: testRedemptionInfoAccount },
+      });
+    } catch (err) {
+      pruned = false;
+    }
+    assert.ok(!pruned);
+
+    const tx = await program.rpc.setPriority(0, { accounts });
+    assert.equal(0, (await program.account.priorityIndex.fetch(priorityIndex)).entries.length);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for resync_redemption():', async () => {
+
+    const eventSequence = (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).eventSequence;
+
+    const tx = await program.rpc.resyncRedemption({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+      },
+    });
+
+    // Resyncing doesn't count as an event
+    assert.ok(eventSequence.eq((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).eventSequence));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for attest_reserves():', async () => {
+
+    const [proofOfReserve] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("proof_of_reserve")], program.programId);
+    const attestAccounts = {
+      proofOfReserve: proofOfReserve,
+      config: testConfigAccount,
+      opsAuthority: program.provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+    const completeAccounts = {
+      proofOfReserve: proofOfReserve,
+      depositedIndex: testStatusIndexAccounts[0],
+      kycApprovedIndex: testStatusIndexAccounts[1],
+      packedIndex: testStatusIndexAccounts[2],
+      shippedIndex: testStatusIndexAccounts[3],
+      config: testConfigAccount,
+      opsAuthority: program.provider.wallet.publicKey,
+    };
+
+    // Every open redemption is listed on a page of one of the status indexes before Delivered
+    const openRedemptions: PublicKey[] = [];
+    for (const [i, statusIndex] of testStatusIndexAccounts.slice(0, 4).entries()) {
+      const { pageCount } = await program.account.statusIndex.fetch(statusIndex);
+      for (let page = 0; page < pageCount; page++) {
+        const [statusIndexPage] = await anchor.web3.PublicKey.findProgramAddress(
+          [Buffer.from("status_index_page"), Buffer.from([i]), new anchor.BN(page).toArrayLike(Buffer, "le", 4)],
+          program.programId,
+        );
+        openRedemptions.push(...(await program.account.statusIndexPage.fetch(statusIndexPage)).redemptions);
+      }
+    }
+    openRedemptions.sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()));
+
+    // Pair each one with the token account its token is held in
+    const pairs: AccountMeta[][] = [];
+    for (const redemptionInfo of openRedemptions) {
+      const redemption = await program.account.redemptionInfo.fetch(redemptionInfo);
+      const { mint } = await getAccount(program.provider.connection, redemption.customerTokenAccount);
+      const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+      const custodyAccount = redemption.coldVaultAccount.equals(PublicKey.default) ? escrow : redemption.coldVaultAccount;
+      pairs.push([
+        { pubkey: redemptionInfo, isSigner: false, isWritable: false },
+        { pubkey: custodyAccount, isSigner: false, isWritable: false },
+      ]);
+    }
+    assert.ok(pairs.length > 0);
+
+    // A fresh attestation covers none of them, so it can't be completed yet
+    const tx = await program.rpc.attestReserves(true, { accounts: attestAccounts });
+    assert.equal('ReservesIncomplete', await errorCodeOf(program.rpc.completeReserveAttestation({ accounts: completeAccounts })));
+
+    // Attest them a few at a time, and then it can
+    const batchSize = 8;
+    for (let i = 0; i < pairs.length; i += batchSize) {
+      await program.rpc.attestReserves(false, {
+        accounts: attestAccounts,
+        remainingAccounts: pairs.slice(i, i + batchSize).flat(),
+      });
+    }
+    await program.rpc.completeReserveAttestation({ accounts: completeAccounts });
+
+    let proof = await program.account.proofOfReserve.fetch(proofOfReserve);
+    assert.ok(proof.completed);
+    assert.equal(pairs.length, proof.attestedCount);
+    assert.equal(pairs.length, proof.openCount.toNumber());
+
+    // A completed attestation can't be added to, only restarted
+    assert.equal('ReserveAttestationCompleted', await errorCodeOf(program.rpc.attestReserves(false, {
+      accounts: attestAccounts,
+      remainingAccounts: pairs[0],
+    })));
+    await program.rpc.attestReserves(true, { accounts: attestAccounts, remainingAccounts: pairs[0] });
+    proof = await program.account.proofOfReserve.fetch(proofOfReserve);
+    assert.ok(!proof.completed);
+    assert.equal(1, proof.attestedCount);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for assert_invariants():', async () => {
+
+    // The redemption holds no collateral yet
+    const tx = await program.rpc.assertInvariants({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        custodyAccount: testBaxusEscrowAccount,
+        collateralAccount: program.programId,
+        customerRegistry: testCustomerRegistryAccount,
+      },
+    });
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for attest_inventory():', async () => {
+
+    const [inventoryAttestation] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("inventory_attestation")], program.programId);
+    const accounts = {
+      inventoryAttestation: inventoryAttestation,
+      config: testConfigAccount,
+      opsAuthority: program.provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const tx = await program.rpc.attestInventory(1, Array(32).fill(7), { accounts });
+    assert.equal(1, (await program.account.inventoryAttestation.fetch(inventoryAttestation)).bottleCount);
+
+    // Only one attestation a week
+    let reattested = true;
+    try {
+      await program.rpc.attestInventory(2, Array(32).fill(8), { accounts });
+    } catch (err) {
+      reattested = false;
+    }
+    assert.ok(!reattested);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for set_risk_score():', async () => {
+
+    const [riskScore] = await anchor.web3.PublicKey.findProgramAddress(
+      [program.provider.wallet.publicKey.toBuffer(), Buffer.from("risk_score")],
+      program.programId,
+    );
+
+    // The test wallet is the compliance authority
+    const tx = await program.rpc.setRiskScore(10, {
+      accounts: {
+        riskScore: riskScore,
+        customer: program.provider.wallet.publicKey,
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        complianceAuthority: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    assert.equal(10, (await program.account.riskScore.fetch(riskScore)).score);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for flag_suspicious() and unfreeze():', async () => {
+
+    const accounts = {
+      redemptionInfo: testRedemptionInfoAccount,
+      tokenMintAccount: testTokenMintAccount,
+      config: testConfigAccount,
+      auditLog: testAuditLogAccount,
+      complianceAuthority: program.provider.wallet.publicKey,
+    };
+    await program.rpc.flagSuspicious({ accounts });
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).frozenByCompliance);
+
+    // The test wallet is both the compliance authority and the admin
+    const tx = await program.rpc.unfreeze({ accounts: { ...accounts, admin: program.provider.wallet.publicKey } });
+    assert.ok(!(await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).frozenByCompliance);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for reject_redemption(), appeal_rejection() and resolve_appeal():', async () => {
+
+    await program.rpc.rejectRedemption({ unsupportedJurisdiction: {} }, {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        priorityIndex: program.programId,
+      },
+    });
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).rejection.unsupportedJurisdiction);
+
+    // The token stays in escrow while the customer can still appeal
+    const createdAt = (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).createdAt;
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), testTokenMintAccount.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+    let finalizedEarly = true;
+    try {
+      await program.rpc.finalizeRejection({
+        accounts: {
+          redemptionInfo: testRedemptionInfoAccount,
+          customerTokenAccount: testCustomerTokenAccount,
+          customerPaymentAccount: program.provider.wallet.publicKey,
+          tokenMintAccount: testTokenMintAccount,
+          baxusEscrowAccount: testBaxusEscrowAccount,
+          statusIndex: testStatusIndexAccounts[0],
+          statusIndexPage: testStatusIndexPageAccounts[0],
+          priorityIndex: program.programId,
+          auditLog: program.programId,
+          customerRegistry: testCustomerRegistryAccount,
+          config: testConfigAccount,
+          treasury: program.programId,
+          partner: program.programId,
+          authority: program.provider.wallet.publicKey,
+          receipt: receipt,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        },
+      });
+    } catch (err) {
+      finalizedEarly = false;
+    }
+    assert.ok(!finalizedEarly);
+    assert.equal(1, Number((await getAccount(program.provider.connection, testBaxusEscrowAccount)).amount));
+
+    // The customer appeals, which freezes the redemption again until compliance looks at the evidence, and posts the appeal bond
+    const rentBeforeAppeal = await program.provider.connection.getBalance(testRedemptionInfoAccount);
+    const appealAccounts = {
+      redemptionInfo: testRedemptionInfoAccount,
+      tokenMintAccount: testTokenMintAccount,
+      config: testConfigAccount,
+      auditLog: testAuditLogAccount,
+    };
+    await program.rpc.appealRejection(Array(32).fill(3), {
+      accounts: { ...appealAccounts, customerPaymentAccount: program.provider.wallet.publicKey, systemProgram: SystemProgram.programId },
+    });
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).frozenByCompliance);
+    assert.equal(rentBeforeAppeal + LAMPORTS_PER_SOL / 100, await program.provider.connection.getBalance(testRedemptionInfoAccount));
+
+    // A redemption can only be appealed once
+    let appealedTwice = true;
+    try {
+      await program.rpc.appealRejection(Array(32).fill(4), {
+        accounts: { ...appealAccounts, customerPaymentAccount: program.provider.wallet.publicKey, systemProgram: SystemProgram.programId },
+      });
+    } catch (err) {
+      appealedTwice = false;
+    }
+    assert.ok(!appealedTwice);
+
+    // The appeal's freeze can't be lifted without deciding it. The test wallet is both the compliance authority and the admin
+    const complianceAccounts = {
+      ...appealAccounts,
+      complianceAuthority: program.provider.wallet.publicKey,
+      admin: program.provider.wallet.publicKey,
+    };
+    let unfrozen = true;
+    try {
+      await program.rpc.unfreeze({ accounts: complianceAccounts });
+    } catch (err) {
+      unfrozen = false;
+    }
+    assert.ok(!unfrozen);
+
+    // Compliance overturns the rejection, which refunds the appeal bond
+    const tx = await program.rpc.resolveAppeal(false, {
+      accounts: {
+        ...complianceAccounts,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        treasury: program.provider.wallet.publicKey,
+      },
+    });
+
+    // The rejection is cleared and the redemption carries on where it was, with the token still in escrow
+    const redemption = await program.account.redemptionInfo.fetch(testRedemptionInfoAccount);
+    assert.equal(null, redemption.rejection);
+    assert.equal(false, redemption.appealResolved);
+    assert.equal(rentBeforeAppeal, await program.provider.connection.getBalance(testRedemptionInfoAccount));
+    assert.ok(!redemption.frozenByCompliance);
+    assert.ok(redemption.status.deposited);
+    assert.equal(1, Number((await getAccount(program.provider.connection, testBaxusEscrowAccount)).amount));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for record_custody_handoff():', async () => {
+
+    const [custodyLog] = await anchor.web3.PublicKey.findProgramAddress(
+      [testRedemptionInfoAccount.toBuffer(), Buffer.from("custody_log")],
+      program.programId,
+    );
+    const courier = Keypair.generate();
+    const accounts = {
+      custodyLog: custodyLog,
+      redemptionInfo: testRedemptionInfoAccount,
+      tokenMintAccount: testTokenMintAccount,
+      config: testConfigAccount,
+      partner: program.programId,
+      authority: program.provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+
+    // Ops hand the asset from the vault to the courier, and the courier hands it to the customer
+    await program.rpc.recordCustodyHandoff({ courier: {} }, courier.publicKey, { accounts });
+    const tx = await program.rpc.recordCustodyHandoff({ customer: {} }, program.provider.wallet.publicKey, {
+      accounts: { ...accounts, authority: courier.publicKey },
+      signers: [courier],
+    });
+
+    const entries = (await program.account.custodyLog.fetch(custodyLog)).entries;
+    assert.equal(2, entries.length);
+    assert.ok(entries[1].recordedBy.equals(courier.publicKey));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for attach_certificate() and detach_certificate():', async () => {
+
+    // Mint an authenticity certificate NFT to the customer
+    const certificateMint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerCertificateAccount = await createAssociatedTokenAccount(program.provider.connection, payer, certificateMint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, certificateMint, customerCertificateAccount, program.provider.wallet.publicKey, 1);
+
+    const [certificateEscrow] = await anchor.web3.PublicKey.findProgramAddress(
+      [certificateMint.toBuffer(), Buffer.from("attachment_escrow")],
+      program.programId,
+    );
+
+    await program.rpc.attachCertificate({ authenticity: {} }, true, {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        certificateMint: certificateMint,
+        customerCertificateAccount: customerCertificateAccount,
+        certificateEscrow: certificateEscrow,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        payer: program.provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).attachments[0].mint.equals(certificateMint));
+    assert.equal(1, Number((await getAccount(program.provider.connection, certificateEscrow)).amount));
+
+    // Detaching the escrowed certificate hands it back to the customer
+    const tx = await program.rpc.detachCertificate({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        certificateMint: certificateMint,
+        certificateEscrow: certificateEscrow,
+        customerCertificateAccount: customerCertificateAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+    });
+    assert.equal(0, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).attachments.length);
+    assert.equal(1, Number((await getAccount(program.provider.connection, customerCertificateAccount)).amount));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for open_invoice(), set_duty_rate(), set_jurisdiction(), add_invoice_item() and settle_invoice():', async () => {
+
+    const [invoice] = await anchor.web3.PublicKey.findProgramAddress(
+      [testRedemptionInfoAccount.toBuffer(), Buffer.from("invoice")],
+      program.programId,
+    );
+
+    await program.rpc.openInvoice({
+      accounts: {
+        invoice: invoice,
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    const jurisdiction = Array.from(Buffer.from("US"));
+
+    await program.rpc.setDutyRate(jurisdiction, 500, {
+      accounts: {
+        dutyRates: testDutyRatesAccount,
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        admin: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    await program.rpc.setJurisdiction(jurisdiction, {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        invoice: invoice,
+        dutyRates: testDutyRatesAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+      },
+    });
+
+    await program.rpc.addInvoiceItem({ shipping: {} }, new anchor.BN(2500), {
+      accounts: {
+        invoice: invoice,
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        opsAuthority: program.provider.wallet.publicKey,
+      },
+    });
+
+    // The base fee, 5% duty on the $2,500 appraisal, and the shipping quote
+    const lineItems = (await program.account.invoice.fetch(invoice)).lineItems;
+    assert.deepEqual([5000, 12500, 2500], lineItems.map((item) => item.amountUsdCents.toNumber()));
+
+    // The test wallet is also the treasury, so it settles the invoice from its own fee account to itself
+    const feeAccount = await createAssociatedTokenAccount(program.provider.connection, payer, testFeeMint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, testFeeMint, feeAccount, program.provider.wallet.publicKey, 1000 * 1000000);
+
+    const tx = await program.rpc.settleInvoice({
+      accounts: {
+        invoice: invoice,
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        feeMint: testFeeMint,
+        partner: program.programId,
+        payerFeeAccount: feeAccount,
+        treasuryFeeAccount: feeAccount,
+        accounting: testAccountingAccount,
+        payer: program.provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+      signers: []
+    });
+
+    assert.equal(20000, (await program.account.invoice.fetch(invoice)).paidUsdCents.toNumber());
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).invoicePaid);
+
+    // The redemption was opened less than a day ago, so no storage has accrued yet
+    assert.equal(3, (await program.account.invoice.fetch(invoice)).lineItems.length);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for set_customer_profile():', async () => {
+
+    const [customerProfile] = await anchor.web3.PublicKey.findProgramAddress(
+      [program.provider.wallet.publicKey.toBuffer(), Buffer.from("customer_profile")],
+      program.programId,
+    );
+
+    // The duty rate for the US was set in the invoice test, and the config's fee mint doesn't need a FeeCurrency
+    const tx = await program.rpc.setCustomerProfile(Array.from(Buffer.from("US")), false, testFeeMint, {
+      accounts: {
+        customerProfile: customerProfile,
+        dutyRates: testDutyRatesAccount,
+        config: testConfigAccount,
+        feeCurrency: program.programId,
+        customer: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    const profile = await program.account.customerProfile.fetch(customerProfile);
+    assert.equal("US", Buffer.from(profile.jurisdiction).toString());
+    assert.ok(!profile.insure);
+    assert.ok(profile.feeCurrency.equals(testFeeMint));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for settle_partner_share():', async () => {
+
+    // None of the test redemptions were opened through the partner, so it has nothing to be paid yet
+    const treasuryFeeAccount = await getAssociatedTokenAddress(testFeeMint, program.provider.wallet.publicKey);
+    const partnerFeeAccount = await createAssociatedTokenAccount(program.provider.connection, payer, testFeeMint, testPartnerAuthority);
+
+    const tx = await program.rpc.settlePartnerShare({
+      accounts: {
+        partner: testPartnerAccount,
+        config: testConfigAccount,
+        feeMint: testFeeMint,
+        treasuryFeeAccount: treasuryFeeAccount,
+        partnerFeeAccount: partnerFeeAccount,
+        accounting: testAccountingAccount,
+        treasury: program.provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+    });
+
+    assert.equal(0, (await program.account.partner.fetch(testPartnerAccount)).shareSettledUsdCents.toNumber());
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for set_fee_currency() and remove_fee_currency():', async () => {
+
+    // Whitelist a second token for paying invoices - there's no Pyth feed on the local validator, so use a placeholder price feed address
+    const paymentMint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      9);
+    const [feeCurrency] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("fee_currency"), paymentMint.toBuffer()],
+      program.programId,
+    );
+    const priceFeed = Keypair.generate().publicKey;
+
+    await program.rpc.setFeeCurrency(priceFeed, new anchor.BN(60), {
+      accounts: {
+        feeCurrency: feeCurrency,
+        paymentMint: paymentMint,
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        admin: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    assert.ok((await program.account.feeCurrency.fetch(feeCurrency)).priceFeed.equals(priceFeed));
+
+    const tx = await program.rpc.removeFeeCurrency({
+      accounts: {
+        feeCurrency: feeCurrency,
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        admin: program.provider.wallet.publicKey,
+      },
+    });
+    assert.equal(null, await program.provider.connection.getAccountInfo(feeCurrency));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for post_collateral() and settle_collateral():', async () => {
+
+    // The customer posts 10% of the $2,500 appraisal as collateral to ship before KYC clears
+    const feeAccount = await getAssociatedTokenAddress(testFeeMint, program.provider.wallet.publicKey);
+    const [collateralAccount] = await anchor.web3.PublicKey.findProgramAddress(
+      [testRedemptionInfoAccount.toBuffer(), Buffer.from("collateral")],
+      program.programId,
+    );
+
+    await program.rpc.postCollateral({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        feeMint: testFeeMint,
+        customerFeeAccount: feeAccount,
+        collateralAccount: collateralAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    assert.equal(250 * 1000000, Number((await getAccount(program.provider.connection, collateralAccount)).amount));
+
+    // KYC clears, so ops release the collateral back to the customer
+    const tx = await program.rpc.settleCollateral({ release: {} }, {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        feeMint: testFeeMint,
+        collateralAccount: collateralAccount,
+        destinationFeeAccount: feeAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+    });
+    assert.equal(null, await program.provider.connection.getAccountInfo(collateralAccount));
+    assert.equal(0, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).collateralAmount.toNumber());
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for flag_sla_breach():', async () => {
+
+    // Make sure the one second Deposited deadline has passed
+    await warpClock(2);
+
+    const tx = await program.rpc.flagSlaBreach({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+      },
+      signers: []
+    });
+
+    // Check that the breach was recorded against the Deposited status, and unlocked the remedies in the SLA policy
+    const redemptionInfo = await program.account.redemptionInfo.fetch(testRedemptionInfoAccount);
+    assert.equal(1, redemptionInfo.slaBreaches);
+    assert.ok(redemptionInfo.feesWaived);
+    assert.ok(redemptionInfo.penaltyFreeCancellation);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for pay_sla_compensation():', async () => {
+
+    const tx = await program.rpc.paySlaCompensation({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        config: testConfigAccount,
+        accounting: testAccountingAccount,
+        treasury: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+      signers: []
+    });
+
+    // Compensation only accrues per full day, so nothing is owed yet
+    assert.equal(0, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).compensationPaid.toNumber());
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for request_hold() and release_hold():', async () => {
+
+    await program.rpc.requestHold({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+      },
+    });
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).heldSince.toNumber() > 0);
+
+    const tx = await program.rpc.releaseHold({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+      },
+    });
+    assert.equal(0, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).heldSince.toNumber());
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for advance_status():', async () => {
+
+    const tx = await program.rpc.advanceStatus({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        currentStatusIndex: testStatusIndexAccounts[0],
+        currentStatusIndexPage: testStatusIndexPageAccounts[0],
+        nextStatusIndex: testStatusIndexAccounts[1],
+        nextStatusIndexPage: testStatusIndexPageAccounts[1],
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        priorityIndex: program.programId,
+        enhancedApprover: program.programId,
+        hookProgram: program.programId,
+        // Write a status memo, so the transition shows up in the customer's wallet history
+        memoProgram: new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+        systemProgram: SystemProgram.programId,
+      },
+      signers: []
+    });
+
+    // Check that the redemption moved from the Deposited index to the KycApproved index
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).status.kycApproved);
+    assert.equal(2, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).sequence.toNumber());
+    assert.equal(0, (await program.account.statusIndexPage.fetch(testStatusIndexPageAccounts[0])).redemptions.length);
+    assert.ok((await program.account.statusIndexPage.fetch(testStatusIndexPageAccounts[1])).redemptions[0].equals(testRedemptionInfoAccount));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for return_asset_token():', async () => {
+
+    const createdAt = (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).createdAt;
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), testTokenMintAccount.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+
+    // Give the redemption a priority, which returning it has to take out of the priority index
+    const [priorityIndex] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("priority_index")], program.programId);
+    await program.rpc.setPriority(3, {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        priorityIndex: priorityIndex,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    assert.equal(1, (await program.account.priorityIndex.fetch(priorityIndex)).entries.length);
+
+    const tx = await program.rpc.returnAssetToken({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        customerTokenAccount: testCustomerTokenAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        tokenMintAccount: testTokenMintAccount,
+        baxusEscrowAccount: testBaxusEscrowAccount,
+        statusIndex: testStatusIndexAccounts[1],
+        statusIndexPage: testStatusIndexPageAccounts[1],
+        priorityIndex: priorityIndex,
+        auditLog: testAuditLogAccount,
+        customerRegistry: testCustomerRegistryAccount,
+        config: testConfigAccount,
+        treasury: program.programId,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        receipt: receipt,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      },
+      signers: []
+    });
+
+    // Check that the receipt records the return
+    assert.ok((await program.account.redemptionReceipt.fetch(receipt)).outcome.returned);
+    assert.equal(0, (await program.account.priorityIndex.fetch(priorityIndex)).entries.length);
+
+    // The forced return was recorded in the audit log
+    const auditLog = await program.account.auditLog.fetch(testAuditLogAccount);
+    const lastEntry = auditLog.entries[(auditLog.head.toNumber() + auditLog.entries.length - 1) % auditLog.entries.length];
+    assert.equal(17, lastEntry.action);
+    assert.ok(lastEntry.actor.equals(program.provider.wallet.publicKey));
+
+    // Confirm that the testTokenMintAccount still has 1 token in circulation
+    assert.equal(1, Number((await getMint(program.provider.connection, testTokenMintAccount)).supply));
+
+    // Check that the testCustomerTokenAccount has one token in it
+    assert.equal(1, Number((await getAccount(program.provider.connection, testCustomerTokenAccount)).amount));
+
+    // Check that the testRedemptionInfoAccount and testBaxusEscrowAccount were closed 
+    assert.equal(null, await program.provider.connection.getAccountInfo(testRedemptionInfoAccount));
+    assert.equal(null, await program.provider.connection.getAccountInfo(testBaxusEscrowAccount));
+
+    // Check that the redemption was taken off the customer's registry
+    assert.equal(0, (await program.account.customerRegistry.fetch(testCustomerRegistryAccount)).redemptions.length);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for cancel_redemption():', async () => {
+
+    // Open a new insured redemption (since we closed the RedemptionInfo account in the last test), and let it breach its SLA. The test wallet
+    // is also the insurance pool, so it pays the premium from its own fee account to itself
+    const feeAccount = await getAssociatedTokenAddress(testFeeMint, program.provider.wallet.publicKey);
+    const idempotencyKey = Array(32).fill(4);
+    const initializeAccounts = {
+      redemptionInfo: testRedemptionInfoAccount,
+      customerTokenAccount: testCustomerTokenAccount,
+      customerPaymentAccount: program.provider.wallet.publicKey,
+      payer: program.provider.wallet.publicKey,
+      tokenMintAccount: testTokenMintAccount,
+      baxusEscrowAccount: testBaxusEscrowAccount,
+      freezeAuthority: testFreezeAuthority,
+      config: testConfigAccount,
+      appraisal: testAppraisalAccount,
+      riskScore: program.programId,
+      customerProfile: program.programId,
+      feeMint: testFeeMint,
+      payerFeeAccount: feeAccount,
+      insurancePoolFeeAccount: feeAccount,
+      feeTokenProgram: TOKEN_PROGRAM_ID,
+      partner: program.programId,
+      partnerAuthority: program.programId,
+      collectionConfig: testCollectionConfigAccount,
+      hookProgram: program.programId,
+      memoProgram: program.programId,
+      statusIndex: testStatusIndexAccounts[0],
+      statusIndexPage: testStatusIndexPageAccounts[0],
+      customerRegistry: testCustomerRegistryAccount,
+      escrowLink: testEscrowLinkAccount,
+      redemptionCounter: testRedemptionCounterAccount,
+      instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      systemProgram: SystemProgram.programId,
+    };
+
+    // initialize_redemption_for only works through a CPI from a whitelisted marketplace, so calling it directly fails
+    let calledDirectly = true;
+    try {
+      await program.rpc.initializeRedemptionFor(testContactCommitment, true, { express: {} }, idempotencyKey, { accounts: initializeAccounts });
+    } catch (err) {
+      calledDirectly = false;
+    }
+    assert.ok(!calledDirectly);
+
+    await program.rpc.initializeRedemptionV2(testContactCommitment, true, { express: {} }, idempotencyKey, { accounts: initializeAccounts });
+
+    // A retry with the same idempotency key succeeds without opening anything new. Warping the clock in the same transaction keeps the
+    // validator from dropping the retry as a duplicate of the first one
+    await program.rpc.initializeRedemptionV2(testContactCommitment, true, { express: {} }, idempotencyKey, {
+      accounts: initializeAccounts,
+      preInstructions: [warpClockInstruction(1)],
+    });
+    assert.equal(1, (await program.account.statusIndexPage.fetch(testStatusIndexPageAccounts[0])).redemptions.length);
+
+    // The coverage is the $2,500 appraisal
+    assert.equal(250000, (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).coverageUsdCents.toNumber());
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).serviceTier.express);
+
+    await warpClock(2);
+    await program.rpc.flagSlaBreach({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+      },
+    });
+
+    const createdAt = (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).createdAt;
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), testTokenMintAccount.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+
+    const tx = await program.rpc.cancelRedemption({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        customerTokenAccount: testCustomerTokenAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        tokenMintAccount: testTokenMintAccount,
+        baxusEscrowAccount: testBaxusEscrowAccount,
+        statusIndex: testStatusIndexAccounts[0],
+        statusIndexPage: testStatusIndexPageAccounts[0],
+        priorityIndex: program.programId,
+        customerRegistry: testCustomerRegistryAccount,
+        config: testConfigAccount,
+        treasury: program.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        receipt: receipt,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      },
+      signers: []
+    });
+    assert.ok((await program.account.redemptionReceipt.fetch(receipt)).outcome.cancelled);
+
+    // Check that the token is back with the customer and the testRedemptionInfoAccount and testBaxusEscrowAccount were closed
+    assert.equal(1, Number((await getAccount(program.provider.connection, testCustomerTokenAccount)).amount));
+    assert.equal(null, await program.provider.connection.getAccountInfo(testRedemptionInfoAccount));
+    assert.equal(null, await program.provider.connection.getAccountInfo(testBaxusEscrowAccount));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for burn_asset_token():', async () => {
+
+    // Create a new address at which the RedemptionInfo account used by this test will live (since we closed the RedemptionInfo account in the last test)
+    [testRedemptionInfoAccount, testRedemptionBump] = await anchor.web3.PublicKey.findProgramAddress(
+      [testTokenMintAccount.toBuffer(), Buffer.from("redemption")],
+      program.programId,
+    );
+
+    // Create a new PDA at which the BAXUS Escrow Account used by this test will live - also generate a new Escrow Bump
+    [testBaxusEscrowAccount, testEscrowBump] = await anchor.web3.PublicKey.findProgramAddress(
+      [testTokenMintAccount.toBuffer()],
+      program.programId,
+    );
+
+    const tx1 = await program.rpc.initializeRedemption(testContactCommitment, {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        customerTokenAccount: testCustomerTokenAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        payer: program.provider.wallet.publicKey,
+        tokenMintAccount: testTokenMintAccount,
+        baxusEscrowAccount: testBaxusEscrowAccount,
+        freezeAuthority: testFreezeAuthority,
+        config: testConfigAccount,
+        appraisal: testAppraisalAccount,
+        riskScore: program.programId,
+        customerProfile: program.programId,
+        // Uninsured, so the premium accounts are left out (passing the program ID stands in for a missing optional account)
+        feeMint: program.programId,
+        payerFeeAccount: program.programId,
+        insurancePoolFeeAccount: program.programId,
+        feeTokenProgram: program.programId,
+        partner: program.programId,
+        partnerAuthority: program.programId,
+        collectionConfig: testCollectionConfigAccount,
+        hookProgram: program.programId,
+        memoProgram: program.programId,
+        statusIndex: testStatusIndexAccounts[0],
+        statusIndexPage: testStatusIndexPageAccounts[0],
+        customerRegistry: testCustomerRegistryAccount,
+        escrowLink: testEscrowLinkAccount,
+        redemptionCounter: testRedemptionCounterAccount,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+      },
+      // The testRedemptionInfoAccount has to sign upon its own creation
+      signers: []
+    });
+
+    // Perform the same checks as in the first test
+    assert.equal(1, Number((await getMint(program.provider.connection, testTokenMintAccount)).supply));
+    assert.equal(0, Number((await getAccount(program.provider.connection, testCustomerTokenAccount)).amount));
+    assert.equal(1, Number((await getAccount(program.provider.connection, testBaxusEscrowAccount)).amount));
+
+    // Leave a receipt for the burn, keyed by the mint and the time the redemption was opened
+    const createdAt = (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).createdAt;
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), testTokenMintAccount.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+
+    // The token can only be burned once the asset has been delivered
+    const burnAccounts = {
+      redemptionInfo: testRedemptionInfoAccount,
+      config: testConfigAccount,
+      treasury: program.programId,
+      customerTokenAccount: testCustomerTokenAccount,
+      customerPaymentAccount: program.provider.wallet.publicKey,
+      tokenMintAccount: testTokenMintAccount,
+      baxusEscrowAccount: testBaxusEscrowAccount,
+      statusIndex: testStatusIndexAccounts[0],
+      statusIndexPage: testStatusIndexPageAccounts[0],
+      customerRegistry: testCustomerRegistryAccount,
+      partner: program.programId,
+      authority: program.provider.wallet.publicKey,
+      // The redemption's valuation is below any enhanced approval threshold
+      enhancedApprover: program.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      // No certificates are escrowed, so the other token program isn't needed
+      certificateTokenProgram: program.programId,
+      // The redemption missed its Deposited deadline and its collection doesn't share fees with creators, so the payout accounts are left out
+      invoice: program.programId,
+      feeMint: program.programId,
+      treasuryFeeAccount: program.programId,
+      customerFeeAccount: program.programId,
+      rebateAuthority: program.programId,
+      feeTokenProgram: program.programId,
+      accounting: program.programId,
+      // There's no rebate, so only the bond is donated
+      charity: testCharity,
+      charityFeeAccount: program.programId,
+      assetMetadata: program.programId,
+      // The asset's collection doesn't need the brand to approve burns
+      burnApproval: program.programId,
+      brandAuthority: program.programId,
+      edition: editionAddress(testTokenMintAccount),
+      receipt: receipt,
+      systemProgram: SystemProgram.programId,
+    };
+    let burned = true;
+    try {
+      await program.rpc.burnAssetToken({ accounts: burnAccounts });
+    } catch (err) {
+      burned = false;
+    }
+    assert.ok(!burned);
+
+    // Take the redemption through to Delivered. The invoice opened in the invoice test is still around and already paid, so setting the
+    // jurisdiction again leaves nothing owing
+    const [invoice] = await anchor.web3.PublicKey.findProgramAddress(
+      [testRedemptionInfoAccount.toBuffer(), Buffer.from("invoice")],
+      program.programId,
+    );
+    await program.rpc.setJurisdiction(Array.from(Buffer.from("US")), {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        invoice: invoice,
+        dutyRates: testDutyRatesAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+      },
+    });
+    await program.rpc.commitShipmentDocuments(Array(32).fill(7), {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+      },
+    });
+
+    // Let the one second Deposited deadline pass, so the redemption isn't on time and has no rebate to pay out, and flag the breach, which
+    // lets the customer cancel for now
+    await warpClock(2);
+    await program.rpc.flagSlaBreach({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+      },
+    });
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).penaltyFreeCancellation);
+    const advance = (from: number) => program.rpc.advanceStatus({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        currentStatusIndex: testStatusIndexAccounts[from],
+        currentStatusIndexPage: testStatusIndexPageAccounts[from],
+        nextStatusIndex: testStatusIndexAccounts[from + 1],
+        nextStatusIndexPage: testStatusIndexPageAccounts[from + 1],
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        priorityIndex: program.programId,
+        enhancedApprover: program.programId,
+        hookProgram: program.programId,
+        memoProgram: program.programId,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    for (let from = 0; from < 3; from++) {
+      await advance(from);
+    }
+
+    // Once the asset has shipped the customer has the bottle, so they can't cancel to take the token back as well
+    assert.ok(!(await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).penaltyFreeCancellation);
+    let cancelled = true;
+    try {
+      await program.rpc.cancelRedemption({
+        accounts: {
+          redemptionInfo: testRedemptionInfoAccount,
+          customerTokenAccount: testCustomerTokenAccount,
+          customerPaymentAccount: program.provider.wallet.publicKey,
+          tokenMintAccount: testTokenMintAccount,
+          baxusEscrowAccount: testBaxusEscrowAccount,
+          statusIndex: testStatusIndexAccounts[3],
+          statusIndexPage: testStatusIndexPageAccounts[3],
+          priorityIndex: program.programId,
+          customerRegistry: testCustomerRegistryAccount,
+          config: testConfigAccount,
+          treasury: program.programId,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          receipt: receipt,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        },
+      });
+    } catch (err) {
+      cancelled = false;
+    }
+    assert.ok(!cancelled);
+    assert.equal(1, Number((await getAccount(program.provider.connection, testBaxusEscrowAccount)).amount));
+
+    await advance(3);
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).status.delivered);
+
+    // The asset's collection has no brand authority, so nobody can approve its burn
+    const [burnApproval] = await anchor.web3.PublicKey.findProgramAddress(
+      [testRedemptionInfoAccount.toBuffer(), Buffer.from("burn_approval")],
+      program.programId,
+    );
+    let approved = true;
+    try {
+      await program.rpc.approveBurn({
+        accounts: {
+          burnApproval: burnApproval,
+          redemptionInfo: testRedemptionInfoAccount,
+          tokenMintAccount: testTokenMintAccount,
+          config: testConfigAccount,
+          brandAuthority: program.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        },
+      });
+    } catch (err) {
+      approved = false;
+    }
+    assert.ok(!approved);
+
+    // The customer consents to the burn up front, giving up the dispute window
+    await program.rpc.grantBurnConsent({
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+      },
+    });
+    assert.ok((await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).burnConsent);
+
+    // The customer donates a quarter of their bond to charity
+    await program.rpc.setDonation(2500, {
+      accounts: {
+        redemptionInfo: testRedemptionInfoAccount,
+        tokenMintAccount: testTokenMintAccount,
+        config: testConfigAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+      },
+    });
+    const donatedLamports = (await program.account.redemptionInfo.fetch(testRedemptionInfoAccount)).bondLamports.toNumber() / 4;
+
+    const tx2 = await program.rpc.burnAssetToken({
+      accounts: { ...burnAccounts, statusIndex: testStatusIndexAccounts[4], statusIndexPage: testStatusIndexPageAccounts[4] },
+    });
+
+    // Confirm that the testTokenMintAccount has no more tokens left in circulation
+    assert.equal(0, Number((await getMint(program.provider.connection, testTokenMintAccount)).supply));
+
+    // Check that the testRedemptionInfoAccount and testBaxusEscrowAccount were closed 
+    assert.equal(null, await program.provider.connection.getAccountInfo(testRedemptionInfoAccount));
+    assert.equal(null, await program.provider.connection.getAccountInfo(testBaxusEscrowAccount));
+
+    // Check that the receipt records the burn, with no edition number since the asset isn't a print
+    assert.ok((await program.account.redemptionReceipt.fetch(receipt)).outcome.burned);
+    assert.equal(donatedLamports, (await program.account.redemptionReceipt.fetch(receipt)).donatedLamports.toNumber());
+    assert.equal(donatedLamports, await program.provider.connection.getBalance(testCharity));
+    assert.equal(0, (await program.account.redemptionReceipt.fetch(receipt)).editionNumber.toNumber());
+    // The test asset is a legacy SPL Token mint, so it can't be interest-bearing
+    assert.equal(null, (await program.account.redemptionReceipt.fetch(receipt)).interestRate);
+    testReceiptAccount = receipt;
+
+    console.log("Your transaction signature", tx2);
+  });
+
+  it('Basic test for submit_feedback():', async () => {
+
+    const [reputation] = await anchor.web3.PublicKey.findProgramAddress([Buffer.from("reputation")], program.programId);
+
+    const tx = await program.rpc.submitFeedback(5, Array(32).fill(3), {
+      accounts: {
+        receipt: testReceiptAccount,
+        reputation: reputation,
+        config: testConfigAccount,
+        customer: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    assert.equal(5, (await program.account.redemptionReceipt.fetch(testReceiptAccount)).rating);
+    assert.equal(1, (await program.account.fulfillmentReputation.fetch(reputation)).ratingCounts[4].toNumber());
+
+    console.log("Your transaction signature", tx);
+  });
+
+  // Appraise a new asset and open a redemption for it from the test wallet, returning its redemption_info address
+  const initializeFreshRedemption = async (
+    mint: PublicKey,
+    customerTokenAccount: PublicKey,
+    insure: boolean = false,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID,
+  ) => {
+    const [redemptionInfo] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer(), Buffer.from("redemption")], program.programId);
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    const [appraisal] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer(), Buffer.from("appraisal")], program.programId);
+    const [escrowLink] = await anchor.web3.PublicKey.findProgramAddress([escrow.toBuffer(), Buffer.from("escrow_link")], program.programId);
+
+    await program.rpc.setAppraisal(new anchor.BN(250000), {
+      accounts: {
+        appraisal: appraisal,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        opsAuthority: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    // An insured redemption pays its premium from the test wallet's fee account to itself, since the test wallet is also the insurance pool
+    const feeAccount = insure
+      ? await getAssociatedTokenAddress(testFeeMint, program.provider.wallet.publicKey)
+      : program.programId;
+    const initialize = insure
+      ? (options) => program.rpc.initializeRedemptionV2(testContactCommitment, true, { standard: {} }, Array.from(crypto.randomBytes(32)), options)
+      : (options) => program.rpc.initializeRedemption(testContactCommitment, options);
+
+    await initialize({
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        customerTokenAccount: customerTokenAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        payer: program.provider.wallet.publicKey,
+        tokenMintAccount: mint,
+        baxusEscrowAccount: escrow,
+        freezeAuthority: testFreezeAuthority,
+        config: testConfigAccount,
+        appraisal: appraisal,
+        riskScore: program.programId,
+        customerProfile: program.programId,
+        feeMint: insure ? testFeeMint : program.programId,
+        payerFeeAccount: feeAccount,
+        insurancePoolFeeAccount: feeAccount,
+        feeTokenProgram: insure ? TOKEN_PROGRAM_ID : program.programId,
+        partner: program.programId,
+        partnerAuthority: program.programId,
+        collectionConfig: testCollectionConfigAccount,
+        hookProgram: program.programId,
+        memoProgram: program.programId,
+        statusIndex: testStatusIndexAccounts[0],
+        statusIndexPage: testStatusIndexPageAccounts[0],
+        customerRegistry: testCustomerRegistryAccount,
+        escrowLink: escrowLink,
+        redemptionCounter: testRedemptionCounterAccount,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: tokenProgram,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    return redemptionInfo;
+  };
+
+  // Run an instruction that has to fail, and return the name of the program error it failed with
+  const errorCodeOf = async (instruction: Promise<any>) => {
+    try {
+      await instruction;
+    } catch (err) {
+      return err.error?.errorCode?.code;
+    }
+    return null;
+  };
+
+  // Create a Token-2022 asset mint with the given mint extensions, which initExtensions sets up before the mint itself is initialized
+  const createToken2022Mint = async (
+    extensions: ExtensionType[],
+    initExtensions: (mint: PublicKey) => TransactionInstruction[],
+    freezeAuthority: PublicKey = null,
+  ) => {
+    const mint = Keypair.generate();
+    const space = getMintLen(extensions);
+    await program.provider.sendAndConfirm(new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: program.provider.wallet.publicKey,
+        newAccountPubkey: mint.publicKey,
+        space: space,
+        lamports: await program.provider.connection.getMinimumBalanceForRentExemption(space),
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      ...initExtensions(mint.publicKey),
+      createInitializeMintInstruction(mint.publicKey, 0, program.provider.wallet.publicKey, freezeAuthority, TOKEN_2022_PROGRAM_ID),
+    ), [mint]);
+    return mint.publicKey;
+  };
+
+  // Bill and pay for a redemption opened with initializeFreshRedemption and move it on to Shipped. The duty rate for the US is set in the
+  // invoice test
+  const shipFreshRedemption = async (mint: PublicKey, redemptionInfo: PublicKey) => {
+    const [invoice] = await anchor.web3.PublicKey.findProgramAddress([redemptionInfo.toBuffer(), Buffer.from("invoice")], program.programId);
+    await program.rpc.openInvoice({
+      accounts: {
+        invoice: invoice,
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    await program.rpc.setJurisdiction(Array.from(Buffer.from("US")), {
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        invoice: invoice,
+        dutyRates: testDutyRatesAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+      },
+    });
+    const feeAccount = (await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, testFeeMint, program.provider.wallet.publicKey)).address;
+    await program.rpc.settleInvoice({
+      accounts: {
+        invoice: invoice,
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        feeMint: testFeeMint,
+        partner: program.programId,
+        payerFeeAccount: feeAccount,
+        treasuryFeeAccount: feeAccount,
+        accounting: testAccountingAccount,
+        payer: program.provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+    });
+    await program.rpc.commitShipmentDocuments(Array(32).fill(7), {
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+      },
+    });
+
+    for (let status = 0; status < 3; status++) {
+      await program.rpc.advanceStatus({
+        accounts: {
+          redemptionInfo: redemptionInfo,
+          tokenMintAccount: mint,
+          currentStatusIndex: testStatusIndexAccounts[status],
+          currentStatusIndexPage: testStatusIndexPageAccounts[status],
+          nextStatusIndex: testStatusIndexAccounts[status + 1],
+          nextStatusIndexPage: testStatusIndexPageAccounts[status + 1],
+          config: testConfigAccount,
+          partner: program.programId,
+          authority: program.provider.wallet.publicKey,
+          priorityIndex: program.programId,
+          enhancedApprover: program.programId,
+          hookProgram: program.programId,
+          memoProgram: program.programId,
+          systemProgram: SystemProgram.programId,
+        },
+      });
+    }
+  };
+
+  it('Basic test for initialize_redemption() with a mint that creates every account frozen:', async () => {
+
+    const mint = await createToken2022Mint([ExtensionType.DefaultAccountState], (mint) => [
+      createInitializeDefaultAccountStateInstruction(mint, AccountState.Frozen, TOKEN_2022_PROGRAM_ID),
+    ], program.provider.wallet.publicKey);
+    const customerTokenAccount = await createAssociatedTokenAccount(
+      program.provider.connection,
+      payer,
+      mint,
+      program.provider.wallet.publicKey,
+      undefined,
+      TOKEN_2022_PROGRAM_ID,
+    );
+
+    // The customer's account starts out frozen, so thaw it long enough to mint the token
+    await thawAccount(program.provider.connection, payer, customerTokenAccount, mint, program.provider.wallet.publicKey, [], undefined, TOKEN_2022_PROGRAM_ID);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1, [], undefined, TOKEN_2022_PROGRAM_ID);
+    await freezeAccount(program.provider.connection, payer, customerTokenAccount, mint, program.provider.wallet.publicKey, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+    // The escrow account would be created frozen too, and can't be thawed while the mint's freeze authority is someone else's
+    assert.equal('EscrowFrozenByDefault', await errorCodeOf(initializeFreshRedemption(mint, customerTokenAccount, false, TOKEN_2022_PROGRAM_ID)));
+
+    await setAuthority(
+      program.provider.connection,
+      payer,
+      mint,
+      program.provider.wallet.publicKey,
+      AuthorityType.FreezeAccount,
+      testFreezeAuthority,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    await initializeFreshRedemption(mint, customerTokenAccount, false, TOKEN_2022_PROGRAM_ID);
+
+    // Both accounts were thawed so the token could move into escrow, and the customer's was left thawed for its return
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    const escrowAccount = await getAccount(program.provider.connection, escrow, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(1, Number(escrowAccount.amount));
+    assert.ok(!escrowAccount.isFrozen);
+    assert.ok(!(await getAccount(program.provider.connection, customerTokenAccount, undefined, TOKEN_2022_PROGRAM_ID)).isFrozen);
+  });
+
+  it('Basic test for initialize_redemption() with a legacy mint under a collection policy:', async () => {
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+
+    // A legacy SPL Token mint's collection can't be read, so it's turned away once the config restricts redemptions to a collection
+    const accounts = { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey };
+    await program.rpc.updateConfig({ ...testConfigParams, collection: Keypair.generate().publicKey }, { accounts });
+    assert.equal('CollectionUnknown', await errorCodeOf(initializeFreshRedemption(mint, customerTokenAccount)));
+
+    await program.rpc.updateConfig(testConfigParams, { accounts });
+    await initializeFreshRedemption(mint, customerTokenAccount);
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    assert.equal(1, Number((await getAccount(program.provider.connection, escrow)).amount));
+  });
+
+  it('Basic test for initialize_redemption() with Token-2022 native metadata:', async () => {
+
+    // A Token-2022 asset whose metadata lives in the mint itself, with no collection recorded
+    const metadataAuthority = Keypair.generate().publicKey;
+    const mint = await createToken2022Mint([ExtensionType.MetadataPointer], (mint) => [
+      createInitializeMetadataPointerInstruction(mint, program.provider.wallet.publicKey, mint, TOKEN_2022_PROGRAM_ID),
+    ]);
+    await tokenMetadataInitializeWithRentTransfer(
+      program.provider.connection,
+      payer,
+      mint,
+      metadataAuthority,
+      payer,
+      "Test Bottle",
+      "BTL",
+      "",
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    const customerTokenAccount = await createAssociatedTokenAccount(
+      program.provider.connection,
+      payer,
+      mint,
+      program.provider.wallet.publicKey,
+      undefined,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+    // The config's strict mode turns away metadata that wasn't issued by the BAXUS metadata authority...
+    const accounts = { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey };
+    await program.rpc.updateConfig({ ...testConfigParams, metadataAuthority: Keypair.generate().publicKey }, { accounts });
+    assert.equal('InvalidMetadataAuthority', await errorCodeOf(initializeFreshRedemption(mint, customerTokenAccount, false, TOKEN_2022_PROGRAM_ID)));
+
+    // ...and once it is, assets outside the config's collection
+    await program.rpc.updateConfig({ ...testConfigParams, metadataAuthority: metadataAuthority, collection: Keypair.generate().publicKey }, { accounts });
+    assert.equal('InvalidCollection', await errorCodeOf(initializeFreshRedemption(mint, customerTokenAccount, false, TOKEN_2022_PROGRAM_ID)));
+
+    // Without a collection policy the asset is accepted, and its metadata is snapshotted into the redemption
+    await program.rpc.updateConfig({ ...testConfigParams, metadataAuthority: metadataAuthority }, { accounts });
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount, false, TOKEN_2022_PROGRAM_ID);
+    await program.rpc.updateConfig(testConfigParams, { accounts });
+    const redemption = await program.account.redemptionInfo.fetch(redemptionInfo);
+    assert.equal("Test Bottle", redemption.assetName);
+    assert.equal("BTL", redemption.assetSymbol);
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    assert.equal(1, Number((await getAccount(program.provider.connection, escrow, undefined, TOKEN_2022_PROGRAM_ID)).amount));
+  });
+
+  it('Basic test for initialize_redemption() from a CPI-guarded token account:', async () => {
+
+    const mint = await createToken2022Mint([], () => []);
+
+    // A token account with the CPI guard turned on, which won't let its owner transfer out of it through our program
+    const customerTokenAccount = Keypair.generate();
+    const space = getAccountLen([ExtensionType.CpiGuard]);
+    await program.provider.sendAndConfirm(new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: program.provider.wallet.publicKey,
+        newAccountPubkey: customerTokenAccount.publicKey,
+        space: space,
+        lamports: await program.provider.connection.getMinimumBalanceForRentExemption(space),
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeAccountInstruction(customerTokenAccount.publicKey, mint, program.provider.wallet.publicKey, TOKEN_2022_PROGRAM_ID),
+      createEnableCpiGuardInstruction(customerTokenAccount.publicKey, program.provider.wallet.publicKey, [], TOKEN_2022_PROGRAM_ID),
+    ), [customerTokenAccount]);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount.publicKey, program.provider.wallet.publicKey, 1, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+    assert.equal('CpiGuardEnabled', await errorCodeOf(initializeFreshRedemption(mint, customerTokenAccount.publicKey, false, TOKEN_2022_PROGRAM_ID)));
+
+    // Once the customer approves the escrow account as a delegate for the token, the escrow account moves it itself
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    await approve(program.provider.connection, payer, customerTokenAccount.publicKey, escrow, program.provider.wallet.publicKey, 1, [], undefined, TOKEN_2022_PROGRAM_ID);
+    await initializeFreshRedemption(mint, customerTokenAccount.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    assert.equal(1, Number((await getAccount(program.provider.connection, escrow, undefined, TOKEN_2022_PROGRAM_ID)).amount));
+    assert.equal(0, Number((await getAccount(program.provider.connection, customerTokenAccount.publicKey, undefined, TOKEN_2022_PROGRAM_ID)).amount));
+  });
+
+  it('Basic test for initialize_redemption() with a non-transferable mint:', async () => {
+
+    const mint = await createToken2022Mint([ExtensionType.NonTransferable], (mint) => [
+      createInitializeNonTransferableMintInstruction(mint, TOKEN_2022_PROGRAM_ID),
+    ]);
+    const customerTokenAccount = await createAssociatedTokenAccount(
+      program.provider.connection,
+      payer,
+      mint,
+      program.provider.wallet.publicKey,
+      undefined,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1, [], undefined, TOKEN_2022_PROGRAM_ID);
+
+    // The token could never move into escrow, so the redemption isn't opened and the customer keeps it
+    assert.equal('NonTransferableMint', await errorCodeOf(initializeFreshRedemption(mint, customerTokenAccount, false, TOKEN_2022_PROGRAM_ID)));
+    assert.equal(1, Number((await getAccount(program.provider.connection, customerTokenAccount, undefined, TOKEN_2022_PROGRAM_ID)).amount));
+  });
+
+  it('Basic test for finalize_rejection():', async () => {
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    const createdAt = (await program.account.redemptionInfo.fetch(redemptionInfo)).createdAt;
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), mint.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+
+    await program.rpc.rejectRedemption({ failedKyc: {} }, {
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        priorityIndex: program.programId,
+      },
+    });
+
+    // The token stays in escrow while the customer can still appeal
+    const finalizeAccounts = {
+      redemptionInfo: redemptionInfo,
+      customerTokenAccount: customerTokenAccount,
+      customerPaymentAccount: program.provider.wallet.publicKey,
+      tokenMintAccount: mint,
+      baxusEscrowAccount: escrow,
+      statusIndex: testStatusIndexAccounts[0],
+      statusIndexPage: testStatusIndexPageAccounts[0],
+      priorityIndex: program.programId,
+      auditLog: program.programId,
+      customerRegistry: testCustomerRegistryAccount,
+      config: testConfigAccount,
+      treasury: program.programId,
+      partner: program.programId,
+      authority: program.provider.wallet.publicKey,
+      receipt: receipt,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+    let finalizedEarly = true;
+    try {
+      await program.rpc.finalizeRejection({ accounts: finalizeAccounts });
+    } catch (err) {
+      finalizedEarly = false;
+    }
+    assert.ok(!finalizedEarly);
+
+    // The customer appeals and compliance upholds the rejection, so it can be finalized without waiting out the rest of the window. The
+    // test wallet is both the compliance authority and the admin
+    const appealAccounts = {
+      redemptionInfo: redemptionInfo,
+      tokenMintAccount: mint,
+      config: testConfigAccount,
+      auditLog: testAuditLogAccount,
+      customerPaymentAccount: program.provider.wallet.publicKey,
+    };
+    await program.rpc.appealRejection(Array(32).fill(9), {
+      accounts: { ...appealAccounts, systemProgram: SystemProgram.programId },
+    });
+    await program.rpc.resolveAppeal(true, {
+      accounts: {
+        ...appealAccounts,
+        treasury: program.provider.wallet.publicKey,
+        complianceAuthority: program.provider.wallet.publicKey,
+        admin: program.provider.wallet.publicKey,
+      },
+    });
+    const tx = await program.rpc.finalizeRejection({ accounts: finalizeAccounts });
+
+    // The token went back to the customer, and the receipt records why
+    assert.equal(1, Number((await getAccount(program.provider.connection, customerTokenAccount)).amount));
+    assert.ok((await program.account.redemptionReceipt.fetch(receipt)).outcome.rejected.reason.failedKyc);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for verify_kyc_proof():', async () => {
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+
+    const accounts = {
+      redemptionInfo: redemptionInfo,
+      tokenMintAccount: mint,
+      config: testConfigAccount,
+      customerPaymentAccount: program.provider.wallet.publicKey,
+    };
+    const proof = Array(256).fill(1);
+
+    // The test config doesn't accept zero-knowledge proofs
+    let verifiedWhileDisabled = true;
+    try {
+      await program.rpc.verifyKycProof(proof, { accounts: { ...accounts, kycVerifier: SystemProgram.programId } });
+    } catch (err) {
+      verifiedWhileDisabled = false;
+    }
+    assert.ok(!verifiedWhileDisabled);
+
+    // Once a verifier is configured, no other program can stand in for it
+    const memoProgram = new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+    await program.rpc.updateConfig({ ...testConfigParams, kycVerifier: memoProgram }, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+    let verifiedByImpostor = true;
+    try {
+      await program.rpc.verifyKycProof(proof, { accounts: { ...accounts, kycVerifier: TOKEN_PROGRAM_ID } });
+    } catch (err) {
+      verifiedByImpostor = false;
+    }
+    assert.ok(!verifiedByImpostor);
+
+    // The configured program rejects what isn't a valid proof for it, so the customer stays unverified
+    let verifiedWithBadProof = true;
+    try {
+      await program.rpc.verifyKycProof(proof, { accounts: { ...accounts, kycVerifier: memoProgram } });
+    } catch (err) {
+      verifiedWithBadProof = false;
+    }
+    assert.ok(!verifiedWithBadProof);
+    assert.ok(!(await program.account.redemptionInfo.fetch(redemptionInfo)).kycVerified);
+
+    const tx = await program.rpc.updateConfig(testConfigParams, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for top_up_rent():', async () => {
+
+    // The local validator's rent never goes up, so every account is still rent exempt and there's never a shortfall to pay
+    let toppedUpExempt = true;
+    try {
+      await program.rpc.topUpRent({
+        accounts: { account: testConfigAccount, contributor: program.provider.wallet.publicKey, systemProgram: SystemProgram.programId },
+      });
+    } catch (err) {
+      toppedUpExempt = false;
+    }
+    assert.ok(!toppedUpExempt);
+
+    // Only the program's own accounts and escrow token accounts can be topped up
+    const before = await program.provider.connection.getBalance(testCharity);
+    let toppedUpStranger = true;
+    try {
+      await program.rpc.topUpRent({
+        accounts: { account: testCharity, contributor: program.provider.wallet.publicKey, systemProgram: SystemProgram.programId },
+      });
+    } catch (err) {
+      toppedUpStranger = false;
+    }
+    assert.ok(!toppedUpStranger);
+    assert.equal(before, await program.provider.connection.getBalance(testCharity));
+  });
+
+  it('Basic test for close_orphaned_escrow():', async () => {
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    const accounts = {
+      baxusEscrowAccount: escrow,
+      tokenMintAccount: mint,
+      redemptionInfo: redemptionInfo,
+      config: testConfigAccount,
+      treasury: program.provider.wallet.publicKey,
+      auditLog: testAuditLogAccount,
+      admin: program.provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    // An escrow account holding the token of an open redemption isn't orphaned
+    let closedInUse = true;
+    try {
+      await program.rpc.closeOrphanedEscrow({ accounts });
+    } catch (err) {
+      closedInUse = false;
+    }
+    assert.ok(!closedInUse);
+    assert.equal(1, Number((await getAccount(program.provider.connection, escrow)).amount));
+
+    // Returning the asset closes the escrow account along with the redemption, so there's nothing left behind to close. Every flow in the
+    // program closes the two together, so an orphan can't be produced here to close successfully
+    const createdAt = (await program.account.redemptionInfo.fetch(redemptionInfo)).createdAt;
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), mint.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+    await program.rpc.returnAssetToken({
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        customerTokenAccount: customerTokenAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        tokenMintAccount: mint,
+        baxusEscrowAccount: escrow,
+        statusIndex: testStatusIndexAccounts[0],
+        statusIndexPage: testStatusIndexPageAccounts[0],
+        priorityIndex: program.programId,
+        auditLog: testAuditLogAccount,
+        customerRegistry: testCustomerRegistryAccount,
+        config: testConfigAccount,
+        treasury: program.programId,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        receipt: receipt,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    let closedTwice = true;
+    try {
+      await program.rpc.closeOrphanedEscrow({ accounts });
+    } catch (err) {
+      closedTwice = false;
+    }
+    assert.ok(!closedTwice);
+    assert.equal(null, await program.provider.connection.getAccountInfo(escrow));
+  });
+
+  it('Basic test for close_contact_reveal():', async () => {
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+
+    const [contactReveal] = await anchor.web3.PublicKey.findProgramAddress([redemptionInfo.toBuffer(), Buffer.from("contact")], program.programId);
+    await program.rpc.revealContact(crypto.randomBytes(64), {
+      accounts: {
+        contactReveal: contactReveal,
+        redemptionInfo: redemptionInfo,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    const closeAccounts = {
+      contactReveal: contactReveal,
+      redemptionInfo: redemptionInfo,
+      customerPaymentAccount: program.provider.wallet.publicKey,
+    };
+
+    // Ops may still need the contact details while the redemption is open
+    let closedWhileOpen = true;
+    try {
+      await program.rpc.closeContactReveal({ accounts: closeAccounts });
+    } catch (err) {
+      closedWhileOpen = false;
+    }
+    assert.ok(!closedWhileOpen);
+
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    const createdAt = (await program.account.redemptionInfo.fetch(redemptionInfo)).createdAt;
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), mint.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+    await program.rpc.returnAssetToken({
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        customerTokenAccount: customerTokenAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        tokenMintAccount: mint,
+        baxusEscrowAccount: escrow,
+        statusIndex: testStatusIndexAccounts[0],
+        statusIndexPage: testStatusIndexPageAccounts[0],
+        priorityIndex: program.programId,
+        auditLog: testAuditLogAccount,
+        customerRegistry: testCustomerRegistryAccount,
+        config: testConfigAccount,
+        treasury: program.programId,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        receipt: receipt,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    // Once the redemption is closed, the customer can delete their details and get the rent back
+    const tx = await program.rpc.closeContactReveal({ accounts: closeAccounts });
+    assert.equal(null, await program.provider.connection.getAccountInfo(contactReveal));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for pay_insurance_claim():', async () => {
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount, true);
+
+    // The test wallet is both the insurance pool and the customer, so the pool pays out from a separate fee account to see the tokens move
+    const poolFeeAccount = await createAccount(program.provider.connection, payer, testFeeMint, program.provider.wallet.publicKey, Keypair.generate());
+    await mintTo(program.provider.connection, payer, testFeeMint, poolFeeAccount, program.provider.wallet.publicKey, 2500 * 1000000);
+    const customerFeeAccount = (await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, testFeeMint, program.provider.wallet.publicKey)).address;
+    const claimAccounts = {
+      redemptionInfo: redemptionInfo,
+      tokenMintAccount: mint,
+      config: testConfigAccount,
+      accounting: testAccountingAccount,
+      feeMint: testFeeMint,
+      insurancePoolFeeAccount: poolFeeAccount,
+      customerFeeAccount: customerFeeAccount,
+      insurancePool: program.provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    // Only an asset lost in transit can be claimed on
+    let claimedBeforeShipping = true;
+    try {
+      await program.rpc.payInsuranceClaim({ accounts: claimAccounts });
+    } catch (err) {
+      claimedBeforeShipping = false;
+    }
+    assert.ok(!claimedBeforeShipping);
+
+    await shipFreshRedemption(mint, redemptionInfo);
+    const tx = await program.rpc.payInsuranceClaim({ accounts: claimAccounts });
+
+    // The pool paid out the $2,500 coverage, and the claim can't be paid twice
+    assert.equal(0, Number((await getAccount(program.provider.connection, poolFeeAccount)).amount));
+    assert.ok((await program.account.redemptionInfo.fetch(redemptionInfo)).insuranceClaimed);
+    let claimedTwice = true;
+    try {
+      await program.rpc.payInsuranceClaim({ accounts: claimAccounts });
+    } catch (err) {
+      claimedTwice = false;
+    }
+    assert.ok(!claimedTwice);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for record_condition_report():', async () => {
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+    await shipFreshRedemption(mint, redemptionInfo);
+
+    const [conditionLog] = await anchor.web3.PublicKey.findProgramAddress([redemptionInfo.toBuffer(), Buffer.from("condition_log")], program.programId);
+    const report = {
+      windowStart: new anchor.BN(1000),
+      windowEnd: new anchor.BN(2000),
+      minTemperatureCentiC: 1200,
+      maxTemperatureCentiC: 1800,
+      maxHumidityBps: 6500,
+      excursionSecs: 0,
+      readingsHash: Array(32).fill(5),
+    };
+    const accounts = {
+      conditionLog: conditionLog,
+      redemptionInfo: redemptionInfo,
+      tokenMintAccount: mint,
+      config: testConfigAccount,
+      oracle: program.provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+
+    // Only registered condition oracles can report
+    let reportedUnregistered = true;
+    try {
+      await program.rpc.recordConditionReport(report, { accounts });
+    } catch (err) {
+      reportedUnregistered = false;
+    }
+    assert.ok(!reportedUnregistered);
+
+    await program.rpc.updateConfig({ ...testConfigParams, conditionOracles: [program.provider.wallet.publicKey] }, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    // A report's window can't end before it starts
+    let reportedBackwards = true;
+    try {
+      await program.rpc.recordConditionReport({ ...report, windowEnd: new anchor.BN(500) }, { accounts });
+    } catch (err) {
+      reportedBackwards = false;
+    }
+    assert.ok(!reportedBackwards);
+
+    const tx = await program.rpc.recordConditionReport(report, { accounts });
+    const reports = (await program.account.conditionLog.fetch(conditionLog)).reports;
+    assert.equal(1, reports.length);
+    assert.ok(reports[0].oracle.equals(program.provider.wallet.publicKey));
+    assert.equal(1800, reports[0].maxTemperatureCentiC);
+
+    await program.rpc.updateConfig(testConfigParams, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for purge_archived():', async () => {
+
+    // Keep burned redemptions archived for a second
+    await program.rpc.updateConfig({ ...testConfigParams, archiveRetentionSecs: new anchor.BN(1) }, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+
+    // Let the one second Deposited deadline pass, so the redemption isn't on time and has no rebate to pay out, then deliver it
+    await warpClock(2);
+    await shipFreshRedemption(mint, redemptionInfo);
+    await program.rpc.advanceStatus({
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        currentStatusIndex: testStatusIndexAccounts[3],
+        currentStatusIndexPage: testStatusIndexPageAccounts[3],
+        nextStatusIndex: testStatusIndexAccounts[4],
+        nextStatusIndexPage: testStatusIndexPageAccounts[4],
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        priorityIndex: program.programId,
+        enhancedApprover: program.programId,
+        hookProgram: program.programId,
+        memoProgram: program.programId,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    await program.rpc.grantBurnConsent({
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+      },
+    });
+
+    const purgeAccounts = {
+      redemptionInfo: redemptionInfo,
+      tokenMintAccount: mint,
+      customerPaymentAccount: program.provider.wallet.publicKey,
+      config: testConfigAccount,
+    };
+
+    // Only archived redemptions can be purged
+    let purgedOpen = true;
+    try {
+      await program.rpc.purgeArchived({ accounts: purgeAccounts });
+    } catch (err) {
+      purgedOpen = false;
+    }
+    assert.ok(!purgedOpen);
+
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    const createdAt = (await program.account.redemptionInfo.fetch(redemptionInfo)).createdAt;
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), mint.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+    await program.rpc.burnAssetToken({
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        config: testConfigAccount,
+        treasury: program.programId,
+        customerTokenAccount: customerTokenAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        tokenMintAccount: mint,
+        baxusEscrowAccount: escrow,
+        statusIndex: testStatusIndexAccounts[4],
+        statusIndexPage: testStatusIndexPageAccounts[4],
+        customerRegistry: testCustomerRegistryAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        enhancedApprover: program.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        certificateTokenProgram: program.programId,
+        invoice: program.programId,
+        feeMint: program.programId,
+        treasuryFeeAccount: program.programId,
+        customerFeeAccount: program.programId,
+        rebateAuthority: program.programId,
+        feeTokenProgram: program.programId,
+        accounting: program.programId,
+        // The customer doesn't donate, so the charity isn't needed either
+        charity: program.programId,
+        charityFeeAccount: program.programId,
+        assetMetadata: program.programId,
+        burnApproval: program.programId,
+        brandAuthority: program.programId,
+        edition: editionAddress(mint),
+        receipt: receipt,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    // The burned redemption is kept as an archived record until the retention period is up, and then anyone can purge it
+    assert.ok((await program.account.redemptionInfo.fetch(redemptionInfo)).archivedAt.toNumber() > 0);
+    await warpClock(2);
+    const tx = await program.rpc.purgeArchived({ accounts: purgeAccounts });
+    assert.equal(null, await program.provider.connection.getAccountInfo(redemptionInfo));
+
+    await program.rpc.updateConfig(testConfigParams, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for expire_redemption():', async () => {
+
+    // Let redemptions expire a second after they're opened
+    await program.rpc.updateConfig({ ...testConfigParams, redemptionTtl: new anchor.BN(1) }, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    // Open one redemption to leave untouched, and another for BAXUS to start working on
+    const openRedemption = async () => {
+      const mint = await createMint(
+        program.provider.connection,
+        payer,
+        program.provider.wallet.publicKey,
+        null,
+        0);
+      const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+      await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+      const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+      const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+      const createdAt = (await program.account.redemptionInfo.fetch(redemptionInfo)).createdAt;
+      const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from("receipt"), mint.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+        program.programId,
+      );
+      return { mint, customerTokenAccount, redemptionInfo, escrow, receipt };
+    };
+    const untouched = await openRedemption();
+    const started = await openRedemption();
+    const rejected = await openRedemption();
+
+    await program.rpc.updateConfig(testConfigParams, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    await program.rpc.advanceStatus({
+      accounts: {
+        redemptionInfo: started.redemptionInfo,
+        tokenMintAccount: started.mint,
+        currentStatusIndex: testStatusIndexAccounts[0],
+        currentStatusIndexPage: testStatusIndexPageAccounts[0],
+        nextStatusIndex: testStatusIndexAccounts[1],
+        nextStatusIndexPage: testStatusIndexPageAccounts[1],
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        priorityIndex: program.programId,
+        enhancedApprover: program.programId,
+        hookProgram: program.programId,
+        memoProgram: program.programId,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    // BAXUS rejects the third, which has to go back through finalize_rejection rather than expire
+    await program.rpc.rejectRedemption({ unsupportedJurisdiction: {} }, {
+      accounts: {
+        redemptionInfo: rejected.redemptionInfo,
+        tokenMintAccount: rejected.mint,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        priorityIndex: program.programId,
+      },
+    });
+
+    // Make sure all three deadlines have passed
+    await warpClock(2);
+
+    const expireAccounts = (redemption, statusIndex: PublicKey, statusIndexPage: PublicKey) => ({
+      redemptionInfo: redemption.redemptionInfo,
+      customerTokenAccount: redemption.customerTokenAccount,
+      customerPaymentAccount: program.provider.wallet.publicKey,
+      tokenMintAccount: redemption.mint,
+      baxusEscrowAccount: redemption.escrow,
+      config: testConfigAccount,
+      treasury: program.provider.wallet.publicKey,
+      statusIndex: statusIndex,
+      statusIndexPage: statusIndexPage,
+      priorityIndex: program.programId,
+      customerRegistry: testCustomerRegistryAccount,
+      receipt: redemption.receipt,
+      payer: program.provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    });
+
+    // BAXUS has moved the second redemption on, so it can't expire even though its deadline has passed
+    let expired = true;
+    try {
+      await program.rpc.expireRedemption({ accounts: expireAccounts(started, testStatusIndexAccounts[1], testStatusIndexPageAccounts[1]) });
+    } catch (err) {
+      expired = false;
+    }
+    assert.ok(!expired);
+
+    // The rejected redemption can't expire either, and its token stays in escrow
+    let expiredRejected = true;
+    try {
+      await program.rpc.expireRedemption({ accounts: expireAccounts(rejected, testStatusIndexAccounts[0], testStatusIndexPageAccounts[0]) });
+    } catch (err) {
+      expiredRejected = false;
+    }
+    assert.ok(!expiredRejected);
+    assert.ok((await program.account.redemptionInfo.fetch(rejected.redemptionInfo)).rejection.unsupportedJurisdiction);
+    assert.equal(1, Number((await getAccount(program.provider.connection, rejected.escrow)).amount));
+
+    const tx = await program.rpc.expireRedemption({ accounts: expireAccounts(untouched, testStatusIndexAccounts[0], testStatusIndexPageAccounts[0]) });
+
+    // The token went back to the customer, and the receipt records that the redemption expired
+    assert.equal(1, Number((await getAccount(program.provider.connection, untouched.customerTokenAccount)).amount));
+    assert.equal(null, await program.provider.connection.getAccountInfo(untouched.redemptionInfo));
+    assert.ok((await program.account.redemptionReceipt.fetch(untouched.receipt)).outcome.expired);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for self_return():', async () => {
+
+    // Let customers take their token back once BAXUS hasn't moved a redemption on for a second
+    await program.rpc.updateConfig({ ...testConfigParams, deadManSecs: new anchor.BN(1) }, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    const createdAt = (await program.account.redemptionInfo.fetch(redemptionInfo)).createdAt;
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), mint.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+
+    // Make sure BAXUS has left the redemption alone for longer than dead_man_secs
+    await warpClock(2);
+
+    const tx = await program.rpc.selfReturn({
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        customerTokenAccount: customerTokenAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+        tokenMintAccount: mint,
+        baxusEscrowAccount: escrow,
+        statusIndex: testStatusIndexAccounts[0],
+        statusIndexPage: testStatusIndexPageAccounts[0],
+        priorityIndex: program.programId,
+        customerRegistry: testCustomerRegistryAccount,
+        config: testConfigAccount,
+        treasury: program.programId,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        receipt: receipt,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    await program.rpc.updateConfig(testConfigParams, {
+      accounts: { config: testConfigAccount, auditLog: testAuditLogAccount, admin: program.provider.wallet.publicKey },
+    });
+
+    // The token went back to the customer without any BAXUS signature
+    assert.equal(1, Number((await getAccount(program.provider.connection, customerTokenAccount)).amount));
+    assert.equal(null, await program.provider.connection.getAccountInfo(redemptionInfo));
+    assert.ok((await program.account.redemptionReceipt.fetch(receipt)).outcome.returned);
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Basic test for start_buyback_payout() and claim_tranche():', async () => {
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+
+    const [escrow] = await anchor.web3.PublicKey.findProgramAddress([mint.toBuffer()], program.programId);
+    const createdAt = (await program.account.redemptionInfo.fetch(redemptionInfo)).createdAt;
+    const [receipt] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("receipt"), mint.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+    // The test wallet is also the cold vault authority
+    const coldVaultAccount = await getAssociatedTokenAddress(mint, program.provider.wallet.publicKey);
+    const feeAccount = await getAssociatedTokenAddress(testFeeMint, program.provider.wallet.publicKey);
+    const [buybackPayout] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("buyback_payout"), mint.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+    const [payoutEscrow] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("buyback_escrow"), mint.toBuffer(), createdAt.toArrayLike(Buffer, "le", 8)],
+      program.programId,
+    );
+    const buybackAccounts = {
+      buybackPayout: buybackPayout,
+      redemptionInfo: redemptionInfo,
+      customerPaymentAccount: program.provider.wallet.publicKey,
+      tokenMintAccount: mint,
+      baxusEscrowAccount: escrow,
+      coldVaultAccount: coldVaultAccount,
+      coldVaultAuthority: program.provider.wallet.publicKey,
+      statusIndex: testStatusIndexAccounts[0],
+      statusIndexPage: testStatusIndexPageAccounts[0],
+      priorityIndex: program.programId,
+      customerRegistry: testCustomerRegistryAccount,
+      config: testConfigAccount,
+      treasury: program.programId,
+      receipt: receipt,
+      feeMint: testFeeMint,
+      opsFeeAccount: feeAccount,
+      payoutEscrow: payoutEscrow,
+      opsAuthority: program.provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      feeTokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    // The asset was appraised at $2,500, so it can't be bought back for more
+    let overpaid = true;
+    try {
+      await program.rpc.startBuybackPayout(new anchor.BN(250001), 2, new anchor.BN(24 * 60 * 60), { accounts: buybackAccounts });
+    } catch (err) {
+      overpaid = false;
+    }
+    assert.ok(!overpaid);
+
+    // Buy the asset back for $100, paid in two tranches a day apart
+    await program.rpc.startBuybackPayout(new anchor.BN(10000), 2, new anchor.BN(24 * 60 * 60), { accounts: buybackAccounts });
+    assert.equal(100 * 1000000, Number((await getAccount(program.provider.connection, payoutEscrow)).amount));
+
+    // The token is in the cold vault and the redemption is closed, so it can't be handed back while the payout runs
+    assert.equal(1, Number((await getAccount(program.provider.connection, coldVaultAccount)).amount));
+    assert.equal(null, await program.provider.connection.getAccountInfo(redemptionInfo));
+    assert.equal(10000, (await program.account.redemptionReceipt.fetch(receipt)).outcome.boughtBack.amountUsdCents.toNumber());
+
+    // Only the first tranche has unlocked - the test wallet is both the ops authority and the customer
+    const tx = await program.rpc.claimTranche({
+      accounts: {
+        buybackPayout: buybackPayout,
+        payoutEscrow: payoutEscrow,
+        config: testConfigAccount,
+        feeMint: testFeeMint,
+        customerFeeAccount: feeAccount,
+        customer: program.provider.wallet.publicKey,
+        funder: program.provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+    });
+    assert.equal(1, (await program.account.buybackPayout.fetch(buybackPayout)).tranchesClaimed);
+    assert.equal(50 * 1000000, Number((await getAccount(program.provider.connection, payoutEscrow)).amount));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Mock price test for settle_invoice_in_currency():', async () => {
+
+    // Whitelist a token worth exactly $2, priced by a mock price feed
+    const paymentMint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      6);
+    const [mockPriceFeed] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("mock_price"), paymentMint.toBuffer()],
+      program.programId,
+    );
+    // $2.00 as a price of 200 with an exponent of -2, and no confidence interval
+    const mockPrice = Buffer.alloc(20);
+    new anchor.BN(200).toArrayLike(Buffer, "le", 8).copy(mockPrice, 0);
+    mockPrice.writeInt32LE(-2, 16);
+    await program.provider.sendAndConfirm(new Transaction().add(mockInstruction('set_mock_price', mockPrice, [
+      { pubkey: mockPriceFeed, isSigner: false, isWritable: true },
+      { pubkey: paymentMint, isSigner: false, isWritable: false },
+      { pubkey: testConfigAccount, isSigner: false, isWritable: false },
+      { pubkey: program.provider.wallet.publicKey, isSigner: true, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ])));
+    const [feeCurrency] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("fee_currency"), paymentMint.toBuffer()],
+      program.programId,
+    );
+    await program.rpc.setFeeCurrency(mockPriceFeed, new anchor.BN(60), {
+      accounts: {
+        feeCurrency: feeCurrency,
+        paymentMint: paymentMint,
+        config: testConfigAccount,
+        auditLog: testAuditLogAccount,
+        admin: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+    const [invoice] = await anchor.web3.PublicKey.findProgramAddress([redemptionInfo.toBuffer(), Buffer.from("invoice")], program.programId);
+    await program.rpc.openInvoice({
+      accounts: {
+        invoice: invoice,
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    // The test wallet is the treasury, so pay from a separate token account to see the tokens move
+    const payerTokenAccount = await createAccount(program.provider.connection, payer, paymentMint, program.provider.wallet.publicKey, Keypair.generate());
+    await mintTo(program.provider.connection, payer, paymentMint, payerTokenAccount, program.provider.wallet.publicKey, 1000 * 1000000);
+    const treasuryTokenAccount = await getAssociatedTokenAddress(paymentMint, program.provider.wallet.publicKey);
+    const settle = (maxAmount: number) => program.rpc.settleInvoiceInCurrency(new anchor.BN(maxAmount), {
+      accounts: {
+        invoice: invoice,
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        accounting: testAccountingAccount,
+        feeCurrency: feeCurrency,
+        paymentMint: paymentMint,
+        priceFeed: mockPriceFeed,
+        partner: program.programId,
+        payerTokenAccount: payerTokenAccount,
+        treasuryTokenAccount: treasuryTokenAccount,
+        treasury: program.provider.wallet.publicKey,
+        payer: program.provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    // A single base unit is far less than the invoice is worth, so the slippage cap stops the payment
+    let settledUnderCap = true;
+    try {
+      await settle(1);
+    } catch (err) {
+      settledUnderCap = false;
+    }
+    assert.ok(!settledUnderCap);
+
+    const tx = await settle(1000 * 1000000);
+
+    // At $2 a token, every US cent costs 0.005 tokens, or 5,000 base units with 6 decimals
+    const paid = (await program.account.invoice.fetch(invoice)).paidUsdCents.toNumber();
+    assert.ok(paid > 0);
+    assert.equal(paid * 5000, Number((await getAccount(program.provider.connection, treasuryTokenAccount)).amount));
+    assert.equal(1000 * 1000000 - paid * 5000, Number((await getAccount(program.provider.connection, payerTokenAccount)).amount));
+
+    console.log("Your transaction signature", tx);
+  });
+
+  it('Warped clock test for settle_invoice() and advance_status():', async () => {
+
+    // Open a fresh redemption, since the test asset has been burned
+    const mint = await createMint(
+      program.provider.connection,
+      payer,
+      program.provider.wallet.publicKey,
+      null,
+      0);
+    const customerTokenAccount = await createAssociatedTokenAccount(program.provider.connection, payer, mint, program.provider.wallet.publicKey);
+    await mintTo(program.provider.connection, payer, mint, customerTokenAccount, program.provider.wallet.publicKey, 1);
+    const redemptionInfo = await initializeFreshRedemption(mint, customerTokenAccount);
+    const [invoice] = await anchor.web3.PublicKey.findProgramAddress([redemptionInfo.toBuffer(), Buffer.from("invoice")], program.programId);
+
+    await program.rpc.openInvoice({
+      accounts: {
+        invoice: invoice,
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+
+    // The duty rate for the US was set in the invoice test
+    await program.rpc.setJurisdiction(Array.from(Buffer.from("US")), {
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        invoice: invoice,
+        dutyRates: testDutyRatesAccount,
+        customerPaymentAccount: program.provider.wallet.publicKey,
+      },
+    });
+
+    await program.rpc.commitShipmentDocuments(Array(32).fill(7), {
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+      },
+    });
+
+    const feeAccount = (await getOrCreateAssociatedTokenAccount(program.provider.connection, payer, testFeeMint, program.provider.wallet.publicKey)).address;
+    const settleAccounts = {
+      invoice: invoice,
+      redemptionInfo: redemptionInfo,
+      tokenMintAccount: mint,
+      config: testConfigAccount,
+      feeMint: testFeeMint,
+      partner: program.programId,
+      payerFeeAccount: feeAccount,
+      treasuryFeeAccount: feeAccount,
+      accounting: testAccountingAccount,
+      payer: program.provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    await program.rpc.settleInvoice({ accounts: settleAccounts });
+
+    const advance = (from: number) => program.rpc.advanceStatus({
+      accounts: {
+        redemptionInfo: redemptionInfo,
+        tokenMintAccount: mint,
+        currentStatusIndex: testStatusIndexAccounts[from],
+        currentStatusIndexPage: testStatusIndexPageAccounts[from],
+        nextStatusIndex: testStatusIndexAccounts[from + 1],
+        nextStatusIndexPage: testStatusIndexPageAccounts[from + 1],
+        config: testConfigAccount,
+        partner: program.programId,
+        authority: program.provider.wallet.publicKey,
+        priorityIndex: program.programId,
+        enhancedApprover: program.programId,
+        hookProgram: program.programId,
+        memoProgram: program.programId,
+        systemProgram: SystemProgram.programId,
+      },
+    });
+    await advance(0);
+
+    // KycApproved has no SLA deadline, so all three warped days count as storage
+    await warpClock(3 * 24 * 60 * 60);
+    await advance(1);
+
+    // The storage accrued since the invoice was settled has to be paid before the asset ships
+    let shipped = true;
+    try {
+      await advance(2);
+    } catch (err) {
+      shipped = false;
+    }
+    assert.ok(!shipped);
+
+    // Settling again bills storage up to the warped clock, so the redemption can ship
+    await program.rpc.settleInvoice({ accounts: settleAccounts });
+    assert.equal(3, (await program.account.redemptionInfo.fetch(redemptionInfo)).storageDaysBilled.toNumber());
+
+    const tx = await advance(2);
+    assert.ok((await program.account.redemptionInfo.fetch(redemptionInfo)).status.shipped);
+
+    console.log("Your transaction signature", tx);
   });
 
 });